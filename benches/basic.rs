@@ -24,3 +24,20 @@ fn bench_generate_get_id_by_generator_real_time_version(b: &mut Bencher) {
     let mut snowflake_id_generator = SnowflakeIdGenerator::new_from_ip(ip);
     b.iter(|| snowflake_id_generator.real_time_generate());
 }
+
+#[cfg(feature = "quanta")]
+#[bench]
+fn bench_generate_get_id_by_generator_real_time_version_quanta(b: &mut Bencher) {
+    let ip = "102.65.2.123".to_string();
+    let mut snowflake_id_generator = SnowflakeIdGenerator::new_from_ip(ip)
+        .with_time_source(snowflake::TimeSource::Quanta);
+    b.iter(|| snowflake_id_generator.real_time_generate());
+}
+
+#[bench]
+fn bench_decode_slice(b: &mut Bencher) {
+    let ip = "102.65.2.123".to_string();
+    let mut snowflake_id_generator = SnowflakeIdGenerator::new_from_ip(ip);
+    let ids: Vec<i64> = (0..10000).map(|_| snowflake_id_generator.generate()).collect();
+    b.iter(|| snowflake::decode_slice(&ids));
+}
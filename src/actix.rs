@@ -0,0 +1,131 @@
+//! Request-id middleware and extractor for actix-web.
+//!
+//! ```no_run
+//! use actix_web::{get, App, HttpServer};
+//! use snowflake::actix::{RequestId, RequestIdMiddleware};
+//! use snowflake::{SharedGenerator, SnowflakeIdGenerator};
+//!
+//! #[get("/")]
+//! async fn index(request_id: RequestId) -> String {
+//!     format!("request {}", request_id.id().0)
+//! }
+//!
+//! # async fn run() -> std::io::Result<()> {
+//! let generator = SharedGenerator::new(SnowflakeIdGenerator::new(1, 1));
+//! HttpServer::new(move || {
+//!     App::new()
+//!         .wrap(RequestIdMiddleware::new(generator.clone()))
+//!         .service(index)
+//! })
+//! .bind(("127.0.0.1", 8080))?
+//! .run()
+//! .await
+//! # }
+//! ```
+
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+
+use actix_web::dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::error::ErrorInternalServerError;
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
+
+use crate::{SharedGenerator, SnowflakeId};
+
+/// Name of the response header carrying the request id.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// The id assigned to the current request by `RequestIdMiddleware`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RequestId(SnowflakeId);
+
+impl RequestId {
+    pub fn id(&self) -> SnowflakeId {
+        self.0
+    }
+}
+
+impl FromRequest for RequestId {
+    type Error = Error;
+    type Future = Ready<Result<RequestId, Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<RequestId>()
+                .copied()
+                .ok_or_else(|| ErrorInternalServerError("RequestIdMiddleware is not installed")),
+        )
+    }
+}
+
+/// Middleware assigning a snowflake id to every request.
+///
+/// The id is available to handlers through the `RequestId` extractor and
+/// is echoed back in the `X-Request-Id` response header.
+#[derive(Clone, Debug)]
+pub struct RequestIdMiddleware {
+    generator: SharedGenerator,
+}
+
+impl RequestIdMiddleware {
+    pub fn new(generator: SharedGenerator) -> RequestIdMiddleware {
+        RequestIdMiddleware { generator }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestIdMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestIdService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestIdService {
+            service,
+            generator: self.generator.clone(),
+        }))
+    }
+}
+
+/// The service produced by `RequestIdMiddleware`.
+pub struct RequestIdService<S> {
+    service: S,
+    generator: SharedGenerator,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestIdService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<ServiceResponse<B>, Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let id = SnowflakeId(self.generator.generate());
+        req.extensions_mut().insert(RequestId(id));
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let mut res = fut.await?;
+            res.headers_mut().insert(
+                HeaderName::from_static(REQUEST_ID_HEADER),
+                HeaderValue::from(id.0),
+            );
+            Ok(res)
+        })
+    }
+}
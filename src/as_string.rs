@@ -0,0 +1,149 @@
+//! Serialize ids as decimal strings.
+//!
+//! JavaScript numbers lose precision past 2^53, which most ids are. Use the
+//! module with `#[serde(with = "snowflake::as_string")]` on `i64` or
+//! `SnowflakeId` fields, or wrap ids in `SnowflakeIdStr`. Either way ids
+//! are written as strings and read back from strings or numbers.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct User {
+//!     #[serde(with = "snowflake::as_string")]
+//!     id: i64,
+//! }
+//!
+//! let json = serde_json::to_string(&User { id: 6725650666386976769 }).unwrap();
+//! assert_eq!(json, r#"{"id":"6725650666386976769"}"#);
+//!
+//! let user: User = serde_json::from_str(r#"{"id":6725650666386976769}"#).unwrap();
+//! assert_eq!(user.id, 6725650666386976769);
+//! ```
+
+use std::fmt;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::SnowflakeId;
+
+/// The `SnowflakeIdStr` type is a `SnowflakeId` serialized as a string.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SnowflakeIdStr(pub SnowflakeId);
+
+/// Serialize an id as a decimal string.
+pub fn serialize<T, S>(id: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Copy + Into<i64>,
+    S: Serializer,
+{
+    serializer.collect_str(&(*id).into())
+}
+
+/// Deserialize an id from a decimal string or a number.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: From<i64>,
+    D: Deserializer<'de>,
+{
+    deserializer
+        .deserialize_any(IdVisitor { base62: false })
+        .map(T::from)
+}
+
+impl Serialize for SnowflakeIdStr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SnowflakeIdStr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<SnowflakeIdStr, D::Error> {
+        deserialize(deserializer).map(SnowflakeIdStr)
+    }
+}
+
+/// Ids are written as numbers.
+impl Serialize for SnowflakeId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(self.0)
+    }
+}
+
+/// Ids are read from numbers, decimal strings, or base62 strings as
+/// written by `SnowflakeId::to_base62`, whichever upstream APIs emit.
+///
+/// Strings are read as decimal whenever they only hold digits.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::SnowflakeId;
+///
+/// let ids: Vec<SnowflakeId> =
+///     serde_json::from_str(r#"[6725650666386976769, "6725650666386976769", "80pYBbV5CzZ"]"#)
+///         .unwrap();
+///
+/// assert!(ids.iter().all(|&id| id == SnowflakeId(6725650666386976769)));
+/// ```
+impl<'de> Deserialize<'de> for SnowflakeId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<SnowflakeId, D::Error> {
+        deserializer
+            .deserialize_any(IdVisitor { base62: true })
+            .map(SnowflakeId)
+    }
+}
+
+impl From<SnowflakeId> for SnowflakeIdStr {
+    fn from(id: SnowflakeId) -> SnowflakeIdStr {
+        SnowflakeIdStr(id)
+    }
+}
+
+impl From<SnowflakeIdStr> for SnowflakeId {
+    fn from(id: SnowflakeIdStr) -> SnowflakeId {
+        id.0
+    }
+}
+
+struct IdVisitor {
+    // Also accept base62 strings.
+    base62: bool,
+}
+
+impl<'de> Visitor<'de> for IdVisitor {
+    type Value = i64;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.base62 {
+            f.write_str("a snowflake id as an integer, a decimal string or a base62 string")
+        } else {
+            f.write_str("a snowflake id as a decimal string or an integer")
+        }
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<i64, E> {
+        Ok(value)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<i64, E> {
+        if value > i64::MAX as u64 {
+            return Err(E::invalid_value(de::Unexpected::Unsigned(value), &self));
+        }
+        Ok(value as i64)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<i64, E> {
+        let is_decimal = value.bytes().all(|byte| byte.is_ascii_digit());
+
+        let id = if is_decimal || !self.base62 {
+            value.parse().ok()
+        } else {
+            SnowflakeId::from_base62(value).map(|id| id.0)
+        };
+        id.ok_or_else(|| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{AsyncWaitStrategy, SharedGenerator, SnowflakeIdGenerator};
+
+// The wait strategy of `AsyncGenerator::new`, the timer of the enabled
+// runtime.
+#[cfg(feature = "tokio")]
+type DefaultSleep = crate::AsyncSleep;
+#[cfg(all(not(feature = "tokio"), feature = "async-std"))]
+type DefaultSleep = crate::AsyncStdSleep;
+#[cfg(all(not(feature = "tokio"), not(feature = "async-std")))]
+type DefaultSleep = crate::TimerSleep;
+
+/// A cloneable generator for async code.
+///
+/// When the current time unit runs out of ids the task waits with its
+/// `AsyncWaitStrategy`, by default sleeping on a timer instead of spinning,
+/// so the runtime's worker threads are never blocked. The timer is tokio's
+/// with the `tokio` feature, else async-std's with the `async-std` feature,
+/// else a `futures-timer` delay, which works with any runtime.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::{AsyncGenerator, SnowflakeIdGenerator};
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let generator = AsyncGenerator::new(SnowflakeIdGenerator::new(1, 1));
+///
+/// let first = generator.generate().await;
+/// let second = generator.generate().await;
+///
+/// assert!(first < second);
+/// # });
+/// ```
+#[derive(Clone, Debug)]
+pub struct AsyncGenerator<W = DefaultSleep> {
+    shared: SharedGenerator,
+    wait_strategy: W,
+}
+
+impl AsyncGenerator {
+    pub fn new(generator: SnowflakeIdGenerator) -> AsyncGenerator {
+        AsyncGenerator {
+            shared: SharedGenerator::new(generator),
+            wait_strategy: DefaultSleep::default(),
+        }
+    }
+}
+
+impl<W: AsyncWaitStrategy> AsyncGenerator<W> {
+    /// Wait for the next time unit with `wait_strategy` instead.
+    pub fn with_wait_strategy<S: AsyncWaitStrategy>(self, wait_strategy: S) -> AsyncGenerator<S> {
+        AsyncGenerator {
+            shared: self.shared,
+            wait_strategy,
+        }
+    }
+
+    /// Generate an id in real time, waiting asynchronously for the next
+    /// time unit when the current one is exhausted.
+    pub async fn generate(&self) -> i64 {
+        loop {
+            let (id, next_millis) = self.shared.with_generator(|generator| {
+                let time_unit = generator.layout.time_unit();
+                let id = generator.try_generate();
+                (id, time_unit.to_millis(generator.last_time_millis + 1))
+            });
+
+            match id {
+                Some(id) => return id.0,
+                None => self.wait_strategy.wait_until(next_millis).await,
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Audit collections of ids.
+//!
+//! Feed the ids exported from a fleet to `Audit::run` to spot duplicates,
+//! machines issuing more ids than expected, and clocks that went backwards
+//! or stalled.
+//!
+//! # Examples
+//!
+//! ```
+//! use snowflake::audit::Audit;
+//! use snowflake::SnowflakeIdGenerator;
+//!
+//! let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+//! let ids: Vec<i64> = (0..100).map(|_| id_generator.real_time_generate()).collect();
+//!
+//! let report = Audit::default().run(ids);
+//!
+//! assert!(report.is_clean());
+//! assert_eq!(report.per_machine[&id_generator.machine_bits], 100);
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::Layout;
+
+/// The `Audit` type holds the settings of an audit.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Audit {
+    layout: Layout,
+    gap_threshold_millis: Option<i64>,
+}
+
+/// The `AuditReport` type is the outcome of `Audit::run`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuditReport {
+    /// Number of ids audited.
+    pub total: usize,
+    /// Ids seen more than once, repeated for every extra occurrence.
+    pub duplicates: Vec<i64>,
+    /// Number of ids per machine bits.
+    pub per_machine: BTreeMap<i64, usize>,
+    /// Ids with a timestamp before the previous id of the same machine.
+    pub regressions: Vec<Anomaly>,
+    /// Ids further than the gap threshold from the previous id of the same machine.
+    pub gaps: Vec<Anomaly>,
+}
+
+/// The `Anomaly` type is a pair of consecutive ids of one machine.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Anomaly {
+    /// Machine bits of both ids.
+    pub machine_bits: i64,
+    /// The id seen before `id`.
+    pub previous: i64,
+    /// The offending id.
+    pub id: i64,
+    /// Milliseconds from `previous` to `id`, negative for regressions.
+    pub millis: i64,
+}
+
+impl Audit {
+    /// Constructs a new `Audit` decoding ids with `layout`.
+    pub fn new(layout: Layout) -> Audit {
+        Audit {
+            layout,
+            gap_threshold_millis: None,
+        }
+    }
+
+    /// Report ids more than `millis` after the previous id of the same machine.
+    pub fn with_gap_threshold(mut self, millis: i64) -> Audit {
+        self.gap_threshold_millis = Some(millis);
+        self
+    }
+
+    /// Audit `ids`, in the order they were issued.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::audit::Audit;
+    ///
+    /// let ids = vec![2 << 22, 1 << 22, 1 << 22];
+    /// let report = Audit::default().run(ids);
+    ///
+    /// assert_eq!(report.duplicates, vec![1 << 22]);
+    /// assert_eq!(report.regressions.len(), 1);
+    /// ```
+    pub fn run<I: IntoIterator<Item = i64>>(&self, ids: I) -> AuditReport {
+        let mut report = AuditReport::default();
+        let mut seen = HashSet::new();
+        let mut last_by_machine: HashMap<i64, (i64, i64)> = HashMap::new();
+
+        for id in ids {
+            report.total += 1;
+
+            if !seen.insert(id) {
+                report.duplicates.push(id);
+            }
+
+            let snowflake = self.layout.decode(id);
+            *report
+                .per_machine
+                .entry(snowflake.machine_bits)
+                .or_insert(0) += 1;
+
+            let last = last_by_machine.insert(snowflake.machine_bits, (id, snowflake.timestamp));
+            if let Some((previous, previous_timestamp)) = last {
+                let anomaly = Anomaly {
+                    machine_bits: snowflake.machine_bits,
+                    previous,
+                    id,
+                    millis: snowflake.timestamp - previous_timestamp,
+                };
+
+                let is_gap = match self.gap_threshold_millis {
+                    Some(threshold) => anomaly.millis > threshold,
+                    None => false,
+                };
+
+                if anomaly.millis < 0 {
+                    report.regressions.push(anomaly);
+                } else if is_gap {
+                    report.gaps.push(anomaly);
+                }
+            }
+        }
+
+        report
+    }
+}
+
+impl AuditReport {
+    /// Whether the audit found neither duplicates nor regressions.
+    pub fn is_clean(&self) -> bool {
+        self.duplicates.is_empty() && self.regressions.is_empty()
+    }
+}
@@ -0,0 +1,200 @@
+//! Command line tool to generate and decode snowflake ids.
+//!
+//! ```text
+//! snowflake generate [COUNT] [--machine-id ID] [--format FORMAT]
+//! snowflake decode ID... [--format FORMAT]
+//! snowflake decode - [--format FORMAT]
+//! ```
+//!
+//! `decode -` reads newline-delimited ids from stdin. Decoded ids are
+//! printed one per line as `id timestamp machine sequence`.
+//!
+//! `--format` picks the output format:
+//! - `table`, the default, separates the columns by tabs, so the output
+//!   composes with `grep` and `awk`.
+//! - `csv` prints a header row, then comma-separated columns.
+//! - `json` prints a JSON object per line, for `jq`. Ids are strings,
+//!   doubles can't hold every 64-bit integer.
+
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::process;
+
+use snowflake::{Layout, SnowflakeIdGenerator};
+
+const USAGE: &str = "usage: snowflake generate [COUNT] [--machine-id ID] [--format FORMAT]
+       snowflake decode ID... [--format FORMAT]
+       snowflake decode - [--format FORMAT]
+FORMAT is one of table, csv, json";
+
+const GENERATE_COLUMNS: &[&str] = &["id"];
+const DECODE_COLUMNS: &[&str] = &["id", "timestamp", "machine", "sequence"];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Format {
+    Table,
+    Csv,
+    Json,
+}
+
+impl Format {
+    fn write_header(self, out: &mut impl Write, columns: &[&str]) -> io::Result<()> {
+        match self {
+            Format::Csv => writeln!(out, "{}", columns.join(",")),
+            Format::Table | Format::Json => Ok(()),
+        }
+    }
+
+    // Write a row of integer values; the first one, the id, is quoted in JSON.
+    fn write_row(self, out: &mut impl Write, columns: &[&str], values: &[i64]) -> io::Result<()> {
+        let values: Vec<String> = values.iter().map(i64::to_string).collect();
+        match self {
+            Format::Table => writeln!(out, "{}", values.join("\t")),
+            Format::Csv => writeln!(out, "{}", values.join(",")),
+            Format::Json => {
+                let fields: Vec<String> = columns
+                    .iter()
+                    .zip(&values)
+                    .enumerate()
+                    .map(|(i, (column, value))| match i {
+                        0 => format!("\"{}\":\"{}\"", column, value),
+                        _ => format!("\"{}\":{}", column, value),
+                    })
+                    .collect();
+                writeln!(out, "{{{}}}", fields.join(","))
+            }
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result =
+        split_format(&args).and_then(|(format, args)| match args.first().map(String::as_str) {
+            Some("generate") => generate(&args[1..], format),
+            Some("decode") => decode(&args[1..], format),
+            _ => Err(USAGE.to_string()),
+        });
+
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn generate(args: &[String], format: Format) -> Result<(), String> {
+    let mut count = 1;
+    let mut machine_id = 0;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--machine-id" => machine_id = parse_arg(args.next(), "--machine-id")?,
+            _ => count = parse_arg(Some(arg), "COUNT")?,
+        }
+    }
+
+    let mut id_generator =
+        SnowflakeIdGenerator::with_machine_id(machine_id).map_err(|error| error.to_string())?;
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    format
+        .write_header(&mut out, GENERATE_COLUMNS)
+        .map_err(|error| error.to_string())?;
+    for _ in 0..count {
+        let id = id_generator.real_time_generate();
+        format
+            .write_row(&mut out, GENERATE_COLUMNS, &[id])
+            .map_err(|error| error.to_string())?;
+    }
+    Ok(())
+}
+
+fn decode(args: &[String], format: Format) -> Result<(), String> {
+    if args.is_empty() {
+        return Err(USAGE.to_string());
+    }
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut invalid = 0;
+
+    format
+        .write_header(&mut out, DECODE_COLUMNS)
+        .map_err(|error| error.to_string())?;
+
+    let mut decode_line = |line: &str| -> io::Result<()> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(());
+        }
+
+        match line.parse::<i64>() {
+            Ok(id) => {
+                let snowflake = Layout::default().decode(id);
+                let values = [
+                    id,
+                    snowflake.timestamp,
+                    snowflake.machine_bits,
+                    i64::from(snowflake.idx),
+                ];
+                format.write_row(&mut out, DECODE_COLUMNS, &values)
+            }
+            Err(_) => {
+                invalid += 1;
+                eprintln!("invalid id: {}", line);
+                Ok(())
+            }
+        }
+    };
+
+    if args == ["-"] {
+        for line in io::stdin().lock().lines() {
+            let line = line.map_err(|error| error.to_string())?;
+            decode_line(&line).map_err(|error| error.to_string())?;
+        }
+    } else {
+        for arg in args {
+            decode_line(arg).map_err(|error| error.to_string())?;
+        }
+    }
+
+    match invalid {
+        0 => Ok(()),
+        invalid => Err(format!("{} invalid ids", invalid)),
+    }
+}
+
+// Take the `--format` option out of the arguments, wherever it is.
+fn split_format(args: &[String]) -> Result<(Format, Vec<String>), String> {
+    let mut format = Format::Table;
+    let mut rest = Vec::with_capacity(args.len());
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = match arg.strip_prefix("--format") {
+            Some("") => args.next().map(String::as_str),
+            Some(value) if value.starts_with('=') => Some(&value[1..]),
+            _ => {
+                rest.push(arg.clone());
+                continue;
+            }
+        };
+
+        format = match value {
+            Some("table") => Format::Table,
+            Some("csv") => Format::Csv,
+            Some("json") => Format::Json,
+            _ => return Err(format!("invalid --format\n{}", USAGE)),
+        };
+    }
+
+    Ok((format, rest))
+}
+
+fn parse_arg<T: std::str::FromStr>(arg: Option<&String>, name: &str) -> Result<T, String> {
+    arg.and_then(|arg| arg.parse().ok())
+        .ok_or_else(|| format!("invalid {}\n{}", name, USAGE))
+}
@@ -0,0 +1,83 @@
+//! Borsh serialization of ids.
+
+use std::io::{self, Read, Write};
+
+use ::borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::{Snowflake, Snowflake128, SnowflakeId, TypedId};
+
+/// Ids are written as a little endian `i64`, like the `i64` they wrap.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::SnowflakeId;
+///
+/// let id = SnowflakeId(6725650666386976769);
+/// let bytes = borsh::to_vec(&id).unwrap();
+///
+/// assert_eq!(bytes, 6725650666386976769i64.to_le_bytes());
+/// assert_eq!(borsh::from_slice::<SnowflakeId>(&bytes).unwrap(), id);
+/// ```
+impl BorshSerialize for SnowflakeId {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.0.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for SnowflakeId {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<SnowflakeId> {
+        i64::deserialize_reader(reader).map(SnowflakeId)
+    }
+}
+
+/// Typed ids are written exactly like the `SnowflakeId` they wrap.
+impl<T> BorshSerialize for TypedId<T> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.id().serialize(writer)
+    }
+}
+
+impl<T> BorshDeserialize for TypedId<T> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<TypedId<T>> {
+        SnowflakeId::deserialize_reader(reader).map(TypedId::new)
+    }
+}
+
+/// Decoded ids are written field by field, like a derived struct.
+impl BorshSerialize for Snowflake {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.timestamp.serialize(writer)?;
+        self.machine_bits.serialize(writer)?;
+        self.idx.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for Snowflake {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Snowflake> {
+        Ok(Snowflake {
+            timestamp: i64::deserialize_reader(reader)?,
+            machine_bits: i64::deserialize_reader(reader)?,
+            idx: u16::deserialize_reader(reader)?,
+        })
+    }
+}
+
+/// 128-bit ids are written field by field, like a derived struct.
+impl BorshSerialize for Snowflake128 {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.timestamp_nanos.serialize(writer)?;
+        self.machine_id.serialize(writer)?;
+        self.sequence.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for Snowflake128 {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Snowflake128> {
+        Ok(Snowflake128 {
+            timestamp_nanos: u64::deserialize_reader(reader)?,
+            machine_id: u32::deserialize_reader(reader)?,
+            sequence: u32::deserialize_reader(reader)?,
+        })
+    }
+}
@@ -0,0 +1,188 @@
+//! Boundary flake ids.
+
+use crate::encoding::{self, BASE62_ALPHABET};
+use crate::{try_get_time_millis, IdGenerator, SnowflakeError};
+
+// The worker id is 48 bits, the size of a MAC address.
+const WORKER_ID_MASK: u64 = (1 << 48) - 1;
+
+// Length of the base62 representation of any 128-bit value.
+const BASE62_MAX_LEN: usize = 22;
+
+/// The `BoundaryFlake` type is an id in the format of Boundary's flake.
+///
+/// 64 bits of milliseconds since the Unix epoch, the 48-bit MAC address of
+/// the worker and a 16-bit auto-increment record, in that order. Lets
+/// services migrating off an Erlang flake server keep reading and minting
+/// the same ids.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BoundaryFlake {
+    pub timestamp: u64,
+    pub worker_id: u64,
+    pub sequence: u16,
+}
+
+/// The `BoundaryFlakeGenerator` type generates `BoundaryFlake` ids.
+#[derive(Copy, Clone, Debug)]
+pub struct BoundaryFlakeGenerator {
+    /// last_time_millis, last time generate id is used times millis.
+    pub last_time_millis: u64,
+
+    /// the MAC address of the worker, as an integer.
+    pub worker_id: u64,
+
+    /// auto-increment record.
+    pub sequence: u16,
+}
+
+impl BoundaryFlake {
+    /// The id as a single integer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::BoundaryFlake;
+    ///
+    /// let id = BoundaryFlake { timestamp: 1, worker_id: 2, sequence: 3 };
+    ///
+    /// assert_eq!(id.to_u128(), 1 << 64 | 2 << 16 | 3);
+    /// assert_eq!(BoundaryFlake::from_u128(id.to_u128()), id);
+    /// ```
+    pub fn to_u128(self) -> u128 {
+        u128::from(self.timestamp) << 64
+            | u128::from(self.worker_id & WORKER_ID_MASK) << 16
+            | u128::from(self.sequence)
+    }
+
+    /// Split an integer into the id fields.
+    pub fn from_u128(value: u128) -> BoundaryFlake {
+        BoundaryFlake {
+            timestamp: (value >> 64) as u64,
+            worker_id: (value >> 16) as u64 & WORKER_ID_MASK,
+            sequence: value as u16,
+        }
+    }
+
+    /// The id as 16 big-endian bytes, flake's binary form.
+    pub fn to_bytes(self) -> [u8; 16] {
+        self.to_u128().to_be_bytes()
+    }
+
+    /// Read an id from 16 big-endian bytes.
+    pub fn from_bytes(bytes: [u8; 16]) -> BoundaryFlake {
+        BoundaryFlake::from_u128(u128::from_be_bytes(bytes))
+    }
+
+    /// The id in base62, without padding, as flake writes it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::BoundaryFlake;
+    ///
+    /// let id = BoundaryFlake { timestamp: 0, worker_id: 0, sequence: 61 };
+    ///
+    /// assert_eq!(id.to_base62(), "z");
+    /// assert_eq!(BoundaryFlake::from_base62("z"), Some(id));
+    /// ```
+    pub fn to_base62(self) -> String {
+        encoding::encode(&self.to_bytes(), BASE62_ALPHABET, 1)
+    }
+
+    /// Parse an id from base62, `None` if the text isn't base62 or doesn't
+    /// fit 128 bits. Zero padded text is accepted too.
+    pub fn from_base62(text: &str) -> Option<BoundaryFlake> {
+        if text.is_empty() || text.len() > BASE62_MAX_LEN {
+            return None;
+        }
+
+        encoding::decode(text, BASE62_ALPHABET).map(BoundaryFlake::from_bytes)
+    }
+
+    /// The worker id as the MAC address it was taken from.
+    pub fn mac_address(self) -> [u8; 6] {
+        let bytes = self.worker_id.to_be_bytes();
+        [bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]]
+    }
+}
+
+impl BoundaryFlakeGenerator {
+    /// Constructs a new `BoundaryFlakeGenerator` for the worker with the
+    /// given MAC address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::BoundaryFlakeGenerator;
+    ///
+    /// let id_generator = BoundaryFlakeGenerator::new([0x02, 0x42, 0xac, 0x12, 0x00, 0x02]);
+    ///
+    /// assert_eq!(id_generator.worker_id, 0x0242_ac12_0002);
+    /// ```
+    pub fn new(mac_address: [u8; 6]) -> BoundaryFlakeGenerator {
+        let mut bytes = [0u8; 8];
+        bytes[2..].copy_from_slice(&mac_address);
+
+        BoundaryFlakeGenerator {
+            last_time_millis: try_get_time_millis().unwrap_or(0) as u64,
+            worker_id: u64::from_be_bytes(bytes),
+            sequence: 0,
+        }
+    }
+
+    /// Generate an id.
+    ///
+    /// The auto-increment record restarts every millisecond. If the clock
+    /// doesn't move forward the record keeps counting, and once it wraps
+    /// around the timestamp moves forward by one millisecond, so ids always
+    /// increase.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the clock reads before the Unix epoch, see `try_generate`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::BoundaryFlakeGenerator;
+    ///
+    /// let mut id_generator = BoundaryFlakeGenerator::new([0, 0, 0, 0, 0, 42]);
+    ///
+    /// let first = id_generator.generate();
+    /// let second = id_generator.generate();
+    ///
+    /// assert!(first < second);
+    /// ```
+    pub fn generate(&mut self) -> BoundaryFlake {
+        self.try_generate()
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `generate`, but fails with `SnowflakeError::ClockBeforeEpoch`
+    /// instead of panicking if the clock reads before the Unix epoch.
+    pub fn try_generate(&mut self) -> Result<BoundaryFlake, SnowflakeError> {
+        let now_millis = try_get_time_millis()? as u64;
+
+        if now_millis > self.last_time_millis {
+            self.last_time_millis = now_millis;
+            self.sequence = 0;
+        } else if self.sequence == u16::MAX {
+            self.last_time_millis += 1;
+            self.sequence = 0;
+        } else {
+            self.sequence += 1;
+        }
+
+        Ok(BoundaryFlake {
+            timestamp: self.last_time_millis,
+            worker_id: self.worker_id,
+            sequence: self.sequence,
+        })
+    }
+}
+
+impl IdGenerator<BoundaryFlake> for BoundaryFlakeGenerator {
+    fn next_id(&mut self) -> Result<BoundaryFlake, SnowflakeError> {
+        self.try_generate()
+    }
+}
@@ -0,0 +1,176 @@
+//! Time partitions of ids.
+
+use crate::explain::civil_from_days;
+use crate::{Layout, SnowflakeId, UnixMillis};
+
+const MILLIS_PER_HOUR: i64 = 60 * 60 * 1000;
+const MILLIS_PER_DAY: i64 = 24 * MILLIS_PER_HOUR;
+
+// 1970-01-01 was a Thursday, weeks start 3 days earlier.
+const DAYS_BEFORE_FIRST_MONDAY: i64 = 3;
+
+/// The `Granularity` type is the width of a time partition, see
+/// `SnowflakeId::bucket` and `Layout::start_of`.
+///
+/// Partitions follow the UTC calendar: hours and days start on the hour
+/// and at midnight, weeks on Monday like ISO weeks, months on the first.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Granularity {
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl Granularity {
+    // Index of the partition holding `millis`, counted from the Unix epoch.
+    fn index(self, millis: i64) -> i64 {
+        let days = millis.div_euclid(MILLIS_PER_DAY);
+        match self {
+            Granularity::Hour => millis.div_euclid(MILLIS_PER_HOUR),
+            Granularity::Day => days,
+            Granularity::Week => (days + DAYS_BEFORE_FIRST_MONDAY).div_euclid(7),
+            Granularity::Month => {
+                let (year, month, _) = civil_from_days(days);
+                (year - 1970) * 12 + i64::from(month) - 1
+            }
+        }
+    }
+
+    // Milliseconds since the Unix epoch at the start of partition `index`.
+    fn start_millis(self, index: i64) -> i64 {
+        match self {
+            Granularity::Hour => index * MILLIS_PER_HOUR,
+            Granularity::Day => index * MILLIS_PER_DAY,
+            Granularity::Week => (index * 7 - DAYS_BEFORE_FIRST_MONDAY) * MILLIS_PER_DAY,
+            Granularity::Month => {
+                let year = 1970 + index.div_euclid(12);
+                let month = index.rem_euclid(12) as u32 + 1;
+                days_from_civil(year, month) * MILLIS_PER_DAY
+            }
+        }
+    }
+}
+
+impl SnowflakeId {
+    /// The time partition of the id, the number of `granularity`
+    /// partitions from the one holding `epoch` to the one holding the id.
+    ///
+    /// Routes records to time partitions straight from their id, without
+    /// decoding dates. Pass the Unix epoch to number partitions from 1970.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// use snowflake::{Granularity, SnowflakeId};
+    ///
+    /// // 2024-06-01T12:00:00.123Z
+    /// let id = SnowflakeId(1_717_243_200_123 << 22 | 0x2A7 << 12 | 17);
+    /// // 2024-01-01T00:00:00Z
+    /// let epoch = UNIX_EPOCH + Duration::from_secs(1_704_067_200);
+    ///
+    /// assert_eq!(id.bucket(Granularity::Month, epoch), 5);
+    /// assert_eq!(id.bucket(Granularity::Day, epoch), 152);
+    /// assert_eq!(id.bucket(Granularity::Hour, epoch), 152 * 24 + 12);
+    /// ```
+    pub fn bucket<T: UnixMillis>(self, granularity: Granularity, epoch: T) -> i64 {
+        granularity.index(self.timestamp()) - granularity.index(epoch.unix_millis())
+    }
+}
+
+impl Layout {
+    /// The first id of the `granularity` partition holding `timestamp`.
+    ///
+    /// With `end_of`, bounds the ids of a calendar period for reporting
+    /// queries over tables keyed by id, `id BETWEEN start AND end`,
+    /// without date to id math at every call site.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// use snowflake::{Granularity, Layout};
+    ///
+    /// let layout = Layout::default();
+    /// // 2024-06-01T12:00:00.123Z
+    /// let date = UNIX_EPOCH + Duration::from_millis(1_717_243_200_123);
+    ///
+    /// // 2024-06-01T00:00:00Z
+    /// assert_eq!(layout.start_of(Granularity::Day, date), 1_717_200_000_000 << 22);
+    /// // 2024-06-01T23:59:59.999Z
+    /// assert_eq!(
+    ///     layout.end_of(Granularity::Day, date),
+    ///     layout.compose(1_717_286_399_999, 0x3FF, 0xFFF)
+    /// );
+    /// ```
+    pub fn start_of<T: UnixMillis>(&self, granularity: Granularity, timestamp: T) -> i64 {
+        let index = granularity.index(timestamp.unix_millis());
+        self.first_id_of(
+            self.time_unit()
+                .from_millis(granularity.start_millis(index)),
+        )
+    }
+
+    /// The last id of the `granularity` partition holding `timestamp`, see
+    /// `start_of`.
+    pub fn end_of<T: UnixMillis>(&self, granularity: Granularity, timestamp: T) -> i64 {
+        let index = granularity.index(timestamp.unix_millis());
+        let next = self.first_id_of(
+            self.time_unit()
+                .from_millis(granularity.start_millis(index + 1)),
+        );
+
+        // Clamped past the timestamp field, the last id is the largest.
+        if next == i64::MAX {
+            next
+        } else {
+            next - 1
+        }
+    }
+
+    /// The first id of the UTC day holding `timestamp`.
+    pub fn start_of_day<T: UnixMillis>(&self, timestamp: T) -> i64 {
+        self.start_of(Granularity::Day, timestamp)
+    }
+
+    /// The last id of the UTC day holding `timestamp`.
+    pub fn end_of_day<T: UnixMillis>(&self, timestamp: T) -> i64 {
+        self.end_of(Granularity::Day, timestamp)
+    }
+
+    /// The first id of the week, starting on Monday, holding `timestamp`.
+    pub fn start_of_week<T: UnixMillis>(&self, timestamp: T) -> i64 {
+        self.start_of(Granularity::Week, timestamp)
+    }
+
+    /// The last id of the week, ending on Sunday, holding `timestamp`.
+    pub fn end_of_week<T: UnixMillis>(&self, timestamp: T) -> i64 {
+        self.end_of(Granularity::Week, timestamp)
+    }
+
+    /// The first id of the UTC month holding `timestamp`.
+    pub fn start_of_month<T: UnixMillis>(&self, timestamp: T) -> i64 {
+        self.start_of(Granularity::Month, timestamp)
+    }
+
+    /// The last id of the UTC month holding `timestamp`.
+    pub fn end_of_month<T: UnixMillis>(&self, timestamp: T) -> i64 {
+        self.end_of(Granularity::Month, timestamp)
+    }
+}
+
+// Days since 1970-01-01 of the first of `month` in `year`, in the proleptic
+// Gregorian calendar, the inverse of `civil_from_days`.
+fn days_from_civil(year: i64, month: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_index = i64::from((month + 9) % 12);
+    let day_of_year = (153 * month_index + 2) / 5;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
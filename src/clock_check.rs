@@ -0,0 +1,228 @@
+//! Clock sanity checks against a trusted time source.
+
+use std::io::{self, ErrorKind};
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Duration;
+
+use crate::{get_time_millis, SnowflakeError};
+
+// Seconds from the NTP epoch, 1900, to the Unix epoch.
+const NTP_UNIX_OFFSET_SECONDS: i64 = 2_208_988_800;
+
+// Leap indicator 0, version 3, client mode.
+const NTP_CLIENT_HEADER: u8 = 0x1b;
+
+/// The `ClockReference` trait measures the local clock against a trusted
+/// one.
+///
+/// Implemented by `NtpClock` and `ChronyClock`, so the source can be
+/// picked at runtime.
+pub trait ClockReference {
+    /// Milliseconds the local clock is ahead of the reference, negative
+    /// if it is behind.
+    fn offset_millis(&self) -> io::Result<i64>;
+}
+
+/// The `NtpClock` type queries an NTP server over SNTP.
+///
+/// The offset is corrected for the network delay, assuming it is the same
+/// both ways.
+#[derive(Clone, Debug)]
+pub struct NtpClock {
+    server: String,
+    timeout: Duration,
+}
+
+/// The `ChronyClock` type reads the offset chronyd tracks, through
+/// `chronyc tracking`.
+///
+/// Asks the local daemon rather than the network, for hosts whose only
+/// route to an NTP server is through chronyd.
+#[derive(Clone, Debug)]
+pub struct ChronyClock {
+    command: PathBuf,
+}
+
+impl NtpClock {
+    /// Constructs a new `NtpClock` querying `server`, a `host:port` pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::NtpClock;
+    ///
+    /// let reference = NtpClock::new("pool.ntp.org:123");
+    /// ```
+    pub fn new(server: impl Into<String>) -> NtpClock {
+        NtpClock {
+            server: server.into(),
+            timeout: Duration::from_secs(1),
+        }
+    }
+
+    /// Give up on the server after `timeout`, one second by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> NtpClock {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl ClockReference for NtpClock {
+    fn offset_millis(&self) -> io::Result<i64> {
+        let server = self
+            .server
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| invalid_data("the NTP server doesn't resolve"))?;
+        let local = if server.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+
+        let socket = UdpSocket::bind(local)?;
+        socket.set_read_timeout(Some(self.timeout))?;
+        socket.connect(server)?;
+
+        let sent = get_time_millis();
+        let mut request = [0; 48];
+        request[0] = NTP_CLIENT_HEADER;
+        request[40..].copy_from_slice(&to_ntp_timestamp(sent).to_be_bytes());
+        socket.send(&request)?;
+
+        let mut response = [0; 48];
+        let len = socket.recv(&mut response)?;
+        let received = get_time_millis();
+        if len < response.len() {
+            return Err(invalid_data("truncated NTP response"));
+        }
+
+        let mode = response[0] & 0x7;
+        if mode != 4 && mode != 5 {
+            return Err(invalid_data("the NTP response isn't from a server"));
+        }
+        if response[1] == 0 {
+            return Err(io::Error::new(
+                ErrorKind::ConnectionRefused,
+                "the NTP server sent a kiss-of-death",
+            ));
+        }
+        if response[24..32] != request[40..] {
+            return Err(invalid_data("the NTP response doesn't answer the request"));
+        }
+
+        let server_received = from_ntp_timestamp(&response[32..40]);
+        let server_sent = from_ntp_timestamp(&response[40..48]);
+        let server_ahead = ((server_received - sent) + (server_sent - received)) / 2;
+        Ok(-server_ahead)
+    }
+}
+
+impl ChronyClock {
+    /// Constructs a new `ChronyClock` running `chronyc` from the `PATH`.
+    pub fn new() -> ChronyClock {
+        ChronyClock {
+            command: PathBuf::from("chronyc"),
+        }
+    }
+
+    /// Run `command` instead of `chronyc`.
+    pub fn with_command(mut self, command: impl Into<PathBuf>) -> ChronyClock {
+        self.command = command.into();
+        self
+    }
+}
+
+impl Default for ChronyClock {
+    fn default() -> ChronyClock {
+        ChronyClock::new()
+    }
+}
+
+impl ClockReference for ChronyClock {
+    fn offset_millis(&self) -> io::Result<i64> {
+        let output = Command::new(&self.command)
+            .args(["-c", "tracking"])
+            .output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "chronyc failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        // The fifth field is the correction chronyd is slewing in, in
+        // seconds, positive when the clock is slow.
+        let correction = String::from_utf8_lossy(&output.stdout)
+            .split(',')
+            .nth(4)
+            .and_then(|field| field.trim().parse::<f64>().ok())
+            .ok_or_else(|| invalid_data("malformed chronyc tracking output"))?;
+        Ok((-correction * 1000.0).round() as i64)
+    }
+}
+
+/// Check the local clock against `reference`, before issuing any id.
+///
+/// Returns the offset of the local clock, in milliseconds ahead of the
+/// reference, so a node with a badly skewed clock fails at startup
+/// instead of joining the fleet with ids out of order.
+///
+/// # Errors
+///
+/// Fails with the error of the reference, or with an `ErrorKind::Other`
+/// error wrapping `SnowflakeError::ClockSkewed` if the local clock is off
+/// by more than `max_skew`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use snowflake::{NtpClock, SnowflakeIdGenerator};
+///
+/// snowflake::verify_clock(&NtpClock::new("pool.ntp.org:123"), Duration::from_millis(500))
+///     .expect("clock is skewed");
+/// snowflake::init(SnowflakeIdGenerator::new(1, 1)).unwrap();
+/// ```
+pub fn verify_clock<R: ClockReference + ?Sized>(
+    reference: &R,
+    max_skew: Duration,
+) -> io::Result<i64> {
+    let skew_millis = reference.offset_millis()?;
+    let max_skew_millis = max_skew.as_millis().min(i64::MAX as u128) as i64;
+
+    if skew_millis.unsigned_abs() > max_skew_millis as u64 {
+        return Err(io::Error::other(SnowflakeError::ClockSkewed {
+            skew_millis,
+            max_skew_millis,
+        }));
+    }
+    Ok(skew_millis)
+}
+
+fn to_ntp_timestamp(unix_millis: i64) -> u64 {
+    let seconds = unix_millis.div_euclid(1000) + NTP_UNIX_OFFSET_SECONDS;
+    let fraction = (unix_millis.rem_euclid(1000) << 32) / 1000;
+    ((seconds as u64) << 32) | fraction as u64
+}
+
+fn from_ntp_timestamp(bytes: &[u8]) -> i64 {
+    let mut timestamp = [0; 8];
+    timestamp.copy_from_slice(bytes);
+    let timestamp = u64::from_be_bytes(timestamp);
+
+    // Era 1 starts in 2036, timestamps with the top bit cleared are in it.
+    let mut seconds = (timestamp >> 32) as i64;
+    if seconds < 1 << 31 {
+        seconds += 1 << 32;
+    }
+    let millis = ((timestamp & 0xffff_ffff) * 1000) >> 32;
+    (seconds - NTP_UNIX_OFFSET_SECONDS) * 1000 + millis as i64
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
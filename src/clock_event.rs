@@ -0,0 +1,93 @@
+//! Notifications of clock trouble.
+
+use std::time::{Duration, Instant};
+
+use crate::SnowflakeIdGenerator;
+
+/// Forward move of the clock, in milliseconds beyond the time measured by
+/// the monotonic clock, reported as a `ClockEvent::ForwardJump`.
+pub const CLOCK_JUMP_THRESHOLD_MILLIS: i64 = 1000;
+
+/// The `ClockEvent` type is a clock failure noticed by a generator.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ClockEvent {
+    /// The clock read earlier than the last id issued.
+    Regression { last_millis: i64, now_millis: i64 },
+    /// The clock moved forward by `jump_millis` more than the time that
+    /// passed, e.g. after an NTP step. Ids issued since are ahead of time.
+    ForwardJump { jump_millis: i64 },
+    /// The auto-increment record ran out and waiting for the next time unit
+    /// took `waited`, more than two time units: the clock stalled or went
+    /// back while waiting.
+    ProlongedExhaustion { waited: Duration },
+}
+
+impl SnowflakeIdGenerator {
+    /// Call `handler` for every `ClockEvent` the generator notices.
+    ///
+    /// Takes a plain function so the generator stays `Copy`; forward the
+    /// events to a metrics or alerting system from there.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{ClockEvent, SnowflakeIdGenerator};
+    ///
+    /// fn alert(event: ClockEvent) {
+    ///     eprintln!("snowflake clock trouble: {:?}", event);
+    /// }
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new(1, 1).on_clock_event(alert);
+    /// id_generator.real_time_generate();
+    /// ```
+    pub fn on_clock_event(mut self, handler: fn(ClockEvent)) -> SnowflakeIdGenerator {
+        self.clock_events = Some(handler);
+        self
+    }
+
+    // Compare the move of the clock, read as `now`, with the time measured
+    // by the monotonic clock since the last read.
+    pub(crate) fn observe_clock(&mut self, now: i64) {
+        let handler = match self.clock_events {
+            Some(handler) => handler,
+            None => return,
+        };
+        let now_millis = self.layout.time_unit().to_millis(now);
+        let instant = Instant::now();
+
+        if let Some((last_millis, last_instant)) = self.clock_reference {
+            let elapsed = instant.duration_since(last_instant).as_millis() as i64;
+            let jump_millis = now_millis - last_millis - elapsed;
+            if jump_millis > CLOCK_JUMP_THRESHOLD_MILLIS {
+                handler(ClockEvent::ForwardJump { jump_millis });
+            }
+        }
+        self.clock_reference = Some((now_millis, instant));
+    }
+
+    // Count, and report, a clock reading `now` before the last id issued.
+    pub(crate) fn clock_regressed(&mut self, now: i64) {
+        self.stats.clock_anomalies += 1;
+
+        if let Some(handler) = self.clock_events {
+            let time_unit = self.layout.time_unit();
+            handler(ClockEvent::Regression {
+                last_millis: time_unit.to_millis(self.last_time_millis),
+                now_millis: time_unit.to_millis(now),
+            });
+        }
+    }
+
+    // Report a wait for the next time unit started at `started` if it took
+    // longer than two units.
+    pub(crate) fn exhaustion_waited(&self, started: Option<Instant>) {
+        if let (Some(handler), Some(started)) = (self.clock_events, started) {
+            let waited = started.elapsed();
+            let unit_millis = self.layout.time_unit().to_millis(1).max(1) as u64;
+
+            if waited > Duration::from_millis(2 * unit_millis) {
+                handler(ClockEvent::ProlongedExhaustion { waited });
+            }
+        }
+    }
+}
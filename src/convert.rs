@@ -0,0 +1,125 @@
+//! Validated conversions into ids.
+
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use crate::{Layout, Snowflake, SnowflakeError, SnowflakeId, ValidationError};
+
+impl SnowflakeId {
+    /// Constructs a `SnowflakeId`, validating `id` with the default
+    /// `Layout`.
+    ///
+    /// `SnowflakeId::from` and `SnowflakeId::try_from` take any `i64`
+    /// without validation, through `From<i64>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{SnowflakeError, SnowflakeId, ValidationError};
+    ///
+    /// assert!(SnowflakeId::try_new(7202640023251480593).is_ok());
+    /// assert_eq!(
+    ///     SnowflakeId::try_new(-1),
+    ///     Err(SnowflakeError::Invalid(ValidationError::SignBitSet))
+    /// );
+    /// ```
+    pub fn try_new(id: i64) -> Result<SnowflakeId, SnowflakeError> {
+        Snowflake::try_from(id)?;
+        Ok(SnowflakeId(id))
+    }
+}
+
+/// Decodes and validates an id with the default `Layout`, see
+/// `Layout::validate`.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+///
+/// use snowflake::{Snowflake, SnowflakeError, ValidationError};
+///
+/// let snowflake = Snowflake::try_from(1_700_000_000_000i64 << 22 | 0x2A7 << 12 | 17).unwrap();
+///
+/// assert_eq!(snowflake.machine_bits, 0x2A7);
+/// assert_eq!(
+///     Snowflake::try_from(-1i64),
+///     Err(SnowflakeError::Invalid(ValidationError::SignBitSet))
+/// );
+/// ```
+impl TryFrom<i64> for Snowflake {
+    type Error = SnowflakeError;
+
+    fn try_from(id: i64) -> Result<Snowflake, SnowflakeError> {
+        Layout::default()
+            .validate(id)
+            .map_err(SnowflakeError::Invalid)
+    }
+}
+
+impl TryFrom<u64> for Snowflake {
+    type Error = SnowflakeError;
+
+    fn try_from(id: u64) -> Result<Snowflake, SnowflakeError> {
+        Snowflake::try_from(checked_i64(id)?)
+    }
+}
+
+/// Parses a decimal id, then validates it like `TryFrom<i64>`.
+impl TryFrom<&str> for Snowflake {
+    type Error = SnowflakeError;
+
+    fn try_from(text: &str) -> Result<Snowflake, SnowflakeError> {
+        Snowflake::try_from(parse_i64(text)?)
+    }
+}
+
+impl TryFrom<u64> for SnowflakeId {
+    type Error = SnowflakeError;
+
+    fn try_from(id: u64) -> Result<SnowflakeId, SnowflakeError> {
+        SnowflakeId::try_new(checked_i64(id)?)
+    }
+}
+
+impl TryFrom<&str> for SnowflakeId {
+    type Error = SnowflakeError;
+
+    fn try_from(text: &str) -> Result<SnowflakeId, SnowflakeError> {
+        text.parse()
+    }
+}
+
+/// Parses a decimal id and validates it with the default `Layout`.
+///
+/// Plain `i64`s convert with `From`, without validation, or with
+/// `SnowflakeId::try_new`.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::{SnowflakeError, SnowflakeId};
+///
+/// let id: SnowflakeId = "7202640023251480593".parse().unwrap();
+///
+/// assert_eq!(id, SnowflakeId(7202640023251480593));
+/// assert_eq!("0x2A".parse::<SnowflakeId>(), Err(SnowflakeError::ParseError));
+/// ```
+impl FromStr for SnowflakeId {
+    type Err = SnowflakeError;
+
+    fn from_str(text: &str) -> Result<SnowflakeId, SnowflakeError> {
+        SnowflakeId::try_new(parse_i64(text)?)
+    }
+}
+
+fn checked_i64(id: u64) -> Result<i64, SnowflakeError> {
+    i64::try_from(id).map_err(|_| SnowflakeError::Invalid(ValidationError::SignBitSet))
+}
+
+fn parse_i64(text: &str) -> Result<i64, SnowflakeError> {
+    if text.is_empty() || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(SnowflakeError::ParseError);
+    }
+    text.parse().map_err(|_| SnowflakeError::ParseError)
+}
@@ -0,0 +1,214 @@
+use std::fmt;
+use std::io;
+use std::ops::Range;
+use std::sync::Arc;
+
+use postgres::Client;
+
+use crate::leased::LeaseState;
+use crate::{Layout, LeasedGenerator, RenewableLease};
+
+/// The `PostgresAllocator` type leases machine ids with Postgres advisory
+/// locks.
+///
+/// Every machine id is the advisory lock `(lock_space, machine_id)`, held
+/// by the connection of the process that owns it. Postgres releases the
+/// locks of a connection when it closes, so a crashed process frees its
+/// machine id. Apps already talking to Postgres need no other coordinator.
+#[derive(Clone, Debug)]
+pub struct PostgresAllocator {
+    machine_ids: Range<i32>,
+    lock_space: i32,
+    layout: Layout,
+}
+
+/// The `PostgresLease` type is a machine id held by an advisory lock.
+///
+/// The lease owns the connection holding the lock, the lock is released on
+/// drop, and the generators of the lease stop working.
+pub struct PostgresLease {
+    client: Client,
+    lock_space: i32,
+    machine_id: i32,
+    state: Arc<LeaseState>,
+    layout: Layout,
+}
+
+impl PostgresAllocator {
+    /// The lock space used unless told otherwise, `0x5F1A` for snowflake.
+    pub const DEFAULT_LOCK_SPACE: i32 = 0x5F1A;
+
+    /// Constructs a new `PostgresAllocator` leasing `machine_ids`, for
+    /// generators of the default layout.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the machine ids don't fit the machine bits of the layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::PostgresAllocator;
+    ///
+    /// let allocator = PostgresAllocator::new(0..1024);
+    /// ```
+    pub fn new(machine_ids: Range<i32>) -> PostgresAllocator {
+        PostgresAllocator {
+            machine_ids,
+            lock_space: PostgresAllocator::DEFAULT_LOCK_SPACE,
+            layout: Layout::default(),
+        }
+        .with_layout(Layout::default())
+    }
+
+    /// Build the generators of the leases with `layout`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the machine ids don't fit the machine bits of the layout,
+    /// below its shard and tag bits.
+    pub fn with_layout(mut self, layout: Layout) -> PostgresAllocator {
+        let machine_ids = i64::from(self.machine_ids.start)..i64::from(self.machine_ids.end);
+        assert!(
+            layout.fits_machine_ids(&machine_ids),
+            "machine ids {:?} don't fit the layout",
+            self.machine_ids
+        );
+        self.layout = layout;
+        self
+    }
+
+    /// Take the advisory locks in `lock_space`, the first key of the
+    /// two-key form, to stay clear of the other advisory locks of the app.
+    pub fn with_lock_space(mut self, lock_space: i32) -> PostgresAllocator {
+        self.lock_space = lock_space;
+        self
+    }
+
+    /// Lease the first machine id whose lock no other connection holds.
+    ///
+    /// The connection is dedicated to the lease from then on. Fails with
+    /// the error of Postgres, or with `io::ErrorKind::AddrInUse` if every
+    /// machine id is taken.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use postgres::{Client, NoTls};
+    /// use snowflake::PostgresAllocator;
+    ///
+    /// let client = Client::connect("host=localhost user=postgres", NoTls).unwrap();
+    /// let lease = PostgresAllocator::new(0..1024).acquire(client).unwrap();
+    ///
+    /// let mut id_generator = lease.generator();
+    /// id_generator.generate().unwrap();
+    /// ```
+    pub fn acquire(&self, mut client: Client) -> io::Result<PostgresLease> {
+        for machine_id in self.machine_ids.clone() {
+            let locked: bool = client
+                .query_one(
+                    "SELECT pg_try_advisory_lock($1, $2)",
+                    &[&self.lock_space, &machine_id],
+                )
+                .and_then(|row| row.try_get(0))
+                .map_err(io::Error::other)?;
+
+            if locked {
+                return Ok(PostgresLease {
+                    client,
+                    lock_space: self.lock_space,
+                    machine_id,
+                    state: LeaseState::new(i64::from(machine_id), None),
+                    layout: self.layout,
+                });
+            }
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            "every machine id is taken",
+        ))
+    }
+}
+
+impl PostgresLease {
+    /// The leased machine id.
+    pub fn machine_id(&self) -> i64 {
+        i64::from(self.machine_id)
+    }
+
+    /// Check that the connection still holds the lock.
+    ///
+    /// Fails once the connection broke, the machine id may then already be
+    /// leased by another process and must not be used anymore, and the
+    /// generators of the lease stop working.
+    pub fn renew(&mut self) -> io::Result<()> {
+        let renewed = self.check_lock();
+        if renewed.is_err() {
+            self.state.lose();
+        }
+        renewed
+    }
+
+    /// Constructs a generator using the leased machine id, which fails
+    /// once the lease is dropped or a renewal failed.
+    pub fn generator(&self) -> LeasedGenerator {
+        LeasedGenerator::new(&self.state, self.layout)
+    }
+
+    fn check_lock(&mut self) -> io::Result<()> {
+        let held: bool = self
+            .client
+            .query_one(
+                "SELECT EXISTS (
+                    SELECT 1 FROM pg_locks
+                    WHERE locktype = 'advisory' AND granted AND pid = pg_backend_pid()
+                        AND classid = $1::int::oid AND objid = $2::int::oid AND objsubid = 2
+                )",
+                &[&self.lock_space, &self.machine_id],
+            )
+            .and_then(|row| row.try_get(0))
+            .map_err(io::Error::other)?;
+
+        if !held {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "the advisory lock is gone",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for PostgresLease {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PostgresLease")
+            .field("lock_space", &self.lock_space)
+            .field("machine_id", &self.machine_id)
+            .finish()
+    }
+}
+
+impl RenewableLease for PostgresLease {
+    fn machine_id(&self) -> i64 {
+        i64::from(self.machine_id)
+    }
+
+    fn renew(&mut self) -> io::Result<()> {
+        PostgresLease::renew(self)
+    }
+
+    fn layout(&self) -> Layout {
+        self.layout
+    }
+}
+
+impl Drop for PostgresLease {
+    fn drop(&mut self) {
+        self.state.lose();
+        let _ = self.client.execute(
+            "SELECT pg_advisory_unlock($1, $2)",
+            &[&self.lock_space, &self.machine_id],
+        );
+    }
+}
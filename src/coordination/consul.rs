@@ -0,0 +1,246 @@
+use std::io;
+use std::net::SocketAddr;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::leased::LeaseState;
+use crate::{http, Layout, LeasedGenerator, RenewableLease};
+
+/// The `ConsulAllocator` type leases machine ids through Consul sessions.
+///
+/// Every machine id is a key under the prefix, locked by the session of
+/// the process holding it. The session has a TTL: a process that stops
+/// renewing it loses its keys once it expires, and a crashed process frees
+/// its machine id instead of holding it forever.
+#[derive(Clone, Debug)]
+pub struct ConsulAllocator {
+    endpoint: SocketAddr,
+    machine_ids: Range<i64>,
+    prefix: String,
+    ttl: Duration,
+    timeout: Duration,
+    layout: Layout,
+}
+
+/// The `ConsulLease` type is a machine id locked in Consul.
+///
+/// Call `renew` more often than the TTL to keep it, its generators stop
+/// working a TTL after the last successful renewal. The key is released
+/// and the session destroyed on drop.
+#[derive(Debug)]
+pub struct ConsulLease {
+    endpoint: SocketAddr,
+    timeout: Duration,
+    ttl: Duration,
+    key: String,
+    session: String,
+    state: Arc<LeaseState>,
+    layout: Layout,
+}
+
+impl ConsulAllocator {
+    /// Constructs a new `ConsulAllocator` leasing `machine_ids` from the
+    /// Consul agent at `endpoint`, for generators of the default layout.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the machine ids don't fit the machine bits of the layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::ConsulAllocator;
+    ///
+    /// let allocator = ConsulAllocator::new("127.0.0.1:8500".parse().unwrap(), 0..1024);
+    /// ```
+    pub fn new(endpoint: SocketAddr, machine_ids: Range<i64>) -> ConsulAllocator {
+        ConsulAllocator {
+            endpoint,
+            machine_ids,
+            prefix: "snowflake/workers".to_string(),
+            ttl: Duration::from_secs(15),
+            timeout: Duration::from_secs(5),
+            layout: Layout::default(),
+        }
+        .with_layout(Layout::default())
+    }
+
+    /// Lock the machine ids under `prefix` in the KV store,
+    /// `snowflake/workers` by default.
+    pub fn with_prefix(mut self, prefix: &str) -> ConsulAllocator {
+        self.prefix = prefix.trim_matches('/').to_string();
+        self
+    }
+
+    /// Let the session expire after `ttl` without renewal, 15 seconds by
+    /// default. Consul accepts 10 seconds to 24 hours.
+    pub fn with_ttl(mut self, ttl: Duration) -> ConsulAllocator {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Give up on each request to Consul after `timeout`, 5 seconds by
+    /// default.
+    pub fn with_timeout(mut self, timeout: Duration) -> ConsulAllocator {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Build the generators of the leases with `layout`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the machine ids don't fit the machine bits of the layout,
+    /// below its shard and tag bits.
+    pub fn with_layout(mut self, layout: Layout) -> ConsulAllocator {
+        assert!(
+            layout.fits_machine_ids(&self.machine_ids),
+            "machine ids {:?} don't fit the layout",
+            self.machine_ids
+        );
+        self.layout = layout;
+        self
+    }
+
+    /// Lease the first machine id no other session holds.
+    ///
+    /// Fails with the error of Consul, or with `io::ErrorKind::AddrInUse`
+    /// if every machine id is taken.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use snowflake::ConsulAllocator;
+    ///
+    /// let allocator = ConsulAllocator::new("127.0.0.1:8500".parse().unwrap(), 0..1024);
+    /// let lease = allocator.acquire().unwrap();
+    ///
+    /// let mut id_generator = lease.generator();
+    /// id_generator.generate().unwrap();
+    /// ```
+    pub fn acquire(&self) -> io::Result<ConsulLease> {
+        // The session expires a TTL after it was created, at the earliest.
+        let created = Instant::now();
+        let body = format!(
+            r#"{{"Name":"snowflake-worker","TTL":"{}s","Behavior":"delete"}}"#,
+            self.ttl.as_secs()
+        );
+        let response = self.request("/v1/session/create", &body)?;
+        let session = json_string_field(&response, "ID").ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "no session id in response")
+        })?;
+
+        for machine_id in self.machine_ids.clone() {
+            let key = format!("{}/{}", self.prefix, machine_id);
+            let path = format!("/v1/kv/{}?acquire={}", key, session);
+
+            match self.request(&path, &session) {
+                Ok(acquired) if acquired.trim() == "true" => {
+                    return Ok(ConsulLease {
+                        endpoint: self.endpoint,
+                        timeout: self.timeout,
+                        ttl: self.ttl,
+                        key,
+                        session,
+                        state: LeaseState::requested_at(machine_id, Some(self.ttl), created),
+                        layout: self.layout,
+                    });
+                }
+                Ok(_) => continue,
+                Err(error) => {
+                    let _ = self.request(&format!("/v1/session/destroy/{}", session), "");
+                    return Err(error);
+                }
+            }
+        }
+
+        let _ = self.request(&format!("/v1/session/destroy/{}", session), "");
+        Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            "every machine id is taken",
+        ))
+    }
+
+    fn request(&self, path: &str, body: &str) -> io::Result<String> {
+        http::request(self.endpoint, "PUT", path, &[], body, self.timeout)
+    }
+}
+
+impl ConsulLease {
+    /// The leased machine id.
+    pub fn machine_id(&self) -> i64 {
+        self.state.machine_id()
+    }
+
+    /// The id of the Consul session holding the machine id.
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+
+    /// Reset the TTL of the session.
+    ///
+    /// Fails once the session expired, the machine id may then already be
+    /// leased by another process and must not be used anymore, and the
+    /// generators of the lease stop working.
+    pub fn renew(&self) -> io::Result<()> {
+        let started = Instant::now();
+        let path = format!("/v1/session/renew/{}", self.session);
+
+        match http::request(self.endpoint, "PUT", &path, &[], "", self.timeout) {
+            Ok(_) => {
+                self.state.renewed_at(started);
+                Ok(())
+            }
+            Err(error) => {
+                self.state.lose();
+                Err(error)
+            }
+        }
+    }
+
+    /// Constructs a generator using the leased machine id, which fails
+    /// once the lease is dropped, a renewal failed, or the TTL passed
+    /// since the last successful renewal.
+    pub fn generator(&self) -> LeasedGenerator {
+        LeasedGenerator::new(&self.state, self.layout)
+    }
+}
+
+impl RenewableLease for ConsulLease {
+    fn machine_id(&self) -> i64 {
+        self.state.machine_id()
+    }
+
+    fn renew(&mut self) -> io::Result<()> {
+        ConsulLease::renew(self)
+    }
+
+    fn ttl(&self) -> Option<Duration> {
+        Some(self.ttl)
+    }
+
+    fn layout(&self) -> Layout {
+        self.layout
+    }
+}
+
+impl Drop for ConsulLease {
+    fn drop(&mut self) {
+        self.state.lose();
+        let release = format!("/v1/kv/{}?release={}", self.key, self.session);
+        let destroy = format!("/v1/session/destroy/{}", self.session);
+
+        let _ = http::request(self.endpoint, "PUT", &release, &[], "", self.timeout);
+        let _ = http::request(self.endpoint, "PUT", &destroy, &[], "", self.timeout);
+    }
+}
+
+// The string value of `field` in a flat JSON object.
+fn json_string_field(json: &str, field: &str) -> Option<String> {
+    let name = format!("\"{}\"", field);
+    let rest = json[json.find(&name)? + name.len()..].trim_start();
+    let rest = rest.strip_prefix(':')?.trim_start().strip_prefix('"')?;
+
+    Some(rest[..rest.find('"')?].to_string())
+}
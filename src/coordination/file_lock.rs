@@ -0,0 +1,200 @@
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::leased::LeaseState;
+use crate::{Layout, LeasedGenerator, RenewableLease};
+
+/// The `FileLockAllocator` type leases machine ids by locking slot files.
+///
+/// Every machine id is a file in a directory shared by the processes, the
+/// process holding the exclusive lock of a file owns its machine id. The
+/// operating system releases the lock when the process exits, even on a
+/// crash, so no external service is needed on a single machine. On an NFS
+/// share the locks are only as reliable as the server's lock manager.
+#[derive(Clone, Debug)]
+pub struct FileLockAllocator {
+    directory: PathBuf,
+    machine_ids: Range<i64>,
+    layout: Layout,
+}
+
+/// The `FileLockLease` type is a machine id held by a locked slot file.
+///
+/// The lock is released on drop, and the generators of the lease stop
+/// working.
+#[derive(Debug)]
+pub struct FileLockLease {
+    file: File,
+    path: PathBuf,
+    state: Arc<LeaseState>,
+    layout: Layout,
+}
+
+impl FileLockAllocator {
+    /// Constructs a new `FileLockAllocator` leasing `machine_ids` through
+    /// slot files in `directory`, for generators of the default layout.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the machine ids don't fit the machine bits of the layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::FileLockAllocator;
+    ///
+    /// let allocator = FileLockAllocator::new("/var/lib/snowflake", 0..32);
+    /// ```
+    pub fn new(directory: impl Into<PathBuf>, machine_ids: Range<i64>) -> FileLockAllocator {
+        FileLockAllocator {
+            directory: directory.into(),
+            machine_ids,
+            layout: Layout::default(),
+        }
+        .with_layout(Layout::default())
+    }
+
+    /// Build the generators of the leases with `layout`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the machine ids don't fit the machine bits of the layout,
+    /// below its shard and tag bits.
+    pub fn with_layout(mut self, layout: Layout) -> FileLockAllocator {
+        assert!(
+            layout.fits_machine_ids(&self.machine_ids),
+            "machine ids {:?} don't fit the layout",
+            self.machine_ids
+        );
+        self.layout = layout;
+        self
+    }
+
+    /// Lease the first machine id whose slot file isn't locked.
+    ///
+    /// The directory is created if needed. Fails with the error of the file
+    /// system, or with `io::ErrorKind::AddrInUse` if every machine id is
+    /// taken.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::FileLockAllocator;
+    ///
+    /// let directory = std::env::temp_dir().join("snowflake-doc-slots");
+    /// let lease = FileLockAllocator::new(&directory, 0..32).acquire().unwrap();
+    ///
+    /// let mut id_generator = lease.generator();
+    /// id_generator.generate().unwrap();
+    /// ```
+    pub fn acquire(&self) -> io::Result<FileLockLease> {
+        fs::create_dir_all(&self.directory)?;
+
+        for machine_id in self.machine_ids.clone() {
+            let path = self.directory.join(format!("worker-{}.lock", machine_id));
+            let mut file = OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&path)?;
+
+            match file.try_lock() {
+                Ok(()) => {
+                    // Only informative, for whoever looks at the directory.
+                    file.set_len(0)?;
+                    writeln!(file, "{}", std::process::id())?;
+
+                    return Ok(FileLockLease {
+                        file,
+                        path,
+                        state: LeaseState::new(machine_id, None),
+                        layout: self.layout,
+                    });
+                }
+                Err(TryLockError::WouldBlock) => continue,
+                Err(TryLockError::Error(error)) => return Err(error),
+            }
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            "every machine id is taken",
+        ))
+    }
+}
+
+impl FileLockLease {
+    /// The leased machine id.
+    pub fn machine_id(&self) -> i64 {
+        self.state.machine_id()
+    }
+
+    /// The locked slot file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Check that the locked file is still the slot file.
+    ///
+    /// The lock is held until the file is closed, but once the slot file
+    /// was deleted or replaced, another process can lock a new one for the
+    /// same machine id. Fails with `io::ErrorKind::NotFound` then, and the
+    /// generators of the lease stop working.
+    pub fn renew(&mut self) -> io::Result<()> {
+        let renewed = self.check_slot();
+        if renewed.is_err() {
+            self.state.lose();
+        }
+        renewed
+    }
+
+    /// Constructs a generator using the leased machine id, which fails
+    /// once the lease is dropped or a renewal failed.
+    pub fn generator(&self) -> LeasedGenerator {
+        LeasedGenerator::new(&self.state, self.layout)
+    }
+
+    #[cfg(unix)]
+    fn check_slot(&self) -> io::Result<()> {
+        use std::os::unix::fs::MetadataExt;
+
+        let locked = self.file.metadata()?;
+        let slot = fs::metadata(&self.path)?;
+        if (locked.dev(), locked.ino()) != (slot.dev(), slot.ino()) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "the slot file was replaced",
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn check_slot(&self) -> io::Result<()> {
+        fs::metadata(&self.path).map(drop)
+    }
+}
+
+impl RenewableLease for FileLockLease {
+    fn machine_id(&self) -> i64 {
+        self.state.machine_id()
+    }
+
+    fn renew(&mut self) -> io::Result<()> {
+        FileLockLease::renew(self)
+    }
+
+    fn layout(&self) -> Layout {
+        self.layout
+    }
+}
+
+impl Drop for FileLockLease {
+    fn drop(&mut self) {
+        self.state.lose();
+        let _ = self.file.unlock();
+    }
+}
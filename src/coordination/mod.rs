@@ -0,0 +1,19 @@
+//! Machine ids leased from a coordinator shared by the fleet.
+
+#[cfg(feature = "postgres")]
+mod advisory_lock;
+#[cfg(feature = "consul")]
+mod consul;
+mod file_lock;
+mod renewal;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+#[cfg(feature = "postgres")]
+pub use advisory_lock::{PostgresAllocator, PostgresLease};
+#[cfg(feature = "consul")]
+pub use consul::{ConsulAllocator, ConsulLease};
+pub use file_lock::{FileLockAllocator, FileLockLease};
+pub use renewal::{LeaseKeeper, LeaseLost, RenewableLease};
+#[cfg(feature = "sqlite")]
+pub use sqlite::{RegistryLease, WorkerClaim, WorkerRegistry};
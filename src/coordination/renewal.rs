@@ -0,0 +1,164 @@
+use std::fmt;
+use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::leased::LeaseState;
+use crate::{Layout, LeasedGenerator};
+
+/// The `RenewableLease` trait is a machine id claim that must be renewed
+/// to stay valid.
+///
+/// Implemented by the leases of every coordinated allocator, so
+/// `LeaseKeeper` can renew any of them.
+pub trait RenewableLease {
+    /// The leased machine id.
+    fn machine_id(&self) -> i64;
+
+    /// Renew the claim, failing once it was lost.
+    fn renew(&mut self) -> io::Result<()>;
+
+    /// How long the claim stays valid after a renewal, `None` if it stays
+    /// valid until lost.
+    fn ttl(&self) -> Option<Duration> {
+        None
+    }
+
+    /// The layout of the generators of the lease.
+    fn layout(&self) -> Layout {
+        Layout::default()
+    }
+}
+
+/// The `LeaseLost` type is the notification sent when a claim is lost.
+#[derive(Debug)]
+pub struct LeaseLost {
+    pub machine_id: i64,
+    /// Why the renewal failed.
+    pub error: io::Error,
+}
+
+/// The `LeaseKeeper` type renews a lease on a background thread.
+///
+/// The first failed renewal marks the lease lost and sends a `LeaseLost`
+/// on the channel returned by `LeaseKeeper::spawn`: the claim may have
+/// expired and the machine id been given to another process, so the
+/// application must stop generating with it, or lease a new one.
+/// Dropping the keeper stops the thread and drops the lease.
+pub struct LeaseKeeper<L> {
+    lease: Arc<Mutex<L>>,
+    state: Arc<LeaseState>,
+    layout: Layout,
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<L: RenewableLease + Send + 'static> LeaseKeeper<L> {
+    /// Renew `lease` every `interval` until the keeper is dropped or the
+    /// lease is lost.
+    ///
+    /// Pick an interval well below the TTL of the claim, a third of it
+    /// leaves room for two failed attempts at the coordinator's side.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use snowflake::{FileLockAllocator, LeaseKeeper};
+    ///
+    /// let directory = std::env::temp_dir().join("snowflake-doc-keeper");
+    /// let lease = FileLockAllocator::new(&directory, 0..32).acquire().unwrap();
+    ///
+    /// let (keeper, lost) = LeaseKeeper::spawn(lease, Duration::from_secs(5));
+    /// let mut id_generator = keeper.generator();
+    ///
+    /// assert!(id_generator.generate().is_ok());
+    /// assert!(lost.try_recv().is_err());
+    /// ```
+    pub fn spawn(lease: L, interval: Duration) -> (LeaseKeeper<L>, Receiver<LeaseLost>) {
+        let machine_id = lease.machine_id();
+        let state = LeaseState::new(machine_id, lease.ttl());
+        let layout = lease.layout();
+        let lease = Arc::new(Mutex::new(lease));
+        let (stop, stopped) = mpsc::channel();
+        let (notify, notifications) = mpsc::channel();
+
+        let thread = {
+            let lease = Arc::clone(&lease);
+            let state = Arc::clone(&state);
+
+            thread::spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                    let started = Instant::now();
+                    let renewed = match lease.lock() {
+                        Ok(mut lease) => lease.renew(),
+                        Err(_) => Err(io::Error::other("lease lock poisoned")),
+                    };
+
+                    if let Err(error) = renewed {
+                        state.lose();
+                        let _ = notify.send(LeaseLost { machine_id, error });
+                        return;
+                    }
+                    state.renewed_at(started);
+                }
+            })
+        };
+
+        let keeper = LeaseKeeper {
+            lease,
+            state,
+            layout,
+            stop: Some(stop),
+            thread: Some(thread),
+        };
+        (keeper, notifications)
+    }
+}
+
+impl<L> LeaseKeeper<L> {
+    /// The leased machine id.
+    pub fn machine_id(&self) -> i64 {
+        self.state.machine_id()
+    }
+
+    /// Whether a renewal failed, after which the machine id must not be
+    /// used anymore.
+    pub fn is_lost(&self) -> bool {
+        self.state.is_lost()
+    }
+
+    /// Run `f` with the lease, e.g. to renew it right away.
+    pub fn with_lease<R>(&self, f: impl FnOnce(&mut L) -> R) -> R {
+        f(&mut self.lease.lock().expect("Lease lock poisoned"))
+    }
+
+    /// Constructs a generator using the leased machine id, which fails
+    /// once a renewal failed, the TTL of the lease passed since the last
+    /// successful renewal, or the keeper is dropped.
+    pub fn generator(&self) -> LeasedGenerator {
+        LeasedGenerator::new(&self.state, self.layout)
+    }
+}
+
+impl<L> fmt::Debug for LeaseKeeper<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LeaseKeeper")
+            .field("machine_id", &self.machine_id())
+            .field("lost", &self.is_lost())
+            .finish()
+    }
+}
+
+impl<L> Drop for LeaseKeeper<L> {
+    fn drop(&mut self) {
+        self.state.lose();
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
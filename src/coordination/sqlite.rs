@@ -0,0 +1,316 @@
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+
+use crate::leased::LeaseState;
+use crate::{
+    get_time_millis, split_mix64, Layout, LeasedGenerator, RenewableLease, SPLIT_MIX64_GAMMA,
+};
+
+static NEXT_TOKEN: AtomicU64 = AtomicU64::new(0);
+
+/// The `WorkerRegistry` type records which machine ids are claimed in a
+/// SQLite file.
+///
+/// Every claim stores the machine id, the hostname of the claimant and a
+/// heartbeat. Claims whose heartbeat is older than the stale timeout are
+/// reaped, so machine ids of crashed processes come back. Suits on-prem
+/// clusters with a shared file system but no coordinator.
+#[derive(Clone, Debug)]
+pub struct WorkerRegistry {
+    connection: Arc<Mutex<Connection>>,
+    machine_ids: Range<i64>,
+    stale_after: Duration,
+    layout: Layout,
+}
+
+/// The `WorkerClaim` type is a row of the `WorkerRegistry`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkerClaim {
+    pub machine_id: i64,
+    pub hostname: String,
+    /// Unix milliseconds of the last heartbeat.
+    pub heartbeat: i64,
+}
+
+/// The `RegistryLease` type is a machine id claimed in a `WorkerRegistry`.
+///
+/// Call `renew` more often than the stale timeout to keep it, its
+/// generators stop working a stale timeout after the last heartbeat. The
+/// claim is removed on drop.
+#[derive(Debug)]
+pub struct RegistryLease {
+    connection: Arc<Mutex<Connection>>,
+    token: String,
+    stale_after: Duration,
+    state: Arc<LeaseState>,
+    layout: Layout,
+}
+
+impl WorkerRegistry {
+    /// Open, or create, the registry in the SQLite file at `path`.
+    ///
+    /// Leases machine ids `0..1024` and reaps claims after a minute without
+    /// heartbeat, unless told otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::WorkerRegistry;
+    ///
+    /// let path = std::env::temp_dir().join("snowflake-doc-registry.db");
+    /// let registry = WorkerRegistry::sqlite(&path).unwrap().with_machine_ids(0..32);
+    ///
+    /// let lease = registry.acquire("web-1").unwrap();
+    /// let mut id_generator = lease.generator();
+    /// id_generator.generate().unwrap();
+    /// ```
+    pub fn sqlite(path: impl AsRef<Path>) -> io::Result<WorkerRegistry> {
+        let connection = Connection::open(path).map_err(io::Error::other)?;
+        connection
+            .busy_timeout(Duration::from_secs(5))
+            .and_then(|_| {
+                connection.execute_batch(
+                    "CREATE TABLE IF NOT EXISTS snowflake_workers (
+                        machine_id INTEGER PRIMARY KEY,
+                        hostname TEXT NOT NULL,
+                        token TEXT NOT NULL,
+                        heartbeat INTEGER NOT NULL
+                    )",
+                )
+            })
+            .map_err(io::Error::other)?;
+
+        Ok(WorkerRegistry {
+            connection: Arc::new(Mutex::new(connection)),
+            machine_ids: 0..1024,
+            stale_after: Duration::from_secs(60),
+            layout: Layout::default(),
+        })
+    }
+
+    /// Lease machine ids out of `machine_ids`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the machine ids don't fit the machine bits of the layout.
+    pub fn with_machine_ids(mut self, machine_ids: Range<i64>) -> WorkerRegistry {
+        self.machine_ids = machine_ids;
+        let layout = self.layout;
+        self.with_layout(layout)
+    }
+
+    /// Build the generators of the leases with `layout`, the default one
+    /// unless told otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the machine ids don't fit the machine bits of the layout,
+    /// below its shard and tag bits.
+    pub fn with_layout(mut self, layout: Layout) -> WorkerRegistry {
+        assert!(
+            layout.fits_machine_ids(&self.machine_ids),
+            "machine ids {:?} don't fit the layout",
+            self.machine_ids
+        );
+        self.layout = layout;
+        self
+    }
+
+    /// Reap claims after `stale_after` without heartbeat.
+    pub fn with_stale_after(mut self, stale_after: Duration) -> WorkerRegistry {
+        self.stale_after = stale_after;
+        self
+    }
+
+    /// Claim the first free machine id for `hostname`.
+    ///
+    /// Stale claims are reaped first. Fails with the error of SQLite, or
+    /// with `io::ErrorKind::AddrInUse` if every machine id is taken.
+    pub fn acquire(&self, hostname: &str) -> io::Result<RegistryLease> {
+        // The claim goes stale a timeout after its heartbeat, at the earliest.
+        let claimed = Instant::now();
+        let mut connection = lock(&self.connection);
+        let transaction = connection
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .map_err(io::Error::other)?;
+
+        reap(&transaction, self.stale_after).map_err(io::Error::other)?;
+
+        let mut machine_id = self.machine_ids.start;
+        let free = loop {
+            if machine_id >= self.machine_ids.end {
+                break None;
+            }
+
+            let taken = transaction
+                .query_row(
+                    "SELECT 1 FROM snowflake_workers WHERE machine_id = ?1",
+                    params![machine_id],
+                    |_| Ok(()),
+                )
+                .optional()
+                .map_err(io::Error::other)?;
+            if taken.is_none() {
+                break Some(machine_id);
+            }
+            machine_id += 1;
+        };
+        let machine_id = free
+            .ok_or_else(|| io::Error::new(io::ErrorKind::AddrInUse, "every machine id is taken"))?;
+
+        let token = new_token();
+        transaction
+            .execute(
+                "INSERT INTO snowflake_workers (machine_id, hostname, token, heartbeat)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![machine_id, hostname, token, get_time_millis()],
+            )
+            .and_then(|_| transaction.commit())
+            .map_err(io::Error::other)?;
+
+        Ok(RegistryLease {
+            connection: Arc::clone(&self.connection),
+            token,
+            stale_after: self.stale_after,
+            state: LeaseState::requested_at(machine_id, Some(self.stale_after), claimed),
+            layout: self.layout,
+        })
+    }
+
+    /// Remove the claims older than the stale timeout, returning how many.
+    pub fn reap_stale(&self) -> io::Result<usize> {
+        reap(&lock(&self.connection), self.stale_after).map_err(io::Error::other)
+    }
+
+    /// Every claim of the registry, by machine id.
+    pub fn claims(&self) -> io::Result<Vec<WorkerClaim>> {
+        let connection = lock(&self.connection);
+        let mut statement = connection
+            .prepare(
+                "SELECT machine_id, hostname, heartbeat FROM snowflake_workers
+                 ORDER BY machine_id",
+            )
+            .map_err(io::Error::other)?;
+
+        statement
+            .query_map([], |row| {
+                Ok(WorkerClaim {
+                    machine_id: row.get(0)?,
+                    hostname: row.get(1)?,
+                    heartbeat: row.get(2)?,
+                })
+            })
+            .and_then(|rows| rows.collect())
+            .map_err(io::Error::other)
+    }
+}
+
+impl RegistryLease {
+    /// The leased machine id.
+    pub fn machine_id(&self) -> i64 {
+        self.state.machine_id()
+    }
+
+    /// Record a heartbeat for the claim.
+    ///
+    /// Fails with `io::ErrorKind::NotFound` once the claim was reaped, the
+    /// machine id may then already be leased by another process and must
+    /// not be used anymore, and the generators of the lease stop working.
+    pub fn renew(&self) -> io::Result<()> {
+        let started = Instant::now();
+        let updated = lock(&self.connection)
+            .execute(
+                "UPDATE snowflake_workers SET heartbeat = ?1 WHERE machine_id = ?2 AND token = ?3",
+                params![get_time_millis(), self.machine_id(), self.token],
+            )
+            .map_err(io::Error::other);
+
+        match updated {
+            Ok(0) => {
+                self.state.lose();
+                Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "the claim was reaped",
+                ))
+            }
+            Ok(_) => {
+                self.state.renewed_at(started);
+                Ok(())
+            }
+            Err(error) => {
+                self.state.lose();
+                Err(error)
+            }
+        }
+    }
+
+    /// Constructs a generator using the leased machine id, which fails
+    /// once the lease is dropped, a renewal failed, or the stale timeout
+    /// passed since the last heartbeat.
+    pub fn generator(&self) -> LeasedGenerator {
+        LeasedGenerator::new(&self.state, self.layout)
+    }
+}
+
+impl RenewableLease for RegistryLease {
+    fn machine_id(&self) -> i64 {
+        self.state.machine_id()
+    }
+
+    fn renew(&mut self) -> io::Result<()> {
+        RegistryLease::renew(self)
+    }
+
+    fn ttl(&self) -> Option<Duration> {
+        Some(self.stale_after)
+    }
+
+    fn layout(&self) -> Layout {
+        self.layout
+    }
+}
+
+impl Drop for RegistryLease {
+    fn drop(&mut self) {
+        self.state.lose();
+        if let Ok(connection) = self.connection.lock() {
+            let _ = connection.execute(
+                "DELETE FROM snowflake_workers WHERE machine_id = ?1 AND token = ?2",
+                params![self.machine_id(), self.token],
+            );
+        }
+    }
+}
+
+fn lock(connection: &Mutex<Connection>) -> MutexGuard<'_, Connection> {
+    connection.lock().expect("Registry lock poisoned")
+}
+
+fn reap(connection: &Connection, stale_after: Duration) -> rusqlite::Result<usize> {
+    let cutoff = get_time_millis() - stale_after.as_millis() as i64;
+    connection.execute(
+        "DELETE FROM snowflake_workers WHERE heartbeat < ?1",
+        params![cutoff],
+    )
+}
+
+// A token telling this claim apart from a later claim of the same machine
+// id, so a reaped lease can't renew or delete it.
+fn new_token() -> String {
+    let nanos = UNIX_EPOCH
+        .elapsed()
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+    let counter = NEXT_TOKEN.fetch_add(1, Ordering::Relaxed);
+    let seed = nanos ^ u64::from(std::process::id()) << 32;
+
+    format!(
+        "{:016x}",
+        split_mix64(seed.wrapping_add(counter.wrapping_mul(SPLIT_MIX64_GAMMA)))
+    )
+}
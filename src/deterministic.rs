@@ -0,0 +1,76 @@
+//! Reproducible ids for fixtures.
+
+use crate::{
+    split_mix64, GeneratorStats, IdGenerator, Layout, SnowflakeError, SnowflakeId,
+    SnowflakeIdGenerator, TimeSource, MACHINE_MASK, SPLIT_MIX64_GAMMA,
+};
+
+/// Earliest timestamp a `DeterministicGenerator` starts from, 2020-01-01T00:00:00Z.
+pub const DETERMINISTIC_EPOCH_MILLIS: i64 = 1_577_836_800_000;
+
+// Window after `DETERMINISTIC_EPOCH_MILLIS` the start timestamp is picked from, one year.
+const START_WINDOW_MILLIS: u64 = 365 * 24 * 60 * 60 * 1000;
+
+/// The `DeterministicGenerator` type yields the same ids for the same seed.
+///
+/// It never reads the clock. The seed is scrambled with SplitMix64, the
+/// result picks a start timestamp within one year after
+/// `DETERMINISTIC_EPOCH_MILLIS` and the machine bits. Ids then follow
+/// `SnowflakeIdGenerator::lazy_generate`: the auto-increment record counts
+/// up and the fake clock moves one millisecond forward every time it wraps.
+#[derive(Copy, Clone, Debug)]
+pub struct DeterministicGenerator {
+    generator: SnowflakeIdGenerator,
+}
+
+impl DeterministicGenerator {
+    /// Constructs a new `DeterministicGenerator` from `seed`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::DeterministicGenerator;
+    ///
+    /// let mut first = DeterministicGenerator::with_seed(7);
+    /// let mut second = DeterministicGenerator::with_seed(7);
+    ///
+    /// assert_eq!(first.generate(), second.generate());
+    /// ```
+    pub fn with_seed(seed: u64) -> DeterministicGenerator {
+        let mixed = split_mix64(seed.wrapping_add(SPLIT_MIX64_GAMMA));
+
+        DeterministicGenerator {
+            generator: SnowflakeIdGenerator {
+                last_time_millis: DETERMINISTIC_EPOCH_MILLIS + (mixed % START_WINDOW_MILLIS) as i64,
+                machine_bits: (mixed >> 48) as i64 & MACHINE_MASK,
+                idx: 0,
+                first_idx: 0,
+                idx_rng: None,
+                layout: Layout::default(),
+                clock_fallback: false,
+                stats: GeneratorStats::default(),
+                clock_events: None,
+                clock_reference: None,
+                wait_strategy: None,
+                time_source: TimeSource::System,
+                past: None,
+            },
+        }
+    }
+
+    /// Generate the next id of the sequence.
+    pub fn generate(&mut self) -> i64 {
+        self.generator.lazy_generate()
+    }
+
+    /// The generator state, including the fake clock.
+    pub fn generator(&self) -> &SnowflakeIdGenerator {
+        &self.generator
+    }
+}
+
+impl IdGenerator for DeterministicGenerator {
+    fn next_id(&mut self) -> Result<SnowflakeId, SnowflakeError> {
+        Ok(SnowflakeId(self.generate()))
+    }
+}
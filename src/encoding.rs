@@ -0,0 +1,98 @@
+//! Arbitrary base encodings of big-endian byte strings.
+
+pub(crate) const BASE62_ALPHABET: &[u8; 62] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+// Bitcoin's base58, without the look-alike 0, O, I and l, in ASCII order.
+pub(crate) const BASE58_ALPHABET: &[u8; 58] =
+    b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+// RFC 4648 base64url, URL and cookie safe.
+const BASE64URL_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// Crockford's base32, in ASCII order so padded strings sort like the numbers.
+pub(crate) const BASE32_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+// Encode `bytes` as a number in the base of `alphabet`, left padded to `width`.
+pub(crate) fn encode(bytes: &[u8], alphabet: &[u8], width: usize) -> String {
+    let base = alphabet.len() as u32;
+    let mut number = bytes.to_vec();
+    let mut digits = Vec::with_capacity(width);
+
+    while number.iter().any(|&byte| byte != 0) {
+        let mut remainder = 0u32;
+        for byte in number.iter_mut() {
+            let value = remainder << 8 | u32::from(*byte);
+            *byte = (value / base) as u8;
+            remainder = value % base;
+        }
+        digits.push(alphabet[remainder as usize]);
+    }
+
+    while digits.len() < width {
+        digits.push(alphabet[0]);
+    }
+
+    digits.iter().rev().map(|&digit| digit as char).collect()
+}
+
+// Decode `text` in the base of `alphabet` into `N` big-endian bytes.
+// `None` on unknown characters or if the number doesn't fit.
+pub(crate) fn decode<const N: usize>(text: &str, alphabet: &[u8]) -> Option<[u8; N]> {
+    let base = alphabet.len() as u32;
+    let mut number = [0u8; N];
+
+    for character in text.bytes() {
+        let mut carry = alphabet.iter().position(|&digit| digit == character)? as u32;
+        for byte in number.iter_mut().rev() {
+            let value = u32::from(*byte) * base + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+        if carry != 0 {
+            return None;
+        }
+    }
+
+    Some(number)
+}
+
+// Encode 8 bytes as 11 characters of base64url, without padding.
+pub(crate) fn encode_base64url(bytes: [u8; 8]) -> String {
+    let number = u64::from_be_bytes(bytes);
+
+    // 64 bits fill 10 characters of 6 bits, and 4 bits of the last one.
+    (0..11)
+        .map(|i| {
+            let digit = match i {
+                10 => (number << 2) & 0x3F,
+                i => (number >> (58 - 6 * i)) & 0x3F,
+            };
+            BASE64URL_ALPHABET[digit as usize] as char
+        })
+        .collect()
+}
+
+// Decode 11 characters of base64url, without padding, into 8 bytes.
+// `None` on unknown characters, any other length, or unused bits set, so
+// every value has a single encoding.
+pub(crate) fn decode_base64url(text: &str) -> Option<[u8; 8]> {
+    if text.len() != 11 {
+        return None;
+    }
+
+    let mut number = 0u64;
+    for (i, character) in text.bytes().enumerate() {
+        let digit = BASE64URL_ALPHABET
+            .iter()
+            .position(|&digit| digit == character)? as u64;
+        number = match i {
+            10 if digit & 0x3 != 0 => return None,
+            10 => number << 4 | digit >> 2,
+            _ => number << 6 | digit,
+        };
+    }
+
+    Some(number.to_be_bytes())
+}
@@ -0,0 +1,166 @@
+//! Entity types told apart by the tag bits of their ids.
+
+use std::any::{self, TypeId};
+
+use crate::{Layout, SnowflakeError, SnowflakeIdGenerator, TypedId};
+
+/// The `Entity` trait assigns an entity type the tag its ids carry, see
+/// `Layout::with_tag_bits`.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::{Entity, Layout, SnowflakeIdGenerator, TypedId};
+///
+/// struct User;
+///
+/// impl Entity for User {
+///     const TAG: u64 = 1;
+/// }
+///
+/// let layout = Layout::default().with_tag_bits(3);
+/// let mut id_generator = SnowflakeIdGenerator::new(0, 7).with_layout(layout);
+///
+/// let user = TypedId::<User>::generate(&mut id_generator).unwrap();
+///
+/// assert_eq!(TypedId::<User>::decode(&layout, user.get()), Some(user));
+/// ```
+pub trait Entity {
+    /// The tag of the ids of the entity.
+    const TAG: u64;
+
+    /// The name `EntityKind::name` reports, the type name by default.
+    fn name() -> &'static str {
+        any::type_name::<Self>()
+    }
+}
+
+/// The `EntityKind` type is an entity type registered in an
+/// `EntityRegistry`, as found by `EntityRegistry::decode_any`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EntityKind {
+    tag: u64,
+    name: &'static str,
+    type_id: TypeId,
+}
+
+/// The `EntityRegistry` type maps the tags of a layout back to the entity
+/// types they were assigned to.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::{Entity, EntityRegistry, Layout, SnowflakeIdGenerator, TypedId};
+///
+/// struct User;
+/// struct Order;
+///
+/// impl Entity for User {
+///     const TAG: u64 = 1;
+/// }
+///
+/// impl Entity for Order {
+///     const TAG: u64 = 2;
+///
+///     fn name() -> &'static str {
+///         "order"
+///     }
+/// }
+///
+/// let layout = Layout::default().with_tag_bits(3);
+/// let registry = EntityRegistry::new(layout).register::<User>().register::<Order>();
+///
+/// let mut id_generator = SnowflakeIdGenerator::new(0, 7).with_layout(layout);
+/// let order = TypedId::<Order>::generate(&mut id_generator).unwrap();
+///
+/// let kind = registry.decode_any(order.get()).unwrap();
+/// assert!(kind.is::<Order>());
+/// assert_eq!(kind.name(), "order");
+/// assert_eq!(registry.decode::<User>(order.get()), None);
+/// ```
+#[derive(Clone, Debug)]
+pub struct EntityRegistry {
+    layout: Layout,
+    kinds: Vec<EntityKind>,
+}
+
+impl EntityKind {
+    /// The tag of the entity's ids.
+    pub fn tag(&self) -> u64 {
+        self.tag
+    }
+
+    /// The name of the entity, see `Entity::name`.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Whether the entity is `T`.
+    pub fn is<T: 'static>(&self) -> bool {
+        self.type_id == TypeId::of::<T>()
+    }
+}
+
+impl EntityRegistry {
+    /// Constructs a new, empty, `EntityRegistry` for ids of `layout`.
+    pub fn new(layout: Layout) -> EntityRegistry {
+        EntityRegistry {
+            layout,
+            kinds: Vec::new(),
+        }
+    }
+
+    /// Register the entity `T` under its tag.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tag doesn't fit the tag bits of the layout, or is
+    /// already registered: ids of two entities sharing a tag can't be told
+    /// apart.
+    pub fn register<T: Entity + 'static>(mut self) -> EntityRegistry {
+        assert!(
+            T::TAG >> self.layout.tag_bits() == 0,
+            "{} doesn't fit the tag bits",
+            T::name()
+        );
+        if let Some(kind) = self.kinds.iter().find(|kind| kind.tag == T::TAG) {
+            panic!("{} and {} share tag {}", kind.name, T::name(), T::TAG);
+        }
+
+        self.kinds.push(EntityKind {
+            tag: T::TAG,
+            name: T::name(),
+            type_id: TypeId::of::<T>(),
+        });
+        self
+    }
+
+    /// The entity of `id`, `None` if its tag wasn't registered.
+    pub fn decode_any(&self, id: i64) -> Option<EntityKind> {
+        let tag = self.layout.tag_of(id);
+        self.kinds.iter().find(|kind| kind.tag == tag).copied()
+    }
+
+    /// `id` typed as an id of `T`, `None` if it carries another tag.
+    pub fn decode<T: Entity>(&self, id: i64) -> Option<TypedId<T>> {
+        TypedId::decode(&self.layout, id)
+    }
+}
+
+impl<T: Entity> TypedId<T> {
+    /// Generate an id of the entity, tagged with `Entity::TAG`, see
+    /// `SnowflakeIdGenerator::generate_tagged`.
+    pub fn generate(generator: &mut SnowflakeIdGenerator) -> Result<TypedId<T>, SnowflakeError> {
+        generator.generate_tagged(T::TAG).map(TypedId::new)
+    }
+
+    /// `id` typed as an id of the entity, `None` if it carries another
+    /// tag in `layout`.
+    pub fn decode(layout: &Layout, id: i64) -> Option<TypedId<T>> {
+        if layout.tag_of(id) == T::TAG {
+            Some(TypedId::from(id))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Errors of the fallible APIs.
+
+use std::fmt;
+
+use crate::ValidationError;
+
+/// The `SnowflakeError` type is the error of the crate's fallible APIs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SnowflakeError {
+    /// The machine id doesn't fit the machine bits of the layout.
+    InvalidMachineId(i64),
+    /// The IP address isn't a dotted IPv4 address.
+    InvalidIp,
+    /// The clock reads earlier than the last id issued, in milliseconds
+    /// since the Unix epoch, negative if the clock is before the epoch.
+    ClockMovedBackwards { last_millis: i64, now_millis: i64 },
+    /// The clock reads before the Unix epoch, in milliseconds since it,
+    /// negative.
+    ClockBeforeEpoch(i64),
+    /// The local clock is off a reference clock by more than the allowed
+    /// skew, in milliseconds, positive if it is ahead.
+    ClockSkewed {
+        skew_millis: i64,
+        max_skew_millis: i64,
+    },
+    /// The timestamp doesn't fit the timestamp bits of the layout, the
+    /// epoch of the layout ran out.
+    EpochOverflow(i64),
+    /// The shard doesn't fit the shard bits of the layout.
+    InvalidShard(u64),
+    /// The tag doesn't fit the tag bits of the layout.
+    InvalidTag(u64),
+    /// The thread index doesn't fit the thread bits of the layout.
+    InvalidThreadIndex(u32),
+    /// The process-wide generator was already installed.
+    AlreadyInitialized,
+    /// The timestamp is before the last one the generator issued ids for,
+    /// ids minted there could sort before ids already issued.
+    BeforeHighWaterMark {
+        timestamp: i64,
+        high_water_mark: i64,
+    },
+    /// Every auto-increment record of the time unit is used.
+    SequenceExhausted(i64),
+    /// The rate limit of a `ThrottledGenerator` is used up.
+    RateLimited,
+    /// The lease of the machine id was released, lost or expired, another
+    /// process may be using the machine id.
+    LeaseLost(i64),
+    /// The `SegmentStore` failed to hand out a segment, the `generate`
+    /// methods of the segment generators return the error of the store.
+    SegmentStoreFailed,
+    /// The text isn't a decimal id.
+    ParseError,
+    /// The id failed validation against its layout.
+    Invalid(ValidationError),
+}
+
+impl fmt::Display for SnowflakeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnowflakeError::InvalidMachineId(machine_id) => {
+                write!(f, "machine id {} doesn't fit the layout", machine_id)
+            }
+            SnowflakeError::InvalidIp => f.write_str("invalid IPv4 address"),
+            SnowflakeError::ClockMovedBackwards {
+                last_millis,
+                now_millis,
+            } => write!(
+                f,
+                "clock moved backwards from {} to {}",
+                last_millis, now_millis
+            ),
+            SnowflakeError::ClockBeforeEpoch(now_millis) => {
+                write!(f, "clock is {} ms before the unix epoch", -now_millis)
+            }
+            SnowflakeError::ClockSkewed {
+                skew_millis,
+                max_skew_millis,
+            } => write!(
+                f,
+                "clock is off the reference by {} ms, more than {} ms",
+                skew_millis, max_skew_millis
+            ),
+            SnowflakeError::EpochOverflow(timestamp) => {
+                write!(f, "timestamp {} overflows the layout", timestamp)
+            }
+            SnowflakeError::InvalidShard(shard) => {
+                write!(f, "shard {} doesn't fit the layout", shard)
+            }
+            SnowflakeError::InvalidTag(tag) => write!(f, "tag {} doesn't fit the layout", tag),
+            SnowflakeError::InvalidThreadIndex(index) => {
+                write!(f, "thread index {} doesn't fit the layout", index)
+            }
+            SnowflakeError::AlreadyInitialized => {
+                f.write_str("the global generator is already initialized")
+            }
+            SnowflakeError::BeforeHighWaterMark {
+                timestamp,
+                high_water_mark,
+            } => write!(
+                f,
+                "timestamp {} is before the high-water mark {}",
+                timestamp, high_water_mark
+            ),
+            SnowflakeError::SequenceExhausted(timestamp) => {
+                write!(f, "no id left for timestamp {}", timestamp)
+            }
+            SnowflakeError::RateLimited => f.write_str("the id rate limit is used up"),
+            SnowflakeError::LeaseLost(machine_id) => {
+                write!(f, "the lease of machine id {} is no longer valid", machine_id)
+            }
+            SnowflakeError::SegmentStoreFailed => f.write_str("the segment store failed"),
+            SnowflakeError::ParseError => f.write_str("invalid snowflake id string"),
+            SnowflakeError::Invalid(error) => write!(f, "invalid snowflake id: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for SnowflakeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SnowflakeError::Invalid(error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Human readable breakdown of ids.
+
+use std::fmt;
+
+use crate::{Layout, SnowflakeId, TimeUnit};
+
+impl Layout {
+    /// Render `id` as its fields with their bit ranges, highest bits first.
+    ///
+    /// Handy to spot layout mismatches between systems.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Layout;
+    ///
+    /// let id = 1_717_243_200_123 << 22 | 0x2A7 << 12 | 17;
+    ///
+    /// assert_eq!(
+    ///     Layout::default().explain(id),
+    ///     "ts=2024-06-01T12:00:00.123Z [62..22] machine=0x2A7 [21..12] seq=17 [11..0]"
+    /// );
+    /// ```
+    pub fn explain(&self, id: i64) -> String {
+        let snowflake = self.decode(id);
+        let mut fields = vec![(
+            self.timestamp_shift(),
+            self.timestamp_bits(),
+            format!(
+                "ts={}",
+                format_timestamp(snowflake.timestamp, self.time_unit())
+            ),
+        )];
+
+        if self.era_bits() > 0 {
+            fields.push((
+                self.era_shift(),
+                self.era_bits(),
+                format!("era={}", self.era(id)),
+            ));
+        }
+        fields.push((
+            self.machine_shift(),
+            self.machine_bits(),
+            format!("machine={:#X}", snowflake.machine_bits).replacen("0X", "0x", 1),
+        ));
+        fields.push((
+            self.sequence_shift(),
+            self.sequence_bits(),
+            format!("seq={}", snowflake.idx),
+        ));
+
+        fields.retain(|&(_, bits, _)| bits > 0);
+        fields.sort_by_key(|&(shift, _, _)| std::cmp::Reverse(shift));
+
+        let rendered: Vec<String> = fields
+            .into_iter()
+            .map(|(shift, bits, field)| format!("{} [{}..{}]", field, shift + bits - 1, shift))
+            .collect();
+        rendered.join(" ")
+    }
+}
+
+impl SnowflakeId {
+    /// Render the id as its fields with their bit ranges, using the default
+    /// `Layout`, see `Layout::explain`.
+    ///
+    /// The alternate `Debug` format, `{:#?}`, prints the same.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::SnowflakeId;
+    ///
+    /// let id = SnowflakeId(1_717_243_200_123 << 22 | 0x2A7 << 12 | 17);
+    ///
+    /// assert_eq!(format!("{:?}", id), "SnowflakeId(7202640023251480593)");
+    /// assert_eq!(format!("{:#?}", id), id.explain());
+    /// ```
+    pub fn explain(self) -> String {
+        Layout::default().explain(self.0)
+    }
+}
+
+impl fmt::Debug for SnowflakeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str(&self.explain())
+        } else {
+            f.debug_tuple("SnowflakeId").field(&self.0).finish()
+        }
+    }
+}
+
+// RFC 3339 in UTC, with as many fraction digits as the unit has.
+fn format_timestamp(timestamp: i64, time_unit: TimeUnit) -> String {
+    let (per_second, digits) = match time_unit {
+        TimeUnit::Seconds => (1, 0),
+        TimeUnit::Milliseconds => (1_000, 3),
+        TimeUnit::Microseconds => (1_000_000, 6),
+    };
+
+    let seconds = timestamp.div_euclid(per_second);
+    let fraction = timestamp.rem_euclid(per_second);
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let second_of_day = seconds.rem_euclid(86_400);
+
+    let mut text = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        second_of_day / 3600,
+        second_of_day / 60 % 60,
+        second_of_day % 60
+    );
+    if digits > 0 {
+        text.push_str(&format!(".{:0width$}", fraction, width = digits));
+    }
+    text.push('Z');
+    text
+}
+
+// Year, month and day of the days since 1970-01-01, in the proleptic
+// Gregorian calendar.
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
@@ -0,0 +1,50 @@
+//! Opt-in process-wide generator.
+
+use std::sync::OnceLock;
+
+use crate::{SharedGenerator, SnowflakeError, SnowflakeIdGenerator};
+
+static GLOBAL_GENERATOR: OnceLock<SharedGenerator> = OnceLock::new();
+
+/// Install `generator` as the process-wide generator used by `next_id`
+/// and `snowflake_id!`.
+///
+/// Fails with `SnowflakeError::AlreadyInitialized` if called twice.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::SnowflakeIdGenerator;
+///
+/// snowflake::init(SnowflakeIdGenerator::new(1, 7)).unwrap();
+///
+/// let id = snowflake::next_id();
+/// let other = snowflake::snowflake_id!();
+///
+/// assert!(id < other);
+/// ```
+pub fn init(generator: SnowflakeIdGenerator) -> Result<(), SnowflakeError> {
+    GLOBAL_GENERATOR
+        .set(SharedGenerator::new(generator))
+        .map_err(|_| SnowflakeError::AlreadyInitialized)
+}
+
+/// Generate an id with the process-wide generator.
+///
+/// # Panics
+///
+/// Panics if `init` wasn't called.
+pub fn next_id() -> i64 {
+    GLOBAL_GENERATOR
+        .get()
+        .expect("snowflake::init was not called")
+        .generate()
+}
+
+/// Generate an id with the process-wide generator, see `next_id`.
+#[macro_export]
+macro_rules! snowflake_id {
+    () => {
+        $crate::next_id()
+    };
+}
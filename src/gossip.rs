@@ -0,0 +1,157 @@
+//! Duplicate machine id detection over UDP.
+
+use std::convert::TryInto;
+use std::fmt;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use crate::split_mix64;
+
+// Marks the datagrams of the detector, the rest of the traffic is ignored.
+const MAGIC: &[u8; 4] = b"SNFK";
+
+// Magic, machine id and nonce.
+const DATAGRAM_LEN: usize = 20;
+
+// How often the thread checks whether it should stop.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The `DuplicateWorker` type is a peer announcing the same machine id.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateWorker {
+    pub machine_id: i64,
+    /// Address the announcement came from.
+    pub peer: SocketAddr,
+}
+
+/// The `GossipDetector` type announces the machine id of the process on
+/// the local network and listens for others announcing the same one.
+///
+/// A safety net for misconfigured fleets, not a coordination mechanism:
+/// every `interval` the machine id and a random nonce are sent to the
+/// target address, a broadcast or multicast group. Any datagram with the
+/// same machine id but another nonce comes from another process using the
+/// same machine id, and is passed to the callback. Dropping the detector
+/// stops its thread.
+pub struct GossipDetector {
+    local_addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl GossipDetector {
+    /// Announce `machine_id` to `target` from `socket` every `interval`,
+    /// calling `on_duplicate` for every announcement of the same machine id
+    /// by another process.
+    ///
+    /// Broadcast is enabled on the socket. For multicast, join the group
+    /// on the socket before.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::net::UdpSocket;
+    /// use std::time::Duration;
+    ///
+    /// use snowflake::GossipDetector;
+    ///
+    /// let socket = UdpSocket::bind("0.0.0.0:7947").unwrap();
+    /// let detector = GossipDetector::spawn(
+    ///     42,
+    ///     socket,
+    ///     "255.255.255.255:7947".parse().unwrap(),
+    ///     Duration::from_secs(10),
+    ///     |duplicate| eprintln!("machine id also used by {}", duplicate.peer),
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn spawn(
+        machine_id: i64,
+        socket: UdpSocket,
+        target: SocketAddr,
+        interval: Duration,
+        on_duplicate: impl Fn(DuplicateWorker) + Send + 'static,
+    ) -> io::Result<GossipDetector> {
+        socket.set_broadcast(true)?;
+        socket.set_read_timeout(Some(POLL_INTERVAL.min(interval)))?;
+        let local_addr = socket.local_addr()?;
+
+        let nonce = new_nonce(local_addr);
+        let mut announcement = [0u8; DATAGRAM_LEN];
+        announcement[..4].copy_from_slice(MAGIC);
+        announcement[4..12].copy_from_slice(&machine_id.to_be_bytes());
+        announcement[12..].copy_from_slice(&nonce.to_be_bytes());
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = Arc::clone(&stop);
+
+            thread::spawn(move || {
+                let mut next_announcement = Instant::now();
+                let mut buffer = [0u8; DATAGRAM_LEN];
+
+                while !stop.load(Ordering::Relaxed) {
+                    if Instant::now() >= next_announcement {
+                        // A lost announcement is retried at the next interval.
+                        let _ = socket.send_to(&announcement, target);
+                        next_announcement = Instant::now() + interval;
+                    }
+
+                    let (len, peer) = match socket.recv_from(&mut buffer) {
+                        Ok(received) => received,
+                        Err(_) => continue,
+                    };
+                    if len != DATAGRAM_LEN || &buffer[..4] != MAGIC {
+                        continue;
+                    }
+
+                    let peer_machine_id = i64::from_be_bytes(buffer[4..12].try_into().unwrap());
+                    let peer_nonce = u64::from_be_bytes(buffer[12..].try_into().unwrap());
+                    if peer_machine_id == machine_id && peer_nonce != nonce {
+                        on_duplicate(DuplicateWorker { machine_id, peer });
+                    }
+                }
+            })
+        };
+
+        Ok(GossipDetector {
+            local_addr,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// The address the detector listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl fmt::Debug for GossipDetector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GossipDetector")
+            .field("local_addr", &self.local_addr)
+            .finish()
+    }
+}
+
+impl Drop for GossipDetector {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// Tells this process apart from others announcing the same machine id.
+fn new_nonce(local_addr: SocketAddr) -> u64 {
+    let nanos = UNIX_EPOCH
+        .elapsed()
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+    split_mix64(nanos ^ u64::from(std::process::id()) << 32 ^ u64::from(local_addr.port()))
+}
@@ -0,0 +1,183 @@
+//! Health reports of the generators.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::{
+    get_time_millis, LeaseKeeper, LeasedGenerator, SharedGenerator, SnowflakeIdGenerator,
+    UnixMillis,
+};
+
+/// Clock skew, in milliseconds, past which `Health::is_ready` fails.
+pub const MAX_HEALTHY_CLOCK_SKEW_MILLIS: i64 = 1000;
+
+/// The `LeaseStatus` type is the state of the machine id claim behind a
+/// generator.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LeaseStatus {
+    /// The machine id isn't leased from a coordinator.
+    Unmanaged,
+    /// The lease was renewed so far.
+    Held,
+    /// A renewal failed, the machine id must not be used anymore.
+    Lost,
+}
+
+/// The `Health` type is a health report of a generator.
+///
+/// `Display` writes it as one line of `key=value` pairs, ready to be
+/// returned by a readiness probe.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::{LeaseStatus, SnowflakeIdGenerator};
+///
+/// let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+/// id_generator.real_time_generate();
+///
+/// let health = id_generator.health().with_lease(LeaseStatus::Held);
+///
+/// assert!(health.is_ready());
+/// assert!(health.to_string().starts_with("status=ready "));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Health {
+    /// Milliseconds the local clock is ahead of the reference clock,
+    /// `None` without a reference.
+    pub clock_skew_millis: Option<i64>,
+
+    /// Milliseconds the last id issued is ahead of the local clock, after
+    /// the clock moved backwards or when generating faster than the time
+    /// units pass. Zero or less is in step with the clock.
+    pub ahead_of_clock_millis: i64,
+
+    /// Time since the timestamp of the last id issued, `None` if no id was
+    /// issued yet.
+    pub since_last_generation: Option<Duration>,
+
+    /// State of the machine id claim.
+    pub lease: LeaseStatus,
+
+    /// Ids generated ahead of time and waiting in a buffer.
+    pub buffered: usize,
+}
+
+impl Health {
+    /// Report the clock skew against `reference`, the time of a trusted
+    /// clock read at the same moment.
+    pub fn with_reference<T: UnixMillis>(mut self, reference: T) -> Health {
+        self.clock_skew_millis = Some(get_time_millis() - reference.unix_millis());
+        self
+    }
+
+    /// Report the lease status, `LeaseStatus::Unmanaged` otherwise.
+    pub fn with_lease(mut self, lease: LeaseStatus) -> Health {
+        self.lease = lease;
+        self
+    }
+
+    /// Whether the generator can be trusted with traffic: the lease isn't
+    /// lost, and neither the clock skew nor the lead over the clock is
+    /// past `MAX_HEALTHY_CLOCK_SKEW_MILLIS`.
+    pub fn is_ready(&self) -> bool {
+        let skewed = self
+            .clock_skew_millis
+            .is_some_and(|skew| skew.abs() > MAX_HEALTHY_CLOCK_SKEW_MILLIS);
+
+        self.lease != LeaseStatus::Lost
+            && !skewed
+            && self.ahead_of_clock_millis <= MAX_HEALTHY_CLOCK_SKEW_MILLIS
+    }
+}
+
+impl fmt::Display for Health {
+    /// Formats the report as `status=ready clock_skew_ms=3
+    /// ahead_of_clock_ms=0 since_last_generation_ms=12 lease=held
+    /// buffered=0`, with `unknown` for missing values.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.is_ready() {
+            "ready"
+        } else {
+            "not_ready"
+        };
+        write!(f, "status={} clock_skew_ms=", status)?;
+        match self.clock_skew_millis {
+            Some(skew) => write!(f, "{}", skew)?,
+            None => f.write_str("unknown")?,
+        }
+
+        write!(
+            f,
+            " ahead_of_clock_ms={} since_last_generation_ms=",
+            self.ahead_of_clock_millis
+        )?;
+        match self.since_last_generation {
+            Some(since) => write!(f, "{}", since.as_millis())?,
+            None => f.write_str("unknown")?,
+        }
+
+        let lease = match self.lease {
+            LeaseStatus::Unmanaged => "unmanaged",
+            LeaseStatus::Held => "held",
+            LeaseStatus::Lost => "lost",
+        };
+        write!(f, " lease={} buffered={}", lease, self.buffered)
+    }
+}
+
+impl SnowflakeIdGenerator {
+    /// A health report of the generator.
+    ///
+    /// The report knows nothing of a reference clock or a lease, add them
+    /// with `Health::with_reference` and `Health::with_lease`.
+    pub fn health(&self) -> Health {
+        let time_unit = self.layout.time_unit();
+        let now = self.time_source.try_now(time_unit).unwrap_or(self.last_time_millis);
+        let now = time_unit.to_millis(now);
+        let last = time_unit.to_millis(self.last_time_millis);
+        let stats = self.stats();
+
+        Health {
+            clock_skew_millis: None,
+            ahead_of_clock_millis: last - now,
+            since_last_generation: if stats.issued == 0 {
+                None
+            } else {
+                Some(Duration::from_millis((now - last).max(0) as u64))
+            },
+            lease: LeaseStatus::Unmanaged,
+            buffered: stats.buffered,
+        }
+    }
+}
+
+impl SharedGenerator {
+    /// A health report of the generator, see `SnowflakeIdGenerator::health`.
+    pub fn health(&self) -> Health {
+        self.with_generator(|generator| generator.health())
+    }
+}
+
+impl<L> LeaseKeeper<L> {
+    /// The status of the kept lease.
+    pub fn status(&self) -> LeaseStatus {
+        if self.is_lost() {
+            LeaseStatus::Lost
+        } else {
+            LeaseStatus::Held
+        }
+    }
+}
+
+impl LeasedGenerator {
+    /// A health report of the generator, with the status of its lease.
+    pub fn health(&self) -> Health {
+        let lease = if self.is_valid() {
+            LeaseStatus::Held
+        } else {
+            LeaseStatus::Lost
+        };
+        self.generator().health().with_lease(lease)
+    }
+}
@@ -0,0 +1,243 @@
+//! High-water marks persisted across restarts.
+
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{SnowflakeError, SnowflakeIdGenerator, TimeUnit};
+
+/// Longest nap of `wait_until_clock_reaches` between clock reads.
+pub const CLOCK_CATCH_UP_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The `HighWaterMarkFile` type persists the latest timestamp a generator
+/// may have issued ids for, in milliseconds since the Unix epoch.
+///
+/// A generator starts from the clock and forgets the ids it issued before
+/// a restart. If ntpd stepped the clock back meanwhile, it issues the
+/// same ids again. Save a mark ahead of the clock every so often, and
+/// resume from it on startup with `SnowflakeIdGenerator::resume_after`,
+/// which refuses to start, or waits, while the clock is behind the mark.
+///
+/// Saving `high_water_millis() + interval` every `interval` keeps the mark
+/// ahead of every id issued even if the process crashes, at the cost of a
+/// wait of up to `interval` on a quick restart.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use snowflake::{HighWaterMarkFile, SnowflakeIdGenerator};
+///
+/// let path = std::env::temp_dir().join(format!("snowflake-mark-{}", std::process::id()));
+/// let mark = HighWaterMarkFile::new(&path);
+///
+/// let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+/// if let Some(mark_millis) = mark.load().unwrap() {
+///     id_generator = id_generator
+///         .resume_after_waiting(mark_millis, Duration::from_secs(1))
+///         .unwrap();
+/// }
+///
+/// id_generator.real_time_generate();
+/// mark.save(id_generator.high_water_millis() + 100).unwrap();
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct HighWaterMarkFile {
+    path: PathBuf,
+}
+
+impl HighWaterMarkFile {
+    /// Constructs a new `HighWaterMarkFile` kept at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> HighWaterMarkFile {
+        HighWaterMarkFile { path: path.into() }
+    }
+
+    /// The file keeping the mark.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the mark, `None` if it was never saved.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `ErrorKind::InvalidData` if the file doesn't hold a
+    /// decimal timestamp.
+    pub fn load(&self) -> io::Result<Option<i64>> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => contents.trim().parse().map(Some).map_err(|_| {
+                io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("invalid high-water mark in {}", self.path.display()),
+                )
+            }),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Replace the mark with `mark_millis`.
+    ///
+    /// The mark is written to a temporary file, synced and renamed over the
+    /// old one, so a crash leaves either mark, never a torn one.
+    pub fn save(&self, mark_millis: i64) -> io::Result<()> {
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+
+        let mut file = File::create(&temporary)?;
+        writeln!(file, "{}", mark_millis)?;
+        file.sync_all()?;
+
+        fs::rename(&temporary, &self.path)
+    }
+}
+
+impl SnowflakeIdGenerator {
+    /// The timestamp of the last id issued, in milliseconds since the Unix
+    /// epoch, the high-water mark to persist.
+    pub fn high_water_millis(&self) -> i64 {
+        self.layout.time_unit().to_millis(self.last_time_millis)
+    }
+
+    /// Resume after `mark_millis`, a high-water mark persisted before a
+    /// restart, so no id is issued for its time unit or an earlier one.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `SnowflakeError::ClockMovedBackwards` if the clock
+    /// doesn't read past the time unit of the mark, or can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{get_time_millis, SnowflakeError, SnowflakeIdGenerator};
+    ///
+    /// let ahead = get_time_millis() + 60_000;
+    ///
+    /// assert!(matches!(
+    ///     SnowflakeIdGenerator::new(1, 1).resume_after(ahead),
+    ///     Err(SnowflakeError::ClockMovedBackwards { .. })
+    /// ));
+    /// ```
+    pub fn resume_after(self, mark_millis: i64) -> Result<SnowflakeIdGenerator, SnowflakeError> {
+        let time_unit = self.layout.time_unit();
+        let mark = time_unit.from_millis(mark_millis);
+
+        match self.time_source.try_now(time_unit) {
+            Some(now) if now > mark => Ok(self.resumed_at(mark)),
+            now => Err(SnowflakeError::ClockMovedBackwards {
+                last_millis: mark_millis,
+                now_millis: now.map_or(i64::MIN, |now| time_unit.to_millis(now)),
+            }),
+        }
+    }
+
+    /// Like `resume_after`, but waits for the clock to pass the mark if it
+    /// is behind by up to `max_wait`, see `wait_until_clock_reaches`.
+    pub fn resume_after_waiting(
+        self,
+        mark_millis: i64,
+        max_wait: Duration,
+    ) -> Result<SnowflakeIdGenerator, SnowflakeError> {
+        let time_unit = self.layout.time_unit();
+        let target_millis = time_unit.to_millis(time_unit.from_millis(mark_millis) + 1);
+
+        // On failure the clock is still behind, and `resume_after` reports
+        // it against the mark.
+        let _ = wait_until_clock_reaches(target_millis, max_wait);
+        self.resume_after(mark_millis)
+    }
+
+    // Move the high-water mark up to `mark`, in time units.
+    fn resumed_at(mut self, mark: i64) -> SnowflakeIdGenerator {
+        if mark > self.last_time_millis {
+            self.last_time_millis = mark;
+        }
+        self
+    }
+}
+
+/// Block until the clock reads `target_millis`, milliseconds since the
+/// Unix epoch, or later, for at most `timeout`.
+///
+/// For a clock a few seconds behind a persisted high-water mark after a
+/// restart: waiting briefly beats crashing. Returns the clock reading
+/// that reached the target.
+///
+/// # Errors
+///
+/// Fails with `SnowflakeError::ClockMovedBackwards` right away if the
+/// clock is behind by more than `timeout`, or once `timeout` passed.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use snowflake::{get_time_millis, wait_until_clock_reaches};
+///
+/// let target_millis = get_time_millis() + 20;
+/// let now_millis = wait_until_clock_reaches(target_millis, Duration::from_secs(1)).unwrap();
+///
+/// assert!(now_millis >= target_millis);
+/// ```
+pub fn wait_until_clock_reaches(
+    target_millis: i64,
+    timeout: Duration,
+) -> Result<i64, SnowflakeError> {
+    wait_until_clock_reaches_with(target_millis, timeout, |_| {})
+}
+
+/// Like `wait_until_clock_reaches`, but calls `progress` with the time
+/// left until the clock reaches the target, before every nap of at most
+/// `CLOCK_CATCH_UP_INTERVAL`, to log the wait or report it to a probe.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use snowflake::{get_time_millis, wait_until_clock_reaches_with};
+///
+/// let target_millis = get_time_millis() + 20;
+/// wait_until_clock_reaches_with(target_millis, Duration::from_secs(1), |left| {
+///     eprintln!("clock is {:?} behind the high-water mark, waiting", left);
+/// })
+/// .unwrap();
+/// ```
+pub fn wait_until_clock_reaches_with(
+    target_millis: i64,
+    timeout: Duration,
+    mut progress: impl FnMut(Duration),
+) -> Result<i64, SnowflakeError> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        let now_millis = TimeUnit::Milliseconds.try_now();
+        if let Some(now_millis) = now_millis {
+            if now_millis >= target_millis {
+                return Ok(now_millis);
+            }
+        }
+
+        // Keep reading an unreadable clock until the deadline.
+        let now = Instant::now();
+        let left = match now_millis {
+            Some(now_millis) => Duration::from_millis((target_millis - now_millis) as u64),
+            None => deadline.saturating_duration_since(now),
+        };
+        if now >= deadline || now + left > deadline {
+            return Err(SnowflakeError::ClockMovedBackwards {
+                last_millis: target_millis,
+                now_millis: now_millis.unwrap_or(i64::MIN),
+            });
+        }
+
+        progress(left);
+        thread::sleep(left.min(CLOCK_CATCH_UP_INTERVAL));
+    }
+}
@@ -0,0 +1,88 @@
+//! Just enough HTTP/1.1 to talk to cloud metadata servers and Consul.
+
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+
+// Send a request and return the body of a 2xx response.
+pub(crate) fn request(
+    endpoint: SocketAddr,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+    timeout: Duration,
+) -> io::Result<String> {
+    let mut stream = TcpStream::connect_timeout(&endpoint, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+
+    let mut request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+        method,
+        path,
+        endpoint,
+        body.len()
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+    stream.write_all(request.as_bytes())?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    parse_response(&response)
+}
+
+fn parse_response(response: &str) -> io::Result<String> {
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| invalid_data("truncated HTTP response"))?;
+    let mut lines = head.split("\r\n");
+
+    let status = lines
+        .next()
+        .and_then(|line| line.split(' ').nth(1))
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| invalid_data("malformed HTTP status line"))?;
+    if !(200..300).contains(&status) {
+        return Err(io::Error::other(format!("server answered {}", status)));
+    }
+
+    let chunked = lines.any(|line| {
+        let line = line.to_ascii_lowercase();
+        line.starts_with("transfer-encoding:") && line.contains("chunked")
+    });
+    if chunked {
+        decode_chunked(body)
+    } else {
+        Ok(body.to_string())
+    }
+}
+
+fn decode_chunked(mut body: &str) -> io::Result<String> {
+    let mut decoded = String::new();
+
+    loop {
+        let (size, rest) = body
+            .split_once("\r\n")
+            .ok_or_else(|| invalid_data("truncated HTTP chunk"))?;
+        let size = usize::from_str_radix(size.split(';').next().unwrap_or("").trim(), 16)
+            .map_err(|_| invalid_data("malformed HTTP chunk size"))?;
+        if size == 0 {
+            return Ok(decoded);
+        }
+
+        let chunk = rest
+            .get(..size)
+            .ok_or_else(|| invalid_data("truncated HTTP chunk"))?;
+        decoded.push_str(chunk);
+        body = rest[size..].trim_start_matches("\r\n");
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,143 @@
+//! Snowflakes with a segment fallback for clock incidents.
+
+use std::ops::Range;
+
+use crate::{IdGenerator, SnowflakeError, SnowflakeId, SnowflakeIdGenerator};
+
+/// A source of numeric id segments, such as a database sequence.
+///
+/// Every call hands out a range no other caller ever gets. The values
+/// should stay clear of the snowflakes issued next to them, a sequence
+/// starting at 1 stays far below any id of the current decades.
+pub trait SegmentStore {
+    type Error;
+
+    /// Reserve the next segment of about `size` ids.
+    fn next_segment(&mut self, size: u64) -> Result<Range<i64>, Self::Error>;
+}
+
+/// The `HybridGenerator` type issues snowflakes, and falls back to
+/// pre-fetched segment ids while the clock is unhealthy.
+///
+/// The clock is unhealthy when it reads earlier than the last id issued,
+/// `real_time_generate` would otherwise hand out ids that were already
+/// used. Fallback ids don't follow the time order of snowflakes.
+///
+/// # Examples
+///
+/// ```
+/// use std::ops::Range;
+///
+/// use snowflake::{HybridGenerator, SegmentStore, SnowflakeIdGenerator};
+///
+/// struct Sequence(i64);
+///
+/// impl SegmentStore for Sequence {
+///     type Error = ();
+///
+///     fn next_segment(&mut self, size: u64) -> Result<Range<i64>, ()> {
+///         let start = self.0;
+///         self.0 += size as i64;
+///         Ok(start..self.0)
+///     }
+/// }
+///
+/// let id_generator = SnowflakeIdGenerator::new(1, 1);
+/// let mut hybrid = HybridGenerator::new(id_generator, Sequence(1), 1000);
+///
+/// assert!(hybrid.generate().unwrap() > 1000);
+/// ```
+#[derive(Debug)]
+pub struct HybridGenerator<S> {
+    generator: SnowflakeIdGenerator,
+    store: S,
+    segment_size: u64,
+    segment: Range<i64>,
+}
+
+impl<S: SegmentStore> HybridGenerator<S> {
+    /// Constructs a new `HybridGenerator`, fetching segments of
+    /// `segment_size` ids from `store`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `segment_size` is zero.
+    pub fn new(generator: SnowflakeIdGenerator, store: S, segment_size: u64) -> HybridGenerator<S> {
+        assert!(segment_size > 0, "segment_size must be positive");
+
+        HybridGenerator {
+            generator,
+            store,
+            segment_size,
+            segment: 0..0,
+        }
+    }
+
+    /// Generate an id.
+    ///
+    /// A segment is fetched ahead of time while the clock is healthy, so
+    /// the first ids of an incident don't wait on the store. That prefetch
+    /// is best-effort, a store failing meanwhile is retried on the next
+    /// call. Fails only if the store fails during an incident.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the store hands out an empty segment during an incident.
+    pub fn generate(&mut self) -> Result<i64, S::Error> {
+        if self.is_clock_healthy() {
+            if self.segment.is_empty() {
+                if let Ok(segment) = self.store.next_segment(self.segment_size) {
+                    self.segment = segment;
+                }
+            }
+
+            return Ok(self.generator.real_time_generate());
+        }
+
+        match self.segment.next() {
+            Some(id) => Ok(id),
+            None => {
+                self.segment = self.store.next_segment(self.segment_size)?;
+                Ok(self
+                    .segment
+                    .next()
+                    .expect("SegmentStore handed out an empty segment"))
+            }
+        }
+    }
+
+    /// Whether the clock reads no earlier than the last id issued, and can
+    /// be read at all.
+    pub fn is_clock_healthy(&self) -> bool {
+        let time_unit = self.generator.layout.time_unit();
+        self.generator
+            .time_source
+            .try_now(time_unit)
+            .is_some_and(|now| now >= self.generator.last_time_millis)
+    }
+
+    /// Segment ids left before the store is called again.
+    pub fn segment_remaining(&self) -> u64 {
+        (self.segment.end - self.segment.start).max(0) as u64
+    }
+
+    /// The wrapped generator.
+    pub fn generator(&self) -> &SnowflakeIdGenerator {
+        &self.generator
+    }
+
+    /// The wrapped generator, mutable.
+    pub fn generator_mut(&mut self) -> &mut SnowflakeIdGenerator {
+        &mut self.generator
+    }
+}
+
+/// Generates with `HybridGenerator::generate`, a failure of the store is
+/// `SnowflakeError::SegmentStoreFailed`.
+impl<S: SegmentStore> IdGenerator for HybridGenerator<S> {
+    fn next_id(&mut self) -> Result<SnowflakeId, SnowflakeError> {
+        self.generate()
+            .map(SnowflakeId)
+            .map_err(|_| SnowflakeError::SegmentStoreFailed)
+    }
+}
@@ -0,0 +1,511 @@
+//! The `SnowflakeId` newtype.
+
+use std::time::Duration;
+
+use crate::encoding::{self, BASE32_ALPHABET, BASE58_ALPHABET, BASE62_ALPHABET};
+use crate::{
+    get_time_millis, split_mix64, DurationMillis, Layout, UnixMillis, MACHINE_MASK,
+    MACHINE_SHIFT, SEQUENCE_MASK, TIMESTAMP_SHIFT,
+};
+
+// Length of the base62 representation, enough for any i64.
+const BASE62_LEN: usize = 11;
+
+// Length of the base58 representation, enough for any i64.
+const BASE58_LEN: usize = 11;
+
+// Length of the base32 representation, enough for any i64.
+const BASE32_LEN: usize = 13;
+
+// Length of the sortable decimal representation, enough for any u64.
+const DECIMAL_LEN: usize = 20;
+
+// Bits below the timestamp, machine bits and auto-increment record.
+const LOW_MASK: i64 = (1 << TIMESTAMP_SHIFT) - 1;
+
+/// How `SnowflakeId::partition_key_with` maps ids to partitions.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PartitionStrategy {
+    /// Hash the whole id.
+    ///
+    /// Spreads ids evenly whatever the traffic looks like. Taking the raw id
+    /// modulo the partition count doesn't: most milliseconds only use the
+    /// first few auto-increment records, so the low bits are heavily biased
+    /// towards zero.
+    HashedId,
+    /// The machine bits modulo the partition count.
+    ///
+    /// Keeps every id of a machine on the same partition, and so in order,
+    /// but the spread is only as even as the machine ids are.
+    MachineBits,
+}
+
+/// The `SnowflakeId` type is a generated id.
+///
+/// It wraps the `i64` returned by the generators, and carries the
+/// conversions to and from other id schemes.
+///
+/// With the `rkyv` feature, ids archive as `ArchivedSnowflakeId`, a little
+/// endian `i64` read in place from memory-mapped records.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash), compare(PartialEq, PartialOrd))
+)]
+pub struct SnowflakeId(pub i64);
+
+impl SnowflakeId {
+    /// The milliseconds of the clock stored in the id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{SnowflakeId, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+    /// let id = SnowflakeId(id_generator.real_time_generate());
+    ///
+    /// assert_eq!(id.timestamp(), id_generator.last_time_millis);
+    /// ```
+    pub fn timestamp(self) -> i64 {
+        self.0 >> TIMESTAMP_SHIFT
+    }
+
+    /// The machine bits stored in the id.
+    pub fn machine_bits(self) -> i64 {
+        (self.0 >> MACHINE_SHIFT) & MACHINE_MASK
+    }
+
+    /// The auto-increment record stored in the id.
+    pub fn idx(self) -> u16 {
+        (self.0 & SEQUENCE_MASK) as u16
+    }
+
+    /// A new id shifted in time by `duration`.
+    ///
+    /// Machine bits and auto-increment record are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use snowflake::SnowflakeId;
+    ///
+    /// let id = SnowflakeId(1_700_000_000_000 << 22 | 42);
+    /// let later = id.offset(Duration::from_secs(1));
+    ///
+    /// assert_eq!(later.timestamp(), 1_700_000_001_000);
+    /// assert_eq!(later.idx(), 42);
+    /// ```
+    pub fn offset<D: DurationMillis>(self, duration: D) -> SnowflakeId {
+        let timestamp = self.timestamp() + duration.duration_millis();
+        SnowflakeId(timestamp << TIMESTAMP_SHIFT | (self.0 & LOW_MASK))
+    }
+
+    /// A new id with the timestamp replaced by `timestamp`.
+    ///
+    /// Machine bits and auto-increment record are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// use snowflake::SnowflakeId;
+    ///
+    /// let id = SnowflakeId(1_700_000_000_000 << 22 | 42);
+    /// let moved = id.with_timestamp(UNIX_EPOCH + Duration::from_millis(1_600_000_000_000));
+    ///
+    /// assert_eq!(moved.timestamp(), 1_600_000_000_000);
+    /// assert_eq!(moved.idx(), 42);
+    /// ```
+    pub fn with_timestamp<T: UnixMillis>(self, timestamp: T) -> SnowflakeId {
+        SnowflakeId(timestamp.unix_millis() << TIMESTAMP_SHIFT | (self.0 & LOW_MASK))
+    }
+
+    /// The smallest id of the same millisecond.
+    ///
+    /// Machine bits and auto-increment record are zeroed, which makes it a
+    /// boundary for range queries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::SnowflakeId;
+    ///
+    /// let id = SnowflakeId(1_700_000_000_000 << 22 | 42);
+    ///
+    /// assert_eq!(id.floor(), SnowflakeId(1_700_000_000_000 << 22));
+    /// ```
+    pub fn floor(self) -> SnowflakeId {
+        SnowflakeId(self.0 & !LOW_MASK)
+    }
+
+    /// Whether the id was created strictly before `timestamp`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// use snowflake::{SnowflakeId, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    /// let id = SnowflakeId(id_generator.real_time_generate());
+    /// let an_hour_ago = SystemTime::now() - Duration::from_secs(3600);
+    ///
+    /// assert!(id.created_after(an_hour_ago));
+    /// assert!(!id.created_before(an_hour_ago));
+    /// ```
+    pub fn created_before<T: UnixMillis>(self, timestamp: T) -> bool {
+        self.timestamp() < timestamp.unix_millis()
+    }
+
+    /// Whether the id was created strictly after `timestamp`.
+    pub fn created_after<T: UnixMillis>(self, timestamp: T) -> bool {
+        self.timestamp() > timestamp.unix_millis()
+    }
+
+    /// Whether the id was created within `start..end`.
+    pub fn created_between<T: UnixMillis>(self, start: T, end: T) -> bool {
+        (start.unix_millis()..end.unix_millis()).contains(&self.timestamp())
+    }
+
+    /// Whether the id was created within the last `duration`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use snowflake::{SnowflakeId, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    /// let id = SnowflakeId(id_generator.real_time_generate());
+    ///
+    /// assert!(id.created_within(Duration::from_secs(3600)));
+    /// ```
+    pub fn created_within<D: DurationMillis>(self, duration: D) -> bool {
+        self.timestamp() >= get_time_millis() - duration.duration_millis()
+    }
+
+    /// Milliseconds from the creation of `a` to the creation of `b`,
+    /// negative if `b` was created first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::SnowflakeId;
+    ///
+    /// let a = SnowflakeId(1_700_000_000_000 << 22);
+    /// let b = SnowflakeId(1_700_000_001_500 << 22 | 42);
+    ///
+    /// assert_eq!(SnowflakeId::millis_between(a, b), 1500);
+    /// assert_eq!(SnowflakeId::millis_between(b, a), -1500);
+    /// ```
+    pub fn millis_between(a: SnowflakeId, b: SnowflakeId) -> i64 {
+        b.timestamp() - a.timestamp()
+    }
+
+    /// Time from the creation of `a` to the creation of `b`, negative if
+    /// `b` was created first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::Duration;
+    /// use snowflake::SnowflakeId;
+    ///
+    /// let a = SnowflakeId(1_700_000_000_000 << 22);
+    /// let b = SnowflakeId(1_700_000_001_500 << 22 | 42);
+    ///
+    /// assert_eq!(SnowflakeId::time_between(a, b), Duration::milliseconds(1500));
+    /// ```
+    #[cfg(feature = "chrono")]
+    pub fn time_between(a: SnowflakeId, b: SnowflakeId) -> chrono::Duration {
+        chrono::Duration::milliseconds(SnowflakeId::millis_between(a, b))
+    }
+
+    /// Time since the id was created, zero for ids from the future.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use snowflake::{SnowflakeId, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    /// let id = SnowflakeId(id_generator.real_time_generate());
+    ///
+    /// assert!(id.elapsed() < Duration::from_secs(1));
+    /// ```
+    pub fn elapsed(self) -> Duration {
+        Duration::from_millis((get_time_millis() - self.timestamp()).max(0) as u64)
+    }
+
+    /// Time since the id was created, zero for ids from the future, read
+    /// with `Layout::default()`, see `Layout::age` for other layouts.
+    ///
+    /// Makes expiry by id a one-liner.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the clock reads before the Unix epoch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use snowflake::{SnowflakeId, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    /// let request_id = SnowflakeId(id_generator.real_time_generate());
+    ///
+    /// let expired = request_id.age() > Duration::from_secs(5 * 60);
+    /// assert!(!expired);
+    /// ```
+    pub fn age(self) -> Duration {
+        Layout::default().age(self.0)
+    }
+
+    /// The id as 11 base62 characters.
+    ///
+    /// Zero padded, so strings sort the same way as the ids.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::SnowflakeId;
+    ///
+    /// let id = SnowflakeId(6_725_650_666_386_976_769);
+    ///
+    /// assert_eq!(id.to_base62(), "80pYBbV5CzZ");
+    /// assert_eq!(SnowflakeId::from_base62("80pYBbV5CzZ"), Some(id));
+    /// ```
+    pub fn to_base62(self) -> String {
+        encoding::encode(&self.0.to_be_bytes(), BASE62_ALPHABET, BASE62_LEN)
+    }
+
+    /// Parse an id from base62, `None` if the text isn't base62 or doesn't
+    /// fit a non-negative id.
+    pub fn from_base62(text: &str) -> Option<SnowflakeId> {
+        if text.is_empty() || text.len() > BASE62_LEN {
+            return None;
+        }
+
+        let id = i64::from_be_bytes(encoding::decode(text, BASE62_ALPHABET)?);
+        if id < 0 {
+            return None;
+        }
+        Some(SnowflakeId(id))
+    }
+
+    /// The id as 11 characters of base58, in the Bitcoin alphabet.
+    ///
+    /// Leaves out 0, O, I and l, which look alike in print, for ids read
+    /// aloud or typed back by people. Padded with `1`, the zero digit, so
+    /// strings sort the same way as the ids.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::SnowflakeId;
+    ///
+    /// let id = SnowflakeId(6_725_650_666_386_976_769);
+    ///
+    /// assert_eq!(id.to_base58(), "GcVGHK4uX8c");
+    /// assert_eq!(SnowflakeId::from_base58("GcVGHK4uX8c"), Some(id));
+    /// ```
+    pub fn to_base58(self) -> String {
+        encoding::encode(&self.0.to_be_bytes(), BASE58_ALPHABET, BASE58_LEN)
+    }
+
+    /// Parse an id from base58, `None` if the text isn't base58 or doesn't
+    /// fit a non-negative id.
+    pub fn from_base58(text: &str) -> Option<SnowflakeId> {
+        if text.is_empty() || text.len() > BASE58_LEN {
+            return None;
+        }
+
+        let id = i64::from_be_bytes(encoding::decode(text, BASE58_ALPHABET)?);
+        if id < 0 {
+            return None;
+        }
+        Some(SnowflakeId(id))
+    }
+
+    /// The id as 11 characters of base64url, the 8 big-endian bytes of
+    /// the id without padding.
+    ///
+    /// The most compact text form, safe in URLs, tokens and cookies. The
+    /// strings don't sort like the ids.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::SnowflakeId;
+    ///
+    /// let id = SnowflakeId(6_725_650_666_386_976_769);
+    ///
+    /// assert_eq!(id.to_base64url(), "XVZPpaVdcAE");
+    /// assert_eq!(SnowflakeId::from_base64url("XVZPpaVdcAE"), Some(id));
+    /// ```
+    pub fn to_base64url(self) -> String {
+        encoding::encode_base64url(self.0.to_be_bytes())
+    }
+
+    /// Parse an id from its 11 character base64url form, `None` if the
+    /// text isn't exactly that, in its canonical form, or doesn't fit a
+    /// non-negative id.
+    ///
+    /// Strict: padding, trailing characters and set unused bits are all
+    /// rejected, so a token holds a single spelling of an id.
+    pub fn from_base64url(text: &str) -> Option<SnowflakeId> {
+        let id = i64::from_be_bytes(encoding::decode_base64url(text)?);
+        if id < 0 {
+            return None;
+        }
+        Some(SnowflakeId(id))
+    }
+
+    /// The id as 20 zero padded decimal digits.
+    ///
+    /// Strings sort the same way as the ids, which makes them usable as
+    /// object store or key-value store keys. Returns `None` for negative
+    /// ids, which would render as `-` followed by 19 digits and sort before
+    /// every id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::SnowflakeId;
+    ///
+    /// let id = SnowflakeId(6_725_650_666_386_976_769);
+    ///
+    /// assert_eq!(id.to_sortable_decimal().unwrap(), "06725650666386976769");
+    /// assert_eq!(SnowflakeId::from_sortable_decimal("06725650666386976769"), Some(id));
+    /// ```
+    pub fn to_sortable_decimal(self) -> Option<String> {
+        if self.0 < 0 {
+            return None;
+        }
+        Some(format!("{:0width$}", self.0, width = DECIMAL_LEN))
+    }
+
+    /// Parse an id from its 20 digit decimal form, `None` if the text isn't
+    /// 20 digits or doesn't fit a non-negative id.
+    pub fn from_sortable_decimal(text: &str) -> Option<SnowflakeId> {
+        if text.len() != DECIMAL_LEN || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+        text.parse().ok().map(SnowflakeId)
+    }
+
+    /// The id as 13 characters of Crockford's base32.
+    ///
+    /// Zero padded, so strings sort the same way as the ids, and shorter
+    /// than the decimal form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::SnowflakeId;
+    ///
+    /// let id = SnowflakeId(6_725_650_666_386_976_769);
+    ///
+    /// assert_eq!(id.to_base32(), "5TNJFMPJNTW01");
+    /// assert_eq!(SnowflakeId::from_base32("5TNJFMPJNTW01"), Some(id));
+    /// ```
+    pub fn to_base32(self) -> String {
+        encoding::encode(&self.0.to_be_bytes(), BASE32_ALPHABET, BASE32_LEN)
+    }
+
+    /// Parse an id from its 13 character base32 form, `None` if the text
+    /// isn't uppercase base32 or doesn't fit a non-negative id.
+    pub fn from_base32(text: &str) -> Option<SnowflakeId> {
+        if text.len() != BASE32_LEN {
+            return None;
+        }
+
+        let id = i64::from_be_bytes(encoding::decode(text, BASE32_ALPHABET)?);
+        if id < 0 {
+            return None;
+        }
+        Some(SnowflakeId(id))
+    }
+
+    /// The partition, out of `num_partitions`, to key the id to.
+    ///
+    /// Uses `PartitionStrategy::HashedId`. The hash is stable across
+    /// releases and platforms, so keys can be computed by any producer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{SnowflakeId, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    /// let id = SnowflakeId(id_generator.real_time_generate());
+    ///
+    /// assert!(id.partition_key(12) < 12);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_partitions` is zero.
+    pub fn partition_key(self, num_partitions: u32) -> u32 {
+        self.partition_key_with(num_partitions, PartitionStrategy::HashedId)
+    }
+
+    /// The partition, out of `num_partitions`, to key the id to with the
+    /// given strategy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{PartitionStrategy, SnowflakeId};
+    ///
+    /// let id = SnowflakeId(1_700_000_000_000 << 22 | 37 << 12 | 5);
+    ///
+    /// assert_eq!(id.partition_key_with(8, PartitionStrategy::MachineBits), 5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_partitions` is zero.
+    pub fn partition_key_with(self, num_partitions: u32, strategy: PartitionStrategy) -> u32 {
+        assert!(num_partitions > 0, "num_partitions must be positive");
+
+        let key = match strategy {
+            PartitionStrategy::HashedId => split_mix64(self.0 as u64),
+            PartitionStrategy::MachineBits => self.machine_bits() as u64,
+        };
+        (key % num_partitions as u64) as u32
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl ArchivedSnowflakeId {
+    /// The archived id, read in place.
+    pub fn get(&self) -> SnowflakeId {
+        SnowflakeId(self.0.to_native())
+    }
+}
+
+/// Wraps any `i64` without validation, negative ones included, and with
+/// it the infallible `TryFrom<i64>`. Validate with `SnowflakeId::try_new`.
+impl From<i64> for SnowflakeId {
+    fn from(id: i64) -> SnowflakeId {
+        SnowflakeId(id)
+    }
+}
+
+impl From<SnowflakeId> for i64 {
+    fn from(id: SnowflakeId) -> i64 {
+        id.0
+    }
+}
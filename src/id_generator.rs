@@ -0,0 +1,69 @@
+//! A common interface over the generators.
+
+use crate::{SnowflakeError, SnowflakeId, SnowflakeIdGenerator};
+
+/// The `IdGenerator` trait is implemented by every generator of the crate,
+/// so application code can be generic over how its ids are made.
+///
+/// `Id` is `SnowflakeId` for the generators of 64-bit snowflakes, and the
+/// id type otherwise, e.g. `Snowflake128`.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::{
+///     DeterministicGenerator, IdGenerator, SnowflakeError, SnowflakeId, SnowflakeIdGenerator,
+/// };
+///
+/// fn create_order<G: IdGenerator>(ids: &mut G) -> Result<SnowflakeId, SnowflakeError> {
+///     ids.next_id()
+/// }
+///
+/// create_order(&mut SnowflakeIdGenerator::new(1, 1)).unwrap();
+/// create_order(&mut DeterministicGenerator::with_seed(7)).unwrap();
+/// ```
+pub trait IdGenerator<Id = SnowflakeId> {
+    /// Generate the next id.
+    fn next_id(&mut self) -> Result<Id, SnowflakeError>;
+}
+
+/// The `LazyGenerator` type generates with `lazy_generate`, for code
+/// generic over `IdGenerator`.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::{IdGenerator, LazyGenerator, SnowflakeIdGenerator};
+///
+/// let mut id_generator = LazyGenerator(SnowflakeIdGenerator::new(1, 1));
+///
+/// let first = id_generator.next_id().unwrap();
+/// assert!(first < id_generator.next_id().unwrap());
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct LazyGenerator(pub SnowflakeIdGenerator);
+
+/// Generates with `try_real_time_generate`.
+impl IdGenerator for SnowflakeIdGenerator {
+    fn next_id(&mut self) -> Result<SnowflakeId, SnowflakeError> {
+        self.try_real_time_generate()
+    }
+}
+
+impl IdGenerator for LazyGenerator {
+    fn next_id(&mut self) -> Result<SnowflakeId, SnowflakeError> {
+        Ok(SnowflakeId(self.0.lazy_generate()))
+    }
+}
+
+impl<Id, G: IdGenerator<Id> + ?Sized> IdGenerator<Id> for &mut G {
+    fn next_id(&mut self) -> Result<Id, SnowflakeError> {
+        (**self).next_id()
+    }
+}
+
+impl<Id, G: IdGenerator<Id> + ?Sized> IdGenerator<Id> for Box<G> {
+    fn next_id(&mut self) -> Result<Id, SnowflakeError> {
+        (**self).next_id()
+    }
+}
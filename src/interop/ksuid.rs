@@ -0,0 +1,176 @@
+//! KSUID conversions.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::encoding::{self, BASE62_ALPHABET};
+use crate::{Snowflake, SnowflakeError, SnowflakeId, TIMESTAMP_SHIFT};
+
+/// Seconds between the Unix epoch and the KSUID epoch.
+pub const KSUID_EPOCH_SECONDS: i64 = 1_400_000_000;
+
+// Length of the base62 representation.
+const KSUID_STRING_LEN: usize = 27;
+
+/// The `Ksuid` type is a 20 byte K-Sortable Unique IDentifier.
+///
+/// Four bytes of seconds since the KSUID epoch followed by a 16 byte payload.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Ksuid(pub [u8; 20]);
+
+/// The `ParseKsuidError` type is returned for malformed KSUID strings.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParseKsuidError;
+
+impl Ksuid {
+    /// The seconds of the Unix clock stored in the KSUID.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Ksuid;
+    ///
+    /// let ksuid: Ksuid = "0ujtsYcgvSTl8PAuAdqWYSMnLOv".parse().unwrap();
+    ///
+    /// assert_eq!(ksuid.timestamp(), 1_507_608_047);
+    /// ```
+    pub fn timestamp(&self) -> i64 {
+        let mut seconds = [0u8; 4];
+        seconds.copy_from_slice(&self.0[..4]);
+        i64::from(u32::from_be_bytes(seconds)) + KSUID_EPOCH_SECONDS
+    }
+
+    /// The 16 byte payload of the KSUID.
+    pub fn payload(&self) -> &[u8] {
+        &self.0[4..]
+    }
+}
+
+impl fmt::Display for Ksuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&encoding::encode(
+            &self.0,
+            BASE62_ALPHABET,
+            KSUID_STRING_LEN,
+        ))
+    }
+}
+
+impl FromStr for Ksuid {
+    type Err = ParseKsuidError;
+
+    fn from_str(s: &str) -> Result<Ksuid, ParseKsuidError> {
+        if s.len() != KSUID_STRING_LEN {
+            return Err(ParseKsuidError);
+        }
+
+        encoding::decode(s, BASE62_ALPHABET)
+            .map(Ksuid)
+            .ok_or(ParseKsuidError)
+    }
+}
+
+impl fmt::Display for ParseKsuidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid KSUID string")
+    }
+}
+
+impl std::error::Error for ParseKsuidError {}
+
+impl SnowflakeId {
+    /// Convert the id into a KSUID.
+    ///
+    /// The timestamp is truncated to seconds, the whole id goes into the
+    /// payload so `from_ksuid` can restore it exactly.
+    ///
+    /// Fails with `SnowflakeError::EpochOverflow` if the id is from before
+    /// the KSUID epoch, May 2014, or past its 136 years.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{SnowflakeId, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+    /// let id = SnowflakeId(id_generator.real_time_generate());
+    ///
+    /// let ksuid = id.to_ksuid().unwrap();
+    ///
+    /// assert_eq!(ksuid.timestamp(), id.timestamp() / 1000);
+    /// assert_eq!(SnowflakeId::from_ksuid(ksuid), Some(id));
+    /// ```
+    pub fn to_ksuid(self) -> Result<Ksuid, SnowflakeError> {
+        let seconds = self.timestamp() / 1000 - KSUID_EPOCH_SECONDS;
+        let seconds =
+            u32::try_from(seconds).map_err(|_| SnowflakeError::EpochOverflow(self.timestamp()))?;
+
+        let mut bytes = [0u8; 20];
+        bytes[..4].copy_from_slice(&seconds.to_be_bytes());
+        bytes[4..12].copy_from_slice(&self.0.to_be_bytes());
+
+        Ok(Ksuid(bytes))
+    }
+
+    /// Convert a KSUID into an id.
+    ///
+    /// KSUIDs made by `to_ksuid` give back the original id, any other KSUID
+    /// gives the first id of its second, with zero machine bits and
+    /// auto-increment record. Returns `None` if the timestamp doesn't fit
+    /// the id, for KSUIDs after 2039.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{Ksuid, SnowflakeId};
+    ///
+    /// let ksuid: Ksuid = "0ujtsYcgvSTl8PAuAdqWYSMnLOv".parse().unwrap();
+    ///
+    /// assert_eq!(SnowflakeId::from_ksuid(ksuid).unwrap().timestamp(), 1_507_608_047_000);
+    /// ```
+    pub fn from_ksuid(ksuid: Ksuid) -> Option<SnowflakeId> {
+        let mut embedded = [0u8; 8];
+        embedded.copy_from_slice(&ksuid.0[4..12]);
+        let embedded = SnowflakeId(i64::from_be_bytes(embedded));
+
+        let is_embedded = ksuid.0[12..].iter().all(|&byte| byte == 0)
+            && embedded.0 >= 0
+            && embedded.timestamp() / 1000 == ksuid.timestamp();
+
+        if is_embedded {
+            return Some(embedded);
+        }
+
+        let timestamp = ksuid.timestamp() * 1000;
+        if timestamp > i64::MAX >> TIMESTAMP_SHIFT {
+            return None;
+        }
+
+        Some(SnowflakeId(timestamp << TIMESTAMP_SHIFT))
+    }
+}
+
+impl Snowflake {
+    /// Decode the timestamp of a KSUID into a `Snowflake`.
+    ///
+    /// The timestamp is in milliseconds like any other `Snowflake`,
+    /// machine bits and auto-increment record are zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{Ksuid, Snowflake};
+    ///
+    /// let ksuid: Ksuid = "0ujtsYcgvSTl8PAuAdqWYSMnLOv".parse().unwrap();
+    ///
+    /// assert_eq!(Snowflake::from_ksuid(ksuid).timestamp, 1_507_608_047_000);
+    /// ```
+    pub fn from_ksuid(ksuid: Ksuid) -> Snowflake {
+        Snowflake {
+            timestamp: ksuid.timestamp() * 1000,
+            machine_bits: 0,
+            idx: 0,
+        }
+    }
+}
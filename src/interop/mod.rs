@@ -0,0 +1,21 @@
+//! Conversions between snowflakes and other id schemes.
+
+mod ksuid;
+#[cfg(feature = "bson")]
+mod object_id;
+#[cfg(feature = "prost")]
+mod prost;
+#[cfg(feature = "uuid")]
+mod timeuuid;
+#[cfg(feature = "ulid")]
+mod ulid;
+#[cfg(feature = "uuid")]
+mod uuid;
+
+#[cfg(feature = "prost")]
+pub use self::prost::SNOWFLAKE_PROTO;
+#[cfg(feature = "ulid")]
+pub use self::ulid::UlidGenerator;
+#[cfg(feature = "uuid")]
+pub use self::uuid::UuidV7Generator;
+pub use ksuid::{Ksuid, ParseKsuidError, KSUID_EPOCH_SECONDS};
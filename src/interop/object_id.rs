@@ -0,0 +1,94 @@
+//! MongoDB ObjectId conversions.
+
+use bson::oid::ObjectId;
+
+use crate::{SnowflakeId, MACHINE_MASK, MACHINE_SHIFT, SEQUENCE_MASK, TIMESTAMP_SHIFT};
+
+// Bits of the process unique value below the milliseconds.
+const MILLIS_SHIFT: u32 = 30;
+
+// Process unique value bits between the milliseconds and the machine bits.
+const PADDING_MASK: u64 = ((1 << MILLIS_SHIFT) - 1) & !(MACHINE_MASK as u64);
+
+impl SnowflakeId {
+    /// Convert the id into an ObjectId.
+    ///
+    /// The seconds land in the ObjectId timestamp, the machine bits in the
+    /// low bits of the process unique value, below the milliseconds of the
+    /// second, and the auto-increment record in the counter. ObjectIds sort
+    /// the same way as the ids they came from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{SnowflakeId, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+    /// let id = SnowflakeId(id_generator.real_time_generate());
+    ///
+    /// let object_id = id.to_object_id();
+    ///
+    /// assert_eq!(object_id.timestamp().timestamp_millis() / 1000, id.timestamp() / 1000);
+    /// assert_eq!(SnowflakeId::from_object_id(object_id), Some(id));
+    /// ```
+    pub fn to_object_id(self) -> ObjectId {
+        let seconds = (self.timestamp() / 1000) as u32;
+        let millis = (self.timestamp() % 1000) as u64;
+        let process = millis << MILLIS_SHIFT | self.machine_bits() as u64;
+
+        let mut bytes = [0u8; 12];
+        bytes[..4].copy_from_slice(&seconds.to_be_bytes());
+        bytes[4..9].copy_from_slice(&process.to_be_bytes()[3..]);
+        bytes[9..].copy_from_slice(&(self.idx() as u32).to_be_bytes()[1..]);
+
+        ObjectId::from_bytes(bytes)
+    }
+
+    /// Convert an ObjectId into an id.
+    ///
+    /// ObjectIds made by `to_object_id` give back the original id. Any
+    /// other ObjectId maps to the start of its second, with the low bits of
+    /// the process unique value as machine bits and the low bits of the
+    /// counter as auto-increment record. Returns `None` if the timestamp
+    /// doesn't fit the id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bson::oid::ObjectId;
+    /// use snowflake::SnowflakeId;
+    ///
+    /// let object_id = ObjectId::parse_str("64e26b55a1b2c3d4e5000001").unwrap();
+    /// let id = SnowflakeId::from_object_id(object_id).unwrap();
+    ///
+    /// assert_eq!(id.timestamp(), 1_692_560_213_000);
+    /// assert_eq!(id.idx(), 1);
+    /// ```
+    pub fn from_object_id(object_id: ObjectId) -> Option<SnowflakeId> {
+        let bytes = object_id.bytes();
+
+        let mut seconds = [0u8; 4];
+        seconds.copy_from_slice(&bytes[..4]);
+        let mut process = [0u8; 8];
+        process[3..].copy_from_slice(&bytes[4..9]);
+        let mut counter = [0u8; 4];
+        counter[1..].copy_from_slice(&bytes[9..]);
+
+        let process = u64::from_be_bytes(process);
+        let millis = process >> MILLIS_SHIFT;
+        let is_embedded = millis < 1000 && process & PADDING_MASK == 0;
+
+        let timestamp =
+            u32::from_be_bytes(seconds) as i64 * 1000 + if is_embedded { millis as i64 } else { 0 };
+        if timestamp > i64::MAX >> TIMESTAMP_SHIFT {
+            return None;
+        }
+
+        let machine_bits = process as i64 & MACHINE_MASK;
+        let idx = u32::from_be_bytes(counter) as i64 & SEQUENCE_MASK;
+
+        Some(SnowflakeId(
+            timestamp << TIMESTAMP_SHIFT | machine_bits << MACHINE_SHIFT | idx,
+        ))
+    }
+}
@@ -0,0 +1,98 @@
+//! Protobuf support through prost.
+//!
+//! Ids go on the wire as `sfixed64`, 8 bytes whatever the id, where a
+//! varint takes 9 for any id of the current era. `SnowflakeId` implements
+//! `prost::Message` as the `snowflake.SnowflakeId` message of
+//! `SNOWFLAKE_PROTO`, so prost-build can map fields of that type straight
+//! to it:
+//!
+//! ```ignore
+//! prost_build::Config::new()
+//!     .extern_path(".snowflake.SnowflakeId", "::snowflake::SnowflakeId")
+//!     .compile_protos(&["proto/user.proto"], &["proto/"])?;
+//! ```
+//!
+//! Plain `sfixed64` fields are `i64`s, and convert with `From`. APIs
+//! consumed from JavaScript, whose numbers lose ids past 2^53, should use
+//! `string` fields, which convert with `String::from` and
+//! `SnowflakeId::try_from`.
+
+use std::convert::TryFrom;
+
+use ::prost::bytes::{Buf, BufMut};
+use ::prost::encoding::{self, DecodeContext, WireType};
+use ::prost::{DecodeError, Message};
+
+use crate::{SnowflakeError, SnowflakeId};
+
+/// The `snowflake.SnowflakeId` message, to import into `.proto` files
+/// carrying ids.
+pub const SNOWFLAKE_PROTO: &str = include_str!("../../proto/snowflake.proto");
+
+// Field number of the id in `snowflake.SnowflakeId`.
+const VALUE_TAG: u32 = 1;
+
+/// Ids are the `snowflake.SnowflakeId` message of `SNOWFLAKE_PROTO`.
+///
+/// # Examples
+///
+/// ```
+/// use prost::Message;
+/// use snowflake::SnowflakeId;
+///
+/// let id = SnowflakeId(6725650666386976769);
+/// let bytes = id.encode_to_vec();
+///
+/// assert_eq!(bytes.len(), 9);
+/// assert_eq!(SnowflakeId::decode(bytes.as_slice()).unwrap(), id);
+/// ```
+impl Message for SnowflakeId {
+    fn encode_raw(&self, buf: &mut impl BufMut) {
+        // Proto3 leaves default values off the wire.
+        if self.0 != 0 {
+            encoding::sfixed64::encode(VALUE_TAG, &self.0, buf);
+        }
+    }
+
+    fn merge_field(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut impl Buf,
+        ctx: DecodeContext,
+    ) -> Result<(), DecodeError> {
+        if tag == VALUE_TAG {
+            encoding::sfixed64::merge(wire_type, &mut self.0, buf, ctx)
+        } else {
+            encoding::skip_field(wire_type, tag, buf, ctx)
+        }
+    }
+
+    fn encoded_len(&self) -> usize {
+        if self.0 != 0 {
+            encoding::sfixed64::encoded_len(VALUE_TAG, &self.0)
+        } else {
+            0
+        }
+    }
+
+    fn clear(&mut self) {
+        self.0 = 0;
+    }
+}
+
+/// Writes the id in decimal, for `string` fields.
+impl From<SnowflakeId> for String {
+    fn from(id: SnowflakeId) -> String {
+        id.0.to_string()
+    }
+}
+
+/// Parses a decimal id from a `string` field, like `FromStr`.
+impl TryFrom<String> for SnowflakeId {
+    type Error = SnowflakeError;
+
+    fn try_from(text: String) -> Result<SnowflakeId, SnowflakeError> {
+        text.parse()
+    }
+}
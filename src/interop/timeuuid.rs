@@ -0,0 +1,107 @@
+//! Cassandra timeuuid (UUIDv1) conversions.
+
+use std::convert::TryFrom;
+
+use ::uuid::Uuid;
+
+use crate::{SnowflakeId, MACHINE_MASK, MACHINE_SHIFT, SEQUENCE_MASK, TIMESTAMP_SHIFT};
+
+// 100 nanosecond intervals between 1582-10-15 and the Unix epoch.
+const GREGORIAN_OFFSET_TICKS: u64 = 0x01B2_1DD2_1381_4000;
+
+// 100 nanosecond intervals in a millisecond.
+const TICKS_PER_MILLI: u64 = 10_000;
+
+// Auto-increment records in a millisecond.
+const IDX_PER_MILLI: u64 = SEQUENCE_MASK as u64 + 1;
+
+// Multicast bit of the node, set for nodes that aren't MAC addresses.
+const NODE_MULTICAST: u128 = 1 << 40;
+
+impl SnowflakeId {
+    /// Convert the id into a Cassandra timeuuid, a UUIDv1.
+    ///
+    /// The milliseconds become the 100ns timestamp, the auto-increment
+    /// record is spread over the sub-millisecond intervals and the machine
+    /// bits land in the node, with the multicast bit set as the RFC asks
+    /// for non-MAC nodes. Timeuuids compare by timestamp the same way the
+    /// ids do. Returns `None` for negative ids, whose timestamp is before
+    /// the Unix epoch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{SnowflakeId, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+    /// let id = SnowflakeId(id_generator.real_time_generate());
+    ///
+    /// let timeuuid = id.to_timeuuid().unwrap();
+    ///
+    /// assert_eq!(timeuuid.get_version_num(), 1);
+    /// assert_eq!(SnowflakeId::try_from_timeuuid(timeuuid), Some(id));
+    /// ```
+    pub fn to_timeuuid(self) -> Option<Uuid> {
+        let sub_millis = self.idx() as u64 * TICKS_PER_MILLI / IDX_PER_MILLI;
+        let ticks = u64::try_from(self.timestamp())
+            .ok()?
+            .checked_mul(TICKS_PER_MILLI)?
+            .checked_add(GREGORIAN_OFFSET_TICKS + sub_millis)? as u128;
+
+        // time_low(32) | time_mid(16) | ver(4) | time_hi(12) | var(2) | clock_seq(14) | node(48)
+        let time_low = ticks & 0xFFFF_FFFF;
+        let time_mid = (ticks >> 32) & 0xFFFF;
+        let time_hi = (ticks >> 48) & 0xFFF;
+        let node = NODE_MULTICAST | self.machine_bits() as u128;
+
+        Some(Uuid::from_u128(
+            time_low << 96 | time_mid << 80 | 0x1 << 76 | time_hi << 64 | 0b10 << 62 | node,
+        ))
+    }
+
+    /// Convert a timeuuid into an id.
+    ///
+    /// The sub-millisecond intervals are scaled down to an auto-increment
+    /// record, so order is kept for any timeuuid, and the low node bits
+    /// become the machine bits. Returns `None` if the UUID isn't version 1,
+    /// or its timestamp is before the Unix epoch or doesn't fit the id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::SnowflakeId;
+    /// use uuid::Uuid;
+    ///
+    /// let uuid = Uuid::parse_str("5b6962dd-3f90-11ee-be56-0242ac120002").unwrap();
+    ///
+    /// assert!(SnowflakeId::try_from_timeuuid(uuid).is_some());
+    /// assert!(SnowflakeId::try_from_timeuuid(Uuid::nil()).is_none());
+    /// ```
+    pub fn try_from_timeuuid(uuid: Uuid) -> Option<SnowflakeId> {
+        if uuid.get_version_num() != 1 {
+            return None;
+        }
+
+        let value = uuid.as_u128();
+        let time_low = (value >> 96) as u64;
+        let time_mid = (value >> 80) as u64 & 0xFFFF;
+        let time_hi = (value >> 64) as u64 & 0xFFF;
+        let ticks =
+            (time_hi << 48 | time_mid << 32 | time_low).checked_sub(GREGORIAN_OFFSET_TICKS)?;
+
+        let timestamp = (ticks / TICKS_PER_MILLI) as i64;
+        if timestamp > i64::MAX >> TIMESTAMP_SHIFT {
+            return None;
+        }
+
+        // Smallest record mapping to these intervals in `to_timeuuid`.
+        let sub_millis = ticks % TICKS_PER_MILLI;
+        let idx = (sub_millis * IDX_PER_MILLI).div_ceil(TICKS_PER_MILLI);
+        let idx = idx.min(SEQUENCE_MASK as u64) as i64;
+        let machine_bits = value as i64 & MACHINE_MASK;
+
+        Some(SnowflakeId(
+            timestamp << TIMESTAMP_SHIFT | machine_bits << MACHINE_SHIFT | idx,
+        ))
+    }
+}
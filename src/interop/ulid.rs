@@ -0,0 +1,139 @@
+//! ULID conversions.
+
+use ::ulid::Ulid;
+
+use crate::{
+    IdGenerator, Snowflake, SnowflakeError, SnowflakeId, SnowflakeIdGenerator, TIMESTAMP_SHIFT,
+};
+
+// Bits of the id below the timestamp, machine bits and auto-increment record.
+const LOW_BITS: u32 = TIMESTAMP_SHIFT as u32;
+
+// Bits of randomness in a ULID.
+const RANDOM_BITS: u32 = 80;
+
+// Largest millisecond timestamp the id can hold.
+const MAX_TIMESTAMP: u64 = (1 << (63 - LOW_BITS)) - 1;
+
+/// The `UlidGenerator` type generates ULIDs from snowflakes.
+///
+/// Each ULID is `SnowflakeId::to_ulid` of an id of the wrapped generator:
+/// the randomness holds the machine bits and the auto-increment record, so
+/// ULIDs are unique across machines and increase without a random source.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::{IdGenerator, SnowflakeIdGenerator, UlidGenerator};
+///
+/// let mut ulids = UlidGenerator::new(SnowflakeIdGenerator::new(1, 1));
+///
+/// let first = ulids.next_id().unwrap();
+/// let second = ulids.next_id().unwrap();
+///
+/// assert!(first < second);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct UlidGenerator {
+    generator: SnowflakeIdGenerator,
+}
+
+impl UlidGenerator {
+    pub fn new(generator: SnowflakeIdGenerator) -> UlidGenerator {
+        UlidGenerator { generator }
+    }
+
+    /// The generator of the underlying snowflakes.
+    pub fn generator(&self) -> &SnowflakeIdGenerator {
+        &self.generator
+    }
+}
+
+/// Generates with `SnowflakeIdGenerator::try_real_time_generate`.
+impl IdGenerator<Ulid> for UlidGenerator {
+    fn next_id(&mut self) -> Result<Ulid, SnowflakeError> {
+        self.generator
+            .try_real_time_generate()
+            .map(SnowflakeId::to_ulid)
+    }
+}
+
+impl SnowflakeId {
+    /// Convert the id into a ULID.
+    ///
+    /// Both share the millisecond timestamp, the machine bits and the
+    /// auto-increment record fill the top of the randomness, so the
+    /// conversion is lossless and keeps the sort order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{SnowflakeId, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+    /// let id = SnowflakeId(id_generator.real_time_generate());
+    ///
+    /// let ulid = id.to_ulid();
+    ///
+    /// assert_eq!(ulid.timestamp_ms() as i64, id.timestamp());
+    /// assert_eq!(SnowflakeId::try_from_ulid(ulid), Some(id));
+    /// ```
+    pub fn to_ulid(self) -> Ulid {
+        let low = (self.0 as u128) & ((1 << LOW_BITS) - 1);
+
+        Ulid::from_parts(self.timestamp() as u64, low << (RANDOM_BITS - LOW_BITS))
+    }
+
+    /// Convert a ULID into an id.
+    ///
+    /// Randomness that doesn't fit the id is dropped, so only ULIDs made by
+    /// `to_ulid` convert back losslessly. Returns `None` if the timestamp
+    /// doesn't fit the id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::SnowflakeId;
+    /// use ulid::Ulid;
+    ///
+    /// let ulid = Ulid::from_parts(1_700_000_000_000, 42);
+    ///
+    /// assert_eq!(SnowflakeId::try_from_ulid(ulid).unwrap().timestamp(), 1_700_000_000_000);
+    /// ```
+    pub fn try_from_ulid(ulid: Ulid) -> Option<SnowflakeId> {
+        let timestamp = ulid.timestamp_ms();
+        if timestamp > MAX_TIMESTAMP {
+            return None;
+        }
+
+        let low = (ulid.random() >> (RANDOM_BITS - LOW_BITS)) as i64;
+
+        Some(SnowflakeId((timestamp as i64) << LOW_BITS | low))
+    }
+}
+
+impl Snowflake {
+    /// Decode the timestamp of a ULID into a `Snowflake`.
+    ///
+    /// Machine bits and auto-increment record are zero, which makes the
+    /// result compare before every id generated in the same millisecond.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Snowflake;
+    /// use ulid::Ulid;
+    ///
+    /// let snowflake = Snowflake::from_ulid(Ulid::from_parts(1_700_000_000_000, 42));
+    ///
+    /// assert_eq!(snowflake.timestamp, 1_700_000_000_000);
+    /// assert_eq!(snowflake.idx, 0);
+    /// ```
+    pub fn from_ulid(ulid: Ulid) -> Snowflake {
+        Snowflake {
+            timestamp: ulid.timestamp_ms() as i64,
+            machine_bits: 0,
+            idx: 0,
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! UUID conversions.
+
+use ::uuid::Uuid;
+
+use crate::{IdGenerator, SnowflakeError, SnowflakeId, SnowflakeIdGenerator, TIMESTAMP_SHIFT};
+
+// Bits of the id below the timestamp, machine bits and auto-increment record.
+const LOW_BITS: u32 = TIMESTAMP_SHIFT as u32;
+
+// Largest millisecond timestamp the id can hold.
+const MAX_TIMESTAMP: u64 = (1 << (63 - LOW_BITS)) - 1;
+
+/// The `UuidV7Generator` type generates UUIDv7s from snowflakes.
+///
+/// Each UUID is `SnowflakeId::to_uuid_v7` of an id of the wrapped
+/// generator: the random fields hold the machine bits and the
+/// auto-increment record, so UUIDs are unique across machines and increase
+/// without a random source.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::{IdGenerator, SnowflakeIdGenerator, UuidV7Generator};
+///
+/// let mut uuids = UuidV7Generator::new(SnowflakeIdGenerator::new(1, 1));
+///
+/// let first = uuids.next_id().unwrap();
+/// let second = uuids.next_id().unwrap();
+///
+/// assert_eq!(first.get_version_num(), 7);
+/// assert!(first < second);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct UuidV7Generator {
+    generator: SnowflakeIdGenerator,
+}
+
+impl UuidV7Generator {
+    pub fn new(generator: SnowflakeIdGenerator) -> UuidV7Generator {
+        UuidV7Generator { generator }
+    }
+
+    /// The generator of the underlying snowflakes.
+    pub fn generator(&self) -> &SnowflakeIdGenerator {
+        &self.generator
+    }
+}
+
+/// Generates with `SnowflakeIdGenerator::try_real_time_generate`.
+impl IdGenerator<Uuid> for UuidV7Generator {
+    fn next_id(&mut self) -> Result<Uuid, SnowflakeError> {
+        self.generator
+            .try_real_time_generate()
+            .map(SnowflakeId::to_uuid_v7)
+    }
+}
+
+impl SnowflakeId {
+    /// Convert the id into a UUIDv7.
+    ///
+    /// The milliseconds land in `unix_ts_ms`, the machine bits and the
+    /// auto-increment record fill the top of the random fields in order,
+    /// so UUIDs sort the same way as the ids they came from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{SnowflakeId, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+    /// let id = SnowflakeId(id_generator.real_time_generate());
+    ///
+    /// let uuid = id.to_uuid_v7();
+    ///
+    /// assert_eq!(uuid.get_version_num(), 7);
+    /// assert_eq!(SnowflakeId::try_from_uuid_v7(uuid), Some(id));
+    /// ```
+    pub fn to_uuid_v7(self) -> Uuid {
+        let timestamp = self.timestamp() as u128;
+        let low = (self.0 as u128) & ((1 << LOW_BITS) - 1);
+
+        // unix_ts_ms(48) | ver(4) | rand_a(12) | var(2) | rand_b(62)
+        let rand_a = low >> (LOW_BITS - 12);
+        let rand_b = (low & ((1 << (LOW_BITS - 12)) - 1)) << (62 - (LOW_BITS - 12));
+
+        Uuid::from_u128(timestamp << 80 | 0x7 << 76 | rand_a << 64 | 0b10 << 62 | rand_b)
+    }
+
+    /// Convert a UUIDv7 into an id.
+    ///
+    /// Random bits that don't fit the id are dropped. Returns `None` if the
+    /// UUID isn't version 7, or its timestamp doesn't fit the id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::SnowflakeId;
+    /// use uuid::Uuid;
+    ///
+    /// let uuid = Uuid::parse_str("01890a5d-ac96-774b-bcce-b302099a8057").unwrap();
+    ///
+    /// assert!(SnowflakeId::try_from_uuid_v7(uuid).is_some());
+    /// assert!(SnowflakeId::try_from_uuid_v7(Uuid::nil()).is_none());
+    /// ```
+    pub fn try_from_uuid_v7(uuid: Uuid) -> Option<SnowflakeId> {
+        if uuid.get_version_num() != 7 {
+            return None;
+        }
+
+        let value = uuid.as_u128();
+        let timestamp = (value >> 80) as u64;
+        if timestamp > MAX_TIMESTAMP {
+            return None;
+        }
+
+        let rand_a = (value >> 64) & 0xFFF;
+        let rand_b = (value >> (62 - (LOW_BITS - 12))) & ((1 << (LOW_BITS - 12)) - 1);
+        let low = (rand_a << (LOW_BITS - 12) | rand_b) as i64;
+
+        Some(SnowflakeId((timestamp as i64) << LOW_BITS | low))
+    }
+}
+
+impl SnowflakeId {
+    /// Embed the id into a UUIDv8.
+    ///
+    /// The 64 bits of the id are spread around the version and variant
+    /// bits in order, followed by a copy of the machine bits so they can be
+    /// read straight from the UUID. `from_uuid` is the exact inverse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{SnowflakeId, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+    /// let id = SnowflakeId(id_generator.real_time_generate());
+    ///
+    /// let uuid = id.as_uuid();
+    ///
+    /// assert_eq!(uuid.get_version_num(), 8);
+    /// assert_eq!(SnowflakeId::from_uuid(uuid), Some(id));
+    /// ```
+    pub fn as_uuid(self) -> Uuid {
+        let id = self.0 as u64 as u128;
+        let machine_bits = self.machine_bits() as u128;
+
+        // custom_a(48) | ver(4) | custom_b(12) | var(2) | custom_c(62)
+        let custom_a = id >> 16;
+        let custom_b = (id >> 4) & 0xFFF;
+        let custom_c = (id & 0xF) << 58 | machine_bits << 48;
+
+        Uuid::from_u128(custom_a << 80 | 0x8 << 76 | custom_b << 64 | 0b10 << 62 | custom_c)
+    }
+
+    /// Extract an id embedded by `as_uuid`.
+    ///
+    /// Returns `None` for any UUID `as_uuid` can't produce.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::SnowflakeId;
+    /// use uuid::Uuid;
+    ///
+    /// assert_eq!(SnowflakeId::from_uuid(Uuid::nil()), None);
+    /// ```
+    pub fn from_uuid(uuid: Uuid) -> Option<SnowflakeId> {
+        let value = uuid.as_u128();
+
+        let custom_a = value >> 80;
+        let custom_b = (value >> 64) & 0xFFF;
+        let custom_c = (value >> 58) & 0xF;
+        let id = SnowflakeId((custom_a << 16 | custom_b << 4 | custom_c) as u64 as i64);
+
+        if id.as_uuid() == uuid {
+            Some(id)
+        } else {
+            None
+        }
+    }
+}
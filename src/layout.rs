@@ -0,0 +1,1011 @@
+//! Bit layout of the ids.
+
+use std::fmt;
+use std::ops::Range;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{DurationMillis, Snowflake, SnowflakeError, UnixMillis};
+
+/// How far in the future a timestamp may be before `validate` rejects it.
+pub const MAX_FUTURE_DRIFT_MILLIS: i64 = 24 * 60 * 60 * 1000;
+
+/// The `Layout` type describes how an id is split into fields.
+///
+/// The optional era takes the most significant bits, then the timestamp,
+/// then machine bits and auto-increment record in the `FieldOrder` of the
+/// layout.
+/// The default is 41 bits of milliseconds, 10 machine bits and 12 bits of
+/// auto-increment record, in that order.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Layout {
+    timestamp_bits: u32,
+    machine_bits: u32,
+    sequence_bits: u32,
+    era_bits: u32,
+    pid_bits: u32,
+    thread_bits: u32,
+    shard_bits: u32,
+    tag_bits: u32,
+    time_unit: TimeUnit,
+    field_order: FieldOrder,
+    max_machine_id: i64,
+}
+
+/// The `TimeUnit` type is the precision of the timestamp field.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimeUnit {
+    Seconds,
+    Milliseconds,
+    Microseconds,
+}
+
+/// The `FieldOrder` type is the order of the fields below the timestamp.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FieldOrder {
+    /// timestamp | machine bits | auto-increment record.
+    MachineSequence,
+    /// timestamp | auto-increment record | machine bits.
+    SequenceMachine,
+}
+
+/// The `ValidationError` type explains why `Layout::validate` rejected an id.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The sign bit is set, the id would decode to a time before the epoch.
+    SignBitSet,
+    /// The timestamp is further in the future than `MAX_FUTURE_DRIFT_MILLIS`.
+    TimestampInFuture(i64),
+    /// The machine bits are above the layout's maximum machine id.
+    MachineOutOfRange(i64),
+    /// The clock reads before the Unix epoch, the timestamp can't be
+    /// checked against it.
+    ClockUnavailable,
+}
+
+impl Layout {
+    /// The layout used by `SnowflakeIdGenerator` unless told otherwise.
+    pub const DEFAULT: Layout = Layout::new(41, 10, 12);
+
+    /// A layout whose ids fit in 53 bits, so they survive a round trip
+    /// through a JavaScript number.
+    ///
+    /// 41 bits of milliseconds, 4 machine bits and 8 bits of auto-increment
+    /// record: 16 machines issuing up to 256 ids per millisecond each.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{Layout, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.11".to_string())
+    ///     .with_layout(Layout::JS_SAFE);
+    ///
+    /// let id = id_generator.real_time_generate();
+    ///
+    /// assert!(id < 1 << 53);
+    /// assert_eq!(id as f64 as i64, id);
+    /// ```
+    pub const JS_SAFE: Layout = Layout::new(41, 4, 8);
+
+    /// A layout with microsecond timestamps, for ordering finer than a
+    /// millisecond.
+    ///
+    /// 51 bits of microseconds, lasting until 2041, 4 machine bits and 8
+    /// bits of auto-increment record: 16 machines issuing up to 256 ids
+    /// per microsecond each.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{Layout, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.11".to_string())
+    ///     .with_layout(Layout::MICROS);
+    ///
+    /// let id = id_generator.real_time_generate();
+    ///
+    /// assert_eq!(Layout::MICROS.decode(id).timestamp / 1000, snowflake::get_time_millis());
+    /// ```
+    pub const MICROS: Layout = Layout::new(51, 4, 8).with_time_unit(TimeUnit::Microseconds);
+
+    /// A layout with second timestamps and a large machine space, for big
+    /// fleets issuing few ids each.
+    ///
+    /// 33 bits of seconds, 16 machine bits and 14 bits of auto-increment
+    /// record: 65536 machines issuing up to 16384 ids per second each.
+    /// Once a second is used up the generator sleeps until the next one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{Layout, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string())
+    ///     .with_layout(Layout::SECONDS);
+    ///
+    /// let id = id_generator.real_time_generate();
+    /// let snowflake = Layout::SECONDS.decode(id);
+    ///
+    /// assert_eq!(snowflake.machine_bits, 2 << 8 | 123);
+    /// assert!(snowflake.timestamp <= snowflake::get_time_millis() / 1000);
+    /// ```
+    pub const SECONDS: Layout = Layout::new(33, 16, 14).with_time_unit(TimeUnit::Seconds);
+
+    /// Constructs a new `Layout` from the width of each field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Layout;
+    ///
+    /// let layout = Layout::new(41, 10, 12);
+    ///
+    /// assert_eq!(layout, Layout::default());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fields together are wider than 63 bits, or
+    /// `sequence_bits` is over 16, the width of the auto-increment record.
+    pub const fn new(timestamp_bits: u32, machine_bits: u32, sequence_bits: u32) -> Layout {
+        assert!(
+            timestamp_bits + machine_bits + sequence_bits <= 63,
+            "the fields don't fit in 63 bits"
+        );
+        assert!(sequence_bits <= 16, "sequence_bits is over 16");
+
+        Layout {
+            timestamp_bits,
+            machine_bits,
+            sequence_bits,
+            era_bits: 0,
+            pid_bits: 0,
+            thread_bits: 0,
+            shard_bits: 0,
+            tag_bits: 0,
+            time_unit: TimeUnit::Milliseconds,
+            field_order: FieldOrder::MachineSequence,
+            max_machine_id: (1 << machine_bits) - 1,
+        }
+    }
+
+    /// Count the timestamp in `time_unit` instead of milliseconds.
+    pub const fn with_time_unit(mut self, time_unit: TimeUnit) -> Layout {
+        self.time_unit = time_unit;
+        self
+    }
+
+    /// Place the fields below the timestamp in `field_order`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{FieldOrder, Layout};
+    ///
+    /// let layout = Layout::default().with_field_order(FieldOrder::SequenceMachine);
+    ///
+    /// assert_eq!(layout.compose(1, 0x2A7, 17), 1 << 22 | 17 << 10 | 0x2A7);
+    /// ```
+    pub const fn with_field_order(mut self, field_order: FieldOrder) -> Layout {
+        self.field_order = field_order;
+        self
+    }
+
+    /// Add an era field of `era_bits` right above the timestamp.
+    ///
+    /// The era counts how many times the timestamp overflowed its field, so
+    /// ids keep decoding to the right time, and keep increasing, past the
+    /// overflow. The bits are taken from the unused top bits of the id, so
+    /// the other fields stay in place and ids issued before, with those
+    /// bits zero, decode to era 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Layout;
+    ///
+    /// // 61 bits in use, 2 left for the era.
+    /// let layout = Layout::new(41, 8, 12).with_era_bits(2);
+    /// let timestamp = (1 << 41) + 1_700_000_000_000;
+    ///
+    /// let id = layout.compose(timestamp, 0x2A, 17);
+    ///
+    /// assert_eq!(layout.era(id), 1);
+    /// assert_eq!(layout.decode(id).timestamp, timestamp);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fields together are wider than 63 bits.
+    pub const fn with_era_bits(mut self, era_bits: u32) -> Layout {
+        assert!(
+            self.timestamp_bits + era_bits + self.machine_bits + self.sequence_bits <= 63,
+            "era_bits don't fit in 63 bits"
+        );
+        self.era_bits = era_bits;
+        self
+    }
+
+    /// Reserve `pid_bits` of the machine bits for the process id, filled in
+    /// by `SnowflakeIdGenerator::with_process_id`.
+    ///
+    /// Processes on one host derive the same machine bits from its IP, the
+    /// process id modulo `2^pid_bits` keeps their ids apart as long as no
+    /// two of them share it. They sit right above the thread bits, the host
+    /// keeps the remaining high machine bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Layout;
+    ///
+    /// // 64 hosts running up to 16 processes each.
+    /// let layout = Layout::default().with_pid_bits(4);
+    ///
+    /// assert_eq!(layout.pid_bits(), 4);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if pid, thread, shard and tag bits together are more than
+    /// the machine bits of the layout.
+    pub const fn with_pid_bits(mut self, pid_bits: u32) -> Layout {
+        assert!(
+            pid_bits + self.thread_bits + self.shard_bits + self.tag_bits <= self.machine_bits,
+            "pid_bits don't fit the machine bits"
+        );
+        self.pid_bits = pid_bits;
+        self
+    }
+
+    /// Reserve the low `thread_bits` of the machine bits for a thread index,
+    /// filled in by `SnowflakeIdGenerator::with_thread_index`.
+    ///
+    /// Every thread then runs its own generator, without locking, and ids
+    /// stay unique as long as each thread has a distinct index. The
+    /// trade-offs: at most `2^thread_bits` threads, each issuing up to
+    /// `max_idx() + 1` ids per time unit, and only the machine bits left
+    /// above the thread and pid bits to tell hosts apart. The default
+    /// layout with 4 thread bits is 64 hosts of 16 threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Layout;
+    ///
+    /// let layout = Layout::default().with_thread_bits(4);
+    ///
+    /// assert_eq!(layout.thread_bits(), 4);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if pid, thread, shard and tag bits together are more than
+    /// the machine bits of the layout.
+    pub const fn with_thread_bits(mut self, thread_bits: u32) -> Layout {
+        assert!(
+            self.pid_bits + thread_bits + self.shard_bits + self.tag_bits <= self.machine_bits,
+            "thread_bits don't fit the machine bits"
+        );
+        self.thread_bits = thread_bits;
+        self
+    }
+
+    /// Reserve the high `shard_bits` of the machine bits for a shard, see
+    /// `Layout::shard_key` and `SnowflakeIdGenerator::generate_for_shard`.
+    ///
+    /// Ids then carry the shard of the row they key, Instagram style, and
+    /// the machine ids of the fleet must fit in the machine bits left
+    /// below the shard.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Layout;
+    ///
+    /// // 16 shards, 64 machines.
+    /// let layout = Layout::default().with_shard_bits(4);
+    ///
+    /// assert_eq!(layout.shard_bits(), 4);
+    /// assert_eq!(layout.shard_key().shard_count(), 16);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if pid, thread, shard and tag bits together are more than
+    /// the machine bits of the layout.
+    pub const fn with_shard_bits(mut self, shard_bits: u32) -> Layout {
+        assert!(
+            self.pid_bits + self.thread_bits + shard_bits + self.tag_bits <= self.machine_bits,
+            "shard_bits don't fit the machine bits"
+        );
+        self.shard_bits = shard_bits;
+        self
+    }
+
+    /// Reserve `tag_bits` of the machine bits, right below the shard bits,
+    /// for an application-defined tag, see
+    /// `SnowflakeIdGenerator::generate_tagged` and `Layout::tag_of`.
+    ///
+    /// The tag carries lightweight metadata, such as the entity type, the
+    /// region or a priority, in the id itself. The machine ids of the fleet
+    /// must fit in the machine bits left below the tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Layout;
+    ///
+    /// // 8 tags, 128 machines.
+    /// let layout = Layout::default().with_tag_bits(3);
+    ///
+    /// assert_eq!(layout.tag_bits(), 3);
+    /// assert_eq!(layout.tag_of(5 << 19 | 0x7F << 12), 5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if pid, thread, shard and tag bits together are more than
+    /// the machine bits of the layout.
+    pub const fn with_tag_bits(mut self, tag_bits: u32) -> Layout {
+        assert!(
+            self.pid_bits + self.thread_bits + self.shard_bits + tag_bits <= self.machine_bits,
+            "tag_bits don't fit the machine bits"
+        );
+        self.tag_bits = tag_bits;
+        self
+    }
+
+    /// Only accept machine ids up to `max_machine_id` when validating,
+    /// for fleets that use a part of the machine bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Layout;
+    ///
+    /// let layout = Layout::default().with_max_machine_id(31);
+    ///
+    /// assert!(layout.validate(32 << 12).is_err());
+    /// ```
+    pub const fn with_max_machine_id(mut self, max_machine_id: i64) -> Layout {
+        self.max_machine_id = max_machine_id;
+        self
+    }
+
+    /// Assemble an id from its fields.
+    ///
+    /// Machine bits and auto-increment record are cut to the width of
+    /// their field, so they can't spill into the neighbouring fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Layout;
+    ///
+    /// let id = Layout::default().compose(1_700_000_000_000, 0x2A7, 17);
+    ///
+    /// assert_eq!(id, 1_700_000_000_000 << 22 | 0x2A7 << 12 | 17);
+    /// ```
+    #[inline(always)]
+    pub fn compose(&self, timestamp: i64, machine_bits: i64, idx: u16) -> i64 {
+        let (era, timestamp) = if self.era_bits == 0 {
+            (0, timestamp)
+        } else {
+            (
+                (timestamp >> self.timestamp_bits) & field_mask(self.era_bits),
+                timestamp & field_mask(self.timestamp_bits),
+            )
+        };
+
+        timestamp << self.timestamp_shift()
+            | era << self.era_shift()
+            | (machine_bits & field_mask(self.machine_bits)) << self.machine_shift()
+            | (i64::from(idx) & field_mask(self.sequence_bits)) << self.sequence_shift()
+    }
+
+    /// Pack the fields into an id, like `compose`, failing instead of
+    /// truncating fields that don't fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{Layout, SnowflakeError};
+    ///
+    /// let layout = Layout::default();
+    ///
+    /// assert_eq!(layout.try_compose(1, 7, 3), Ok(1 << 22 | 7 << 12 | 3));
+    /// assert_eq!(layout.try_compose(1 << 41, 7, 3), Err(SnowflakeError::EpochOverflow(1 << 41)));
+    /// assert_eq!(layout.try_compose(1, 1024, 3), Err(SnowflakeError::InvalidMachineId(1024)));
+    /// ```
+    pub fn try_compose(
+        &self,
+        timestamp: i64,
+        machine_bits: i64,
+        idx: u16,
+    ) -> Result<i64, SnowflakeError> {
+        let timestamp_limit = 1i128 << (self.timestamp_bits + self.era_bits);
+        if timestamp < 0 || i128::from(timestamp) >= timestamp_limit {
+            return Err(SnowflakeError::EpochOverflow(timestamp));
+        }
+        if machine_bits & !field_mask(self.machine_bits) != 0 {
+            return Err(SnowflakeError::InvalidMachineId(machine_bits));
+        }
+
+        Ok(self.compose(timestamp, machine_bits, idx))
+    }
+
+    /// Split an id into its fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Layout;
+    ///
+    /// let snowflake = Layout::default().decode(1_700_000_000_000 << 22 | 0x2A7 << 12 | 17);
+    ///
+    /// assert_eq!(snowflake.timestamp, 1_700_000_000_000);
+    /// assert_eq!(snowflake.machine_bits, 0x2A7);
+    /// assert_eq!(snowflake.idx, 17);
+    /// ```
+    pub fn decode(&self, id: i64) -> Snowflake {
+        let timestamp = (id >> self.timestamp_shift()) & field_mask(self.timestamp_bits)
+            | self.era(id) << self.timestamp_bits;
+        let machine_bits = (id >> self.machine_shift()) & field_mask(self.machine_bits);
+        let idx = ((id >> self.sequence_shift()) & field_mask(self.sequence_bits)) as u16;
+
+        Snowflake {
+            timestamp,
+            machine_bits,
+            idx,
+        }
+    }
+
+    /// Split a slice of ids into their fields.
+    ///
+    /// Shifts and masks are computed once, and ids are decoded in chunks of
+    /// `DECODE_CHUNK` so the bit operations vectorize.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Layout;
+    ///
+    /// let snowflakes = Layout::default().decode_slice(&[1 << 22, 2 << 22 | 1]);
+    ///
+    /// assert_eq!(snowflakes[1].timestamp, 2);
+    /// assert_eq!(snowflakes[1].idx, 1);
+    /// ```
+    pub fn decode_slice(&self, ids: &[i64]) -> Vec<Snowflake> {
+        let timestamp_shift = self.timestamp_shift();
+        let timestamp_bits = self.timestamp_bits;
+        let timestamp_mask = field_mask(self.timestamp_bits);
+        let era_shift = self.era_shift();
+        let era_mask = field_mask(self.era_bits);
+        let machine_shift = self.machine_shift();
+        let machine_mask = field_mask(self.machine_bits);
+        let sequence_shift = self.sequence_shift();
+        let sequence_mask = field_mask(self.sequence_bits);
+
+        let mut snowflakes = Vec::with_capacity(ids.len());
+        let mut chunks = ids.chunks_exact(DECODE_CHUNK);
+
+        for chunk in &mut chunks {
+            let mut timestamps = [0i64; DECODE_CHUNK];
+            let mut machines = [0i64; DECODE_CHUNK];
+            let mut idxs = [0u16; DECODE_CHUNK];
+
+            for i in 0..DECODE_CHUNK {
+                timestamps[i] = (chunk[i] >> timestamp_shift) & timestamp_mask
+                    | ((chunk[i] >> era_shift) & era_mask) << timestamp_bits;
+                machines[i] = (chunk[i] >> machine_shift) & machine_mask;
+                idxs[i] = ((chunk[i] >> sequence_shift) & sequence_mask) as u16;
+            }
+
+            snowflakes.extend((0..DECODE_CHUNK).map(|i| Snowflake {
+                timestamp: timestamps[i],
+                machine_bits: machines[i],
+                idx: idxs[i],
+            }));
+        }
+
+        snowflakes.extend(chunks.remainder().iter().map(|&id| self.decode(id)));
+        snowflakes
+    }
+
+    /// Lazily split ids into their fields as they are iterated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Layout;
+    ///
+    /// let ids = vec![1 << 22, 2 << 22 | 1, 2 << 22 | 2];
+    /// let snowflakes = Layout::default().decode_iter(ids);
+    ///
+    /// assert_eq!(snowflakes.filter(|snowflake| snowflake.timestamp == 2).count(), 2);
+    /// ```
+    pub fn decode_iter<I: IntoIterator<Item = i64>>(&self, ids: I) -> DecodeIter<I::IntoIter> {
+        DecodeIter {
+            layout: *self,
+            ids: ids.into_iter(),
+        }
+    }
+
+    /// The ids of the time window `start..end`, as a range of ids to scan
+    /// in a store keyed by id.
+    ///
+    /// The range starts at the smallest id of the time unit of `start` and
+    /// ends before the smallest id of the time unit of `end`. Times outside
+    /// of the timestamp field are clamped to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// use snowflake::Layout;
+    ///
+    /// let start = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+    /// let ids = Layout::default().ids_in_window(start, start + Duration::from_secs(1));
+    ///
+    /// assert_eq!(ids, 1_700_000_000_000 << 22..1_700_000_001_000 << 22);
+    /// assert!(ids.contains(&(1_700_000_000_999 << 22 | 0x3F_FFFF)));
+    /// ```
+    pub fn ids_in_window<T: UnixMillis>(&self, start: T, end: T) -> Range<i64> {
+        self.id_at(start)..self.id_at(end)
+    }
+
+    /// The smallest id with the timestamp `timestamp`, machine bits and
+    /// auto-increment record zeroed, clamped to the timestamp field.
+    ///
+    /// A synthetic id for paging by date the way Discord's search does:
+    /// ids created at `timestamp` or later are `>= id_at(timestamp)`, ids
+    /// created before it are `< id_at(timestamp)`, so a date filter is a
+    /// range over the primary key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// use snowflake::Layout;
+    ///
+    /// let layout = Layout::default();
+    /// let date = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+    ///
+    /// // SELECT * FROM messages WHERE id >= $after AND id < $before
+    /// let after = layout.id_at(date);
+    /// let before = layout.id_at(date + Duration::from_secs(86_400));
+    ///
+    /// assert_eq!(after, 1_700_000_000_000 << 22);
+    /// assert!(after <= layout.compose(1_700_000_000_000, 0x2A7, 17));
+    /// assert!(before > layout.compose(1_700_086_399_999, 0x3FF, 0xFFF));
+    /// ```
+    pub fn id_at<T: UnixMillis>(&self, timestamp: T) -> i64 {
+        self.first_id_of(self.time_unit.from_millis(timestamp.unix_millis()))
+    }
+
+    /// Split the time window `start..end` into buckets of `bucket`, and
+    /// iterate over the range of ids of each, see `ids_in_window`.
+    ///
+    /// The last bucket is cut at `end`. Buckets of a millisecond walk the
+    /// window a time unit at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// use snowflake::Layout;
+    ///
+    /// let start = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+    /// let end = start + Duration::from_millis(2500);
+    ///
+    /// let buckets: Vec<_> = Layout::default()
+    ///     .buckets_in_window(start, end, Duration::from_secs(1))
+    ///     .collect();
+    ///
+    /// assert_eq!(buckets.len(), 3);
+    /// assert_eq!(buckets[2], 1_700_000_002_000 << 22..1_700_000_002_500 << 22);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bucket` is shorter than the time unit of the layout.
+    pub fn buckets_in_window<T: UnixMillis, D: DurationMillis>(
+        &self,
+        start: T,
+        end: T,
+        bucket: D,
+    ) -> WindowBuckets {
+        let step = self.time_unit.from_millis(bucket.duration_millis());
+        assert!(step > 0, "bucket is shorter than the time unit");
+
+        WindowBuckets {
+            layout: *self,
+            next: self.time_unit.from_millis(start.unix_millis()),
+            end: self.time_unit.from_millis(end.unix_millis()),
+            step,
+        }
+    }
+
+    // The smallest id of `timestamp`, clamped to the timestamp field.
+    pub(crate) fn first_id_of(&self, timestamp: i64) -> i64 {
+        let timestamp_limit = 1i128 << (self.timestamp_bits + self.era_bits);
+        if timestamp <= 0 {
+            0
+        } else if i128::from(timestamp) >= timestamp_limit {
+            i64::MAX
+        } else {
+            self.compose(timestamp, 0, 0)
+        }
+    }
+
+    /// Time since `id` was created, zero for ids from the future.
+    ///
+    /// Reads the timestamp in the time unit of the layout, eras included.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the clock reads before the Unix epoch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use snowflake::{Layout, TimeUnit};
+    ///
+    /// let layout = Layout::default().with_time_unit(TimeUnit::Seconds);
+    /// let five_minutes_ago = TimeUnit::Seconds.now() - 300;
+    ///
+    /// assert!(layout.age(layout.compose(five_minutes_ago, 0, 0)) >= Duration::from_secs(300));
+    /// ```
+    pub fn age(&self, id: i64) -> Duration {
+        let created = self.decode(id).timestamp;
+        let age = (self.time_unit.now() - created).max(0);
+
+        self.time_unit.duration_of(age as u64)
+    }
+
+    /// The era of an id, how many times its timestamp overflowed.
+    ///
+    /// Always 0 for layouts without an era field.
+    pub fn era(&self, id: i64) -> i64 {
+        (id >> self.era_shift()) & field_mask(self.era_bits)
+    }
+
+    /// Decode an id from an untrusted source, checking it is plausible.
+    ///
+    /// Rejects ids with the sign bit set, a timestamp absurdly in the
+    /// future, or machine bits out of the layout's range. Fails with
+    /// `ValidationError::ClockUnavailable` instead of panicking when the
+    /// clock reads before the Unix epoch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{Layout, SnowflakeIdGenerator, ValidationError};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+    /// let id = id_generator.real_time_generate();
+    ///
+    /// assert!(Layout::default().validate(id).is_ok());
+    /// assert_eq!(Layout::default().validate(-id), Err(ValidationError::SignBitSet));
+    /// ```
+    pub fn validate(&self, id: i64) -> Result<Snowflake, ValidationError> {
+        if id < 0 {
+            return Err(ValidationError::SignBitSet);
+        }
+
+        let snowflake = self.decode(id);
+
+        let max_drift = self.time_unit.from_millis(MAX_FUTURE_DRIFT_MILLIS);
+        let now = self
+            .time_unit
+            .try_now()
+            .ok_or(ValidationError::ClockUnavailable)?;
+        if snowflake.timestamp > now + max_drift {
+            return Err(ValidationError::TimestampInFuture(snowflake.timestamp));
+        }
+
+        if snowflake.machine_bits > self.max_machine_id {
+            return Err(ValidationError::MachineOutOfRange(snowflake.machine_bits));
+        }
+
+        Ok(snowflake)
+    }
+
+    /// Precision of the timestamp field.
+    pub const fn time_unit(&self) -> TimeUnit {
+        self.time_unit
+    }
+
+    /// Width of the timestamp field.
+    pub const fn timestamp_bits(&self) -> u32 {
+        self.timestamp_bits
+    }
+
+    /// Width of the era field.
+    pub const fn era_bits(&self) -> u32 {
+        self.era_bits
+    }
+
+    /// Width of the machine bits field.
+    pub const fn machine_bits(&self) -> u32 {
+        self.machine_bits
+    }
+
+    /// Width of the process id, within the machine bits.
+    pub const fn pid_bits(&self) -> u32 {
+        self.pid_bits
+    }
+
+    /// Width of the thread index, the lowest machine bits.
+    pub const fn thread_bits(&self) -> u32 {
+        self.thread_bits
+    }
+
+    /// Width of the shard, the highest machine bits.
+    pub const fn shard_bits(&self) -> u32 {
+        self.shard_bits
+    }
+
+    /// Width of the tag, the machine bits right below the shard.
+    pub const fn tag_bits(&self) -> u32 {
+        self.tag_bits
+    }
+
+    // Width of the machine bits left to the machine id, below the shard
+    // and tag bits.
+    pub(crate) const fn host_bits(&self) -> u32 {
+        self.machine_bits - self.shard_bits - self.tag_bits
+    }
+
+    // Whether every machine id of `machine_ids` fits the machine bits left
+    // below the shard and tag bits.
+    pub(crate) fn fits_machine_ids(&self, machine_ids: &Range<i64>) -> bool {
+        machine_ids.start >= 0 && machine_ids.end <= 1 << self.host_bits()
+    }
+
+    /// Width of the auto-increment record field.
+    pub const fn sequence_bits(&self) -> u32 {
+        self.sequence_bits
+    }
+
+    /// Largest auto-increment record the layout can hold.
+    pub const fn max_idx(&self) -> u16 {
+        field_mask(self.sequence_bits) as u16
+    }
+
+    /// Position of the lowest timestamp bit.
+    pub const fn timestamp_shift(&self) -> u32 {
+        self.machine_bits + self.sequence_bits
+    }
+
+    /// Position of the lowest era bit.
+    pub const fn era_shift(&self) -> u32 {
+        self.timestamp_shift() + self.timestamp_bits
+    }
+
+    /// Position of the lowest machine bit.
+    pub const fn machine_shift(&self) -> u32 {
+        match self.field_order {
+            FieldOrder::MachineSequence => self.sequence_bits,
+            FieldOrder::SequenceMachine => 0,
+        }
+    }
+
+    /// Position of the lowest auto-increment record bit.
+    pub const fn sequence_shift(&self) -> u32 {
+        match self.field_order {
+            FieldOrder::MachineSequence => 0,
+            FieldOrder::SequenceMachine => self.machine_bits,
+        }
+    }
+
+    /// Mask selecting the timestamp bits of an id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Layout;
+    ///
+    /// assert_eq!(Layout::default().timestamp_mask(), 0x7FFF_FFFF_FFC0_0000);
+    /// ```
+    pub const fn timestamp_mask(&self) -> i64 {
+        field_mask(self.timestamp_bits) << self.timestamp_shift()
+    }
+
+    /// Mask selecting the era bits of an id.
+    pub const fn era_mask(&self) -> i64 {
+        field_mask(self.era_bits) << self.era_shift()
+    }
+
+    /// Mask selecting the machine bits of an id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Layout;
+    ///
+    /// assert_eq!(Layout::default().machine_mask(), 0x3F_F000);
+    /// ```
+    pub const fn machine_mask(&self) -> i64 {
+        field_mask(self.machine_bits) << self.machine_shift()
+    }
+
+    /// Mask selecting the auto-increment record bits of an id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Layout;
+    ///
+    /// assert_eq!(Layout::default().sequence_mask(), 0xFFF);
+    /// ```
+    pub const fn sequence_mask(&self) -> i64 {
+        field_mask(self.sequence_bits) << self.sequence_shift()
+    }
+}
+
+impl TimeUnit {
+    /// The time of the clock since the Unix epoch, in this unit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the clock reads before the Unix epoch.
+    #[inline(always)]
+    pub fn now(self) -> i64 {
+        self.try_now().expect("Time went mackward")
+    }
+
+    /// The time of the clock since the Unix epoch, in this unit, `None` if
+    /// the clock can't be read, or reads before the Unix epoch.
+    #[inline(always)]
+    pub fn try_now(self) -> Option<i64> {
+        let elapsed = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+
+        Some(self.units_of(elapsed))
+    }
+
+    /// Convert `elapsed` into this unit, rounding down.
+    pub(crate) fn units_of(self, elapsed: Duration) -> i64 {
+        match self {
+            TimeUnit::Seconds => elapsed.as_secs() as i64,
+            TimeUnit::Milliseconds => elapsed.as_millis() as i64,
+            TimeUnit::Microseconds => elapsed.as_micros() as i64,
+        }
+    }
+
+    /// Convert `value`, in this unit, into a `Duration`.
+    pub(crate) fn duration_of(self, value: u64) -> Duration {
+        match self {
+            TimeUnit::Seconds => Duration::from_secs(value),
+            TimeUnit::Milliseconds => Duration::from_millis(value),
+            TimeUnit::Microseconds => Duration::from_micros(value),
+        }
+    }
+
+    /// Convert `millis` into this unit.
+    pub const fn from_millis(self, millis: i64) -> i64 {
+        match self {
+            TimeUnit::Seconds => millis / 1000,
+            TimeUnit::Milliseconds => millis,
+            TimeUnit::Microseconds => millis * 1000,
+        }
+    }
+
+    /// Convert `value`, in this unit, into milliseconds.
+    pub const fn to_millis(self, value: i64) -> i64 {
+        match self {
+            TimeUnit::Seconds => value * 1000,
+            TimeUnit::Milliseconds => value,
+            TimeUnit::Microseconds => value / 1000,
+        }
+    }
+
+    /// Milliseconds left until the clock reaches the next unit, worth
+    /// sleeping through instead of spinning. Zero for units finer than a
+    /// second.
+    pub(crate) fn millis_to_sleep(self) -> u64 {
+        match self {
+            TimeUnit::Seconds => match TimeUnit::Milliseconds.try_now() {
+                Some(millis) => (1000 - millis % 1000) as u64,
+                None => 0,
+            },
+            TimeUnit::Milliseconds | TimeUnit::Microseconds => 0,
+        }
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Layout {
+        Layout::DEFAULT
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::SignBitSet => f.write_str("sign bit is set"),
+            ValidationError::TimestampInFuture(timestamp) => {
+                write!(f, "timestamp {} is too far in the future", timestamp)
+            }
+            ValidationError::MachineOutOfRange(machine_bits) => {
+                write!(f, "machine bits {} are out of range", machine_bits)
+            }
+            ValidationError::ClockUnavailable => f.write_str("clock is before the unix epoch"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// An iterator decoding ids with a `Layout`, see `Layout::decode_iter`.
+#[derive(Clone, Debug)]
+pub struct DecodeIter<I> {
+    layout: Layout,
+    ids: I,
+}
+
+impl<I: Iterator<Item = i64>> Iterator for DecodeIter<I> {
+    type Item = Snowflake;
+
+    fn next(&mut self) -> Option<Snowflake> {
+        self.ids.next().map(|id| self.layout.decode(id))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ids.size_hint()
+    }
+}
+
+impl<I: DoubleEndedIterator<Item = i64>> DoubleEndedIterator for DecodeIter<I> {
+    fn next_back(&mut self) -> Option<Snowflake> {
+        self.ids.next_back().map(|id| self.layout.decode(id))
+    }
+}
+
+impl<I: ExactSizeIterator<Item = i64>> ExactSizeIterator for DecodeIter<I> {}
+
+/// An iterator over the id ranges of the buckets of a time window, see
+/// `Layout::buckets_in_window`.
+#[derive(Clone, Debug)]
+pub struct WindowBuckets {
+    layout: Layout,
+    next: i64,
+    end: i64,
+    step: i64,
+}
+
+impl Iterator for WindowBuckets {
+    type Item = Range<i64>;
+
+    fn next(&mut self) -> Option<Range<i64>> {
+        if self.next >= self.end {
+            return None;
+        }
+
+        let start = self.next;
+        self.next = start.saturating_add(self.step).min(self.end);
+        Some(self.layout.first_id_of(start)..self.layout.first_id_of(self.next))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let span = self.end.saturating_sub(self.next).max(0);
+        let buckets = (span / self.step + i64::from(span % self.step != 0)) as usize;
+        (buckets, Some(buckets))
+    }
+}
+
+impl ExactSizeIterator for WindowBuckets {}
+
+// Ids decoded per chunk by `decode_slice`.
+const DECODE_CHUNK: usize = 8;
+
+#[inline(always)]
+const fn field_mask(bits: u32) -> i64 {
+    (1 << bits) - 1
+}
@@ -0,0 +1,155 @@
+//! Generators tied to the lease of their machine id.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::{IdGenerator, Layout, SnowflakeError, SnowflakeId, SnowflakeIdGenerator};
+
+/// The `LeasedGenerator` type generates with a leased machine id, for as
+/// long as the lease is valid.
+///
+/// Once the lease is released, lost, or left unrenewed past its TTL, the
+/// machine id may be leased to another process, and every generation fails
+/// with `SnowflakeError::LeaseLost` instead of issuing ids that process
+/// could issue too. Built by the `generator` method of the leases.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::{SnowflakeError, WorkerIdPool};
+///
+/// let pool = WorkerIdPool::new(0..32);
+/// let lease = pool.acquire().unwrap();
+///
+/// let mut id_generator = lease.generator();
+/// assert!(id_generator.generate().is_ok());
+///
+/// drop(lease);
+/// assert_eq!(id_generator.generate(), Err(SnowflakeError::LeaseLost(0)));
+/// ```
+pub struct LeasedGenerator {
+    generator: SnowflakeIdGenerator,
+    state: Arc<LeaseState>,
+}
+
+// The validity of a lease, shared by the lease and its generators.
+#[derive(Debug)]
+pub(crate) struct LeaseState {
+    machine_id: i64,
+    ttl: Option<Duration>,
+    started: Instant,
+    // Nanoseconds from `started` to the expiry of the lease.
+    deadline_nanos: AtomicU64,
+    lost: AtomicBool,
+}
+
+impl LeasedGenerator {
+    pub(crate) fn new(state: &Arc<LeaseState>, layout: Layout) -> LeasedGenerator {
+        LeasedGenerator {
+            generator: SnowflakeIdGenerator::from_machine_bits(state.machine_id)
+                .with_layout(layout),
+            state: Arc::clone(state),
+        }
+    }
+
+    /// The leased machine id.
+    pub fn machine_id(&self) -> i64 {
+        self.state.machine_id
+    }
+
+    /// Whether the lease is still valid, neither released, lost nor
+    /// expired.
+    pub fn is_valid(&self) -> bool {
+        self.state.check().is_ok()
+    }
+
+    /// The underlying generator, to read its state.
+    pub fn generator(&self) -> &SnowflakeIdGenerator {
+        &self.generator
+    }
+
+    /// Generate an id, see `SnowflakeIdGenerator::try_real_time_generate`.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `SnowflakeError::LeaseLost` once the lease isn't valid,
+    /// and with the errors of `try_real_time_generate`.
+    pub fn generate(&mut self) -> Result<SnowflakeId, SnowflakeError> {
+        self.state.check()?;
+        self.generator.try_real_time_generate()
+    }
+}
+
+/// Generates with `LeasedGenerator::generate`.
+impl IdGenerator for LeasedGenerator {
+    fn next_id(&mut self) -> Result<SnowflakeId, SnowflakeError> {
+        self.generate()
+    }
+}
+
+impl fmt::Debug for LeasedGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LeasedGenerator")
+            .field("machine_id", &self.state.machine_id)
+            .field("valid", &self.is_valid())
+            .finish()
+    }
+}
+
+impl LeaseState {
+    // A lease of `machine_id` valid for `ttl` from now, or until lost
+    // without a TTL.
+    pub(crate) fn new(machine_id: i64, ttl: Option<Duration>) -> Arc<LeaseState> {
+        LeaseState::requested_at(machine_id, ttl, Instant::now())
+    }
+
+    // A lease of `machine_id` valid for `ttl` from `requested`, the moment
+    // the claim was sent.
+    pub(crate) fn requested_at(
+        machine_id: i64,
+        ttl: Option<Duration>,
+        requested: Instant,
+    ) -> Arc<LeaseState> {
+        let state = LeaseState {
+            machine_id,
+            ttl,
+            started: requested,
+            deadline_nanos: AtomicU64::new(if ttl.is_some() { 0 } else { u64::MAX }),
+            lost: AtomicBool::new(false),
+        };
+        state.renewed_at(state.started);
+        Arc::new(state)
+    }
+
+    // Extend the lease to `ttl` after `renewed`, the moment the renewal
+    // was sent.
+    pub(crate) fn renewed_at(&self, renewed: Instant) {
+        if let Some(ttl) = self.ttl {
+            let deadline = (renewed + ttl).saturating_duration_since(self.started);
+            let deadline = deadline.as_nanos().min(u128::from(u64::MAX)) as u64;
+            self.deadline_nanos.fetch_max(deadline, Ordering::SeqCst);
+        }
+    }
+
+    pub(crate) fn machine_id(&self) -> i64 {
+        self.machine_id
+    }
+
+    pub(crate) fn lose(&self) {
+        self.lost.store(true, Ordering::SeqCst);
+    }
+
+    pub(crate) fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::SeqCst)
+    }
+
+    fn check(&self) -> Result<(), SnowflakeError> {
+        let elapsed = self.started.elapsed().as_nanos();
+        if self.is_lost() || elapsed >= u128::from(self.deadline_nanos.load(Ordering::SeqCst)) {
+            return Err(SnowflakeError::LeaseLost(self.machine_id));
+        }
+        Ok(())
+    }
+}
@@ -1,24 +1,245 @@
 //! Rust version of the `Twitter snowflake algorithm` .
 //!
+//! # Panics
+//!
+//! The fallible APIs never panic, so the crate can run in hosts built with
+//! `panic = "abort"`: `SnowflakeIdGenerator::with_machine_id`,
+//! `SnowflakeIdGenerator::try_new_from_ip`,
+//! `SnowflakeIdGenerator::try_real_time_generate`, `try_get_time_millis`,
+//! `Layout::try_compose` and `Layout::validate`, along with the decoding
+//! APIs. Generators built `with_clock_fallback` don't panic when the clock
+//! fails either, in `real_time_generate`, `generate` and `try_generate`.
+//!
+//! The other constructors and the clock readers panic on invalid input or
+//! a clock before the Unix epoch, see their `# Panics` sections.
 
 use std::hint::spin_loop;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::net::Ipv4Addr;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+#[cfg(feature = "chrono")]
 use chrono::{DateTime, Utc};
 
+#[cfg(feature = "actix")]
+pub mod actix;
+#[cfg(feature = "serde")]
+pub mod as_string;
+#[cfg(any(feature = "async-std", feature = "futures-timer", feature = "tokio"))]
+mod async_generator;
+pub mod audit;
+mod backfill;
+#[cfg(feature = "borsh")]
+mod borsh;
+mod boundary_flake;
+mod bucket;
+mod buffered;
+#[cfg(feature = "ntp")]
+mod clock_check;
+mod clock_event;
+mod coordination;
+mod convert;
+mod deterministic;
+mod encoding;
+mod entity;
+mod error;
+mod explain;
+mod global;
+mod gossip;
+mod health;
+mod high_water;
+#[cfg(any(
+    feature = "aws",
+    feature = "azure",
+    feature = "consul",
+    feature = "gcp"
+))]
+mod http;
+mod hybrid;
+mod id;
+mod id_generator;
+mod interop;
+mod layout;
+mod leased;
+mod mock;
+pub mod obfuscate;
+#[cfg(feature = "rayon")]
+mod pool;
+#[cfg(feature = "privacy")]
+mod private;
+mod resolver;
+mod segment;
+mod shard;
+mod shared;
+mod short;
+mod snowflake128;
+mod stats;
+mod tag;
+mod thread_local;
+mod throttle;
+mod time_source;
+mod timestamp;
+#[cfg(feature = "timezone")]
+mod timezone;
+#[cfg(feature = "tower")]
+pub mod tower;
+mod trace_id;
+mod typed_id;
+#[cfg(feature = "tracing")]
+pub mod tracing;
+mod uniqueness;
+mod wait;
+mod worker_id;
+
+#[cfg(feature = "serde")]
+pub use as_string::SnowflakeIdStr;
+#[cfg(any(feature = "async-std", feature = "futures-timer", feature = "tokio"))]
+pub use async_generator::AsyncGenerator;
+pub use backfill::BackfillGenerator;
+pub use boundary_flake::{BoundaryFlake, BoundaryFlakeGenerator};
+pub use bucket::Granularity;
+pub use buffered::BufferedGenerator;
+#[cfg(feature = "ntp")]
+pub use clock_check::{verify_clock, ChronyClock, ClockReference, NtpClock};
+pub use clock_event::{ClockEvent, CLOCK_JUMP_THRESHOLD_MILLIS};
+#[cfg(feature = "consul")]
+pub use coordination::{ConsulAllocator, ConsulLease};
+pub use coordination::{
+    FileLockAllocator, FileLockLease, LeaseKeeper, LeaseLost, RenewableLease,
+};
+#[cfg(feature = "postgres")]
+pub use coordination::{PostgresAllocator, PostgresLease};
+#[cfg(feature = "sqlite")]
+pub use coordination::{RegistryLease, WorkerClaim, WorkerRegistry};
+pub use deterministic::{DeterministicGenerator, DETERMINISTIC_EPOCH_MILLIS};
+pub use entity::{Entity, EntityKind, EntityRegistry};
+pub use error::SnowflakeError;
+pub use global::{init, next_id};
+pub use gossip::{DuplicateWorker, GossipDetector};
+pub use health::{Health, LeaseStatus, MAX_HEALTHY_CLOCK_SKEW_MILLIS};
+pub use high_water::{
+    wait_until_clock_reaches, wait_until_clock_reaches_with, HighWaterMarkFile,
+    CLOCK_CATCH_UP_INTERVAL,
+};
+pub use hybrid::{HybridGenerator, SegmentStore};
+pub use id::{PartitionStrategy, SnowflakeId};
+#[cfg(feature = "rkyv")]
+pub use id::{ArchivedSnowflakeId, SnowflakeIdResolver};
+pub use id_generator::{IdGenerator, LazyGenerator};
+pub use interop::{Ksuid, ParseKsuidError, KSUID_EPOCH_SECONDS};
+#[cfg(feature = "prost")]
+pub use interop::SNOWFLAKE_PROTO;
+#[cfg(feature = "ulid")]
+pub use interop::UlidGenerator;
+#[cfg(feature = "uuid")]
+pub use interop::UuidV7Generator;
+pub use layout::{
+    DecodeIter, FieldOrder, Layout, TimeUnit, ValidationError, WindowBuckets,
+    MAX_FUTURE_DRIFT_MILLIS,
+};
+pub use leased::LeasedGenerator;
+pub use mock::MockIdGenerator;
+#[cfg(feature = "rayon")]
+pub use pool::GeneratorPool;
+#[cfg(feature = "privacy")]
+pub use private::PrivateIdGenerator;
+pub use resolver::{ContainerIdResolver, WorkerIdResolver};
+#[cfg(feature = "aws")]
+pub use resolver::AwsMetadataResolver;
+#[cfg(feature = "azure")]
+pub use resolver::AzureMetadataResolver;
+#[cfg(feature = "gcp")]
+pub use resolver::GcpMetadataResolver;
+#[cfg(any(feature = "aws", feature = "gcp"))]
+pub use resolver::InstanceKey;
+pub use segment::SegmentGenerator;
+pub use shard::ShardKey;
+pub use shared::SharedGenerator;
+pub use short::ShortIdGenerator;
+pub use snowflake128::{ParseSnowflake128Error, Snowflake128, Snowflake128Generator};
+pub use stats::GeneratorStats;
+pub use thread_local::{
+    set_thread_local_layout, set_thread_local_machine_bits, thread_local_generate,
+    THREAD_INDEX_BITS,
+};
+pub use throttle::ThrottledGenerator;
+pub use time_source::TimeSource;
+pub use timestamp::{DurationMillis, UnixMillis};
+pub use trace_id::TraceIdGenerator;
+pub use typed_id::TypedId;
+pub use uniqueness::UniquenessChecker;
+#[cfg(feature = "async-std")]
+pub use wait::AsyncStdSleep;
+#[cfg(any(feature = "async-std", feature = "futures-timer", feature = "tokio"))]
+pub use wait::AsyncWaitStrategy;
+#[cfg(feature = "tokio")]
+pub use wait::AsyncSleep;
+#[cfg(feature = "futures-timer")]
+pub use wait::TimerSleep;
+pub use wait::{Sleep, Spin, WaitStrategy, Yield};
+pub use worker_id::{WorkerIdLease, WorkerIdPool};
+
+// Bit positions of the id fields, the auto-increment record takes the low bits.
+pub(crate) const TIMESTAMP_SHIFT: i64 = Layout::DEFAULT.timestamp_shift() as i64;
+pub(crate) const MACHINE_SHIFT: i64 = Layout::DEFAULT.machine_shift() as i64;
+pub(crate) const MACHINE_MASK: i64 = Layout::DEFAULT.machine_mask() >> MACHINE_SHIFT;
+pub(crate) const SEQUENCE_MASK: i64 = Layout::DEFAULT.sequence_mask();
+
 /// The `SnowflakeIdGenerator` type is snowflake algorithm wrapper.
 #[derive(Copy, Clone, Debug)]
 pub struct SnowflakeIdGenerator {
-    /// last_time_millis, last time generate id is used times millis.
+    /// last_time_millis, last time generate id is used times millis,
+    /// or in the time unit of the layout.
     pub last_time_millis: i64,
 
     pub machine_bits: i64,
 
     /// auto-increment record.
     pub idx: u16,
+
+    /// first auto-increment record of the current millisecond.
+    pub first_idx: u16,
+
+    /// State of the random generator picking `first_idx`,
+    /// `None` starts every millisecond at 0.
+    pub idx_rng: Option<u64>,
+
+    /// How the fields are packed into the id.
+    pub layout: Layout,
+
+    /// Keep generating from the last known time, like `lazy_generate`,
+    /// when the clock can't be read instead of panicking.
+    pub clock_fallback: bool,
+
+    stats: GeneratorStats,
+
+    clock_events: Option<fn(ClockEvent)>,
+
+    /// Last clock reading, in milliseconds, and when it was taken, to tell
+    /// forward jumps of the clock apart from time passing.
+    clock_reference: Option<(i64, Instant)>,
+
+    /// How to wait for the next time unit, `None` spins, or sleeps for
+    /// layouts counting seconds.
+    wait_strategy: Option<&'static dyn WaitStrategy>,
+
+    /// Where the clock is read from.
+    time_source: TimeSource,
+
+    /// Time unit and auto-increment record of the last id minted before
+    /// the high-water mark, by `generate_at_allow_past`.
+    past: Option<(i64, u16)>,
 }
 
-#[derive(Copy, Clone, Debug)]
+/// The `Snowflake` type is a decoded id.
+///
+/// Ordered by timestamp, then machine bits, then auto-increment record.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug, PartialEq, Eq, Hash), compare(PartialEq))
+)]
 pub struct Snowflake {
     pub timestamp: i64,
     pub machine_bits: i64,
@@ -26,9 +247,72 @@ pub struct Snowflake {
 }
 
 impl SnowflakeIdGenerator {
+    /// Constructs a new `SnowflakeIdGenerator` from a datacenter and a worker id.
+    ///
+    /// The classic split of the 10 machine bits, 5 bits of datacenter
+    /// followed by 5 bits of worker.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `datacenter_id` or `worker_id` is not smaller than 32(2^5).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::SnowflakeIdGenerator;
+    ///
+    /// let id_generator = SnowflakeIdGenerator::new(1, 7);
+    ///
+    /// assert_eq!(id_generator.machine_bits, 1 << 5 | 7);
+    /// ```
+    pub fn new(datacenter_id: u8, worker_id: u8) -> SnowflakeIdGenerator {
+        assert!(
+            datacenter_id < 32,
+            "datacenter_id {} is not smaller than 32",
+            datacenter_id
+        );
+        assert!(worker_id < 32, "worker_id {} is not smaller than 32", worker_id);
+
+        let machine_bits = i64::from(datacenter_id) << 5 | i64::from(worker_id);
+        SnowflakeIdGenerator::from_machine_bits(machine_bits)
+    }
+
+    /// Constructs a new `SnowflakeIdGenerator` from a numeric machine id.
+    ///
+    /// Fails with `SnowflakeError::InvalidMachineId` if the id doesn't fit
+    /// the machine bits of the default layout, instead of overflowing into
+    /// the neighbouring fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{SnowflakeError, SnowflakeIdGenerator};
+    ///
+    /// let id_generator = SnowflakeIdGenerator::with_machine_id(1023).unwrap();
+    ///
+    /// assert_eq!(id_generator.machine_bits, 1023);
+    /// assert_eq!(
+    ///     SnowflakeIdGenerator::with_machine_id(1024).unwrap_err(),
+    ///     SnowflakeError::InvalidMachineId(1024)
+    /// );
+    /// ```
+    pub fn with_machine_id(id: u32) -> Result<SnowflakeIdGenerator, SnowflakeError> {
+        let machine_id = i64::from(id);
+        if machine_id > MACHINE_MASK {
+            return Err(SnowflakeError::InvalidMachineId(machine_id));
+        }
+
+        Ok(SnowflakeIdGenerator::from_machine_bits(machine_id))
+    }
+
     /// Constructs a new `SnowflakeIdGenerator`.
     /// Please make sure that machine_id and node_id is small than 32(2^5);
     ///
+    /// # Panics
+    ///
+    /// Panics if `ip` isn't a dotted IPv4 address, or its last two octets
+    /// don't fit the machine bits, see `try_new_from_ip`.
+    ///
     /// # Examples
     ///
     /// ```
@@ -37,16 +321,348 @@ impl SnowflakeIdGenerator {
     /// let id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
     /// ```
     pub fn new_from_ip(ip: String) -> SnowflakeIdGenerator {
-        let last_time_millis = get_time_millis();
+        SnowflakeIdGenerator::try_new_from_ip(&ip).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Constructs a new `SnowflakeIdGenerator` from the last two octets of
+    /// an IPv4 address, like `new_from_ip`.
+    ///
+    /// Fails with `SnowflakeError::InvalidIp` if `ip` isn't a dotted IPv4
+    /// address, and with `SnowflakeError::InvalidMachineId` if its last two
+    /// octets don't fit the machine bits of the default layout, instead of
+    /// panicking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{SnowflakeError, SnowflakeIdGenerator};
+    ///
+    /// let id_generator = SnowflakeIdGenerator::try_new_from_ip("102.65.2.123").unwrap();
+    ///
+    /// assert_eq!(id_generator.machine_bits, 2 << 8 | 123);
+    /// assert_eq!(
+    ///     SnowflakeIdGenerator::try_new_from_ip("102.65.2").unwrap_err(),
+    ///     SnowflakeError::InvalidIp
+    /// );
+    /// assert_eq!(
+    ///     SnowflakeIdGenerator::try_new_from_ip("102.65.4.0").unwrap_err(),
+    ///     SnowflakeError::InvalidMachineId(1024)
+    /// );
+    /// ```
+    pub fn try_new_from_ip(ip: &str) -> Result<SnowflakeIdGenerator, SnowflakeError> {
+        let ip: Ipv4Addr = ip.parse().map_err(|_| SnowflakeError::InvalidIp)?;
+        let octets = ip.octets();
+
+        let machine_bits = i64::from(octets[2]) << 8 | i64::from(octets[3]);
+        if machine_bits > MACHINE_MASK {
+            return Err(SnowflakeError::InvalidMachineId(machine_bits));
+        }
+        Ok(SnowflakeIdGenerator::from_machine_bits(machine_bits))
+    }
+
+    /// Pack the ids with `layout` instead of the default one.
+    ///
+    /// Starts from the epoch if the clock can't be read, the first id reads
+    /// the clock again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{FieldOrder, Layout, SnowflakeIdGenerator};
+    ///
+    /// let layout = Layout::default().with_field_order(FieldOrder::SequenceMachine);
+    /// let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string())
+    ///     .with_layout(layout);
+    ///
+    /// let id = id_generator.real_time_generate();
+    ///
+    /// assert_eq!(id_generator.reverse(id as u64).machine_bits, id_generator.machine_bits);
+    /// ```
+    pub fn with_layout(mut self, layout: Layout) -> SnowflakeIdGenerator {
+        self.last_time_millis = self.time_source.try_now(layout.time_unit()).unwrap_or(0);
+        self.layout = layout;
+        self
+    }
+
+    /// Start the auto-increment record of every millisecond at a random
+    /// offset, wrapping around within its field.
+    ///
+    /// Ids then don't all end in low auto-increment records, which keeps
+    /// modulo-based sharding on the low bits even.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::SnowflakeIdGenerator;
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string())
+    ///     .with_random_idx_start();
+    ///
+    /// id_generator.real_time_generate();
+    /// ```
+    pub fn with_random_idx_start(mut self) -> SnowflakeIdGenerator {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+
+        self.idx_rng = Some(nanos ^ (self.machine_bits as u64) << 32);
+        self
+    }
 
-        let ip_split: Vec<&str> = ip.split(".").collect();
+    /// Fold the process id into the machine bits reserved by
+    /// `Layout::with_pid_bits`.
+    ///
+    /// Call it after `with_layout`, a layout without pid bits leaves the
+    /// machine bits as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{Layout, SnowflakeIdGenerator};
+    ///
+    /// let id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string())
+    ///     .with_layout(Layout::SECONDS.with_pid_bits(4))
+    ///     .with_process_id();
+    ///
+    /// assert_eq!(id_generator.machine_bits >> 4, (2 << 8 | 123) >> 4);
+    /// assert_eq!(id_generator.machine_bits & 0xF, i64::from(std::process::id() % 16));
+    /// ```
+    pub fn with_process_id(mut self) -> SnowflakeIdGenerator {
+        let pid = i64::from(std::process::id());
+        self.machine_bits = fold_machine_field(
+            self.machine_bits,
+            pid,
+            self.layout.thread_bits(),
+            self.layout.pid_bits(),
+        );
+        self
+    }
+
+    /// Fold `index` into the low machine bits reserved by
+    /// `Layout::with_thread_bits`, giving a thread its own generator.
+    ///
+    /// Call it after `with_layout`. Fails with
+    /// `SnowflakeError::InvalidThreadIndex` if the index doesn't fit the
+    /// thread bits, as two threads would then share the machine bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{Layout, SnowflakeError, SnowflakeIdGenerator};
+    ///
+    /// let layout = Layout::default().with_thread_bits(2);
+    /// let template = SnowflakeIdGenerator::new(1, 0).with_layout(layout);
+    ///
+    /// assert_eq!(template.with_thread_index(3).unwrap().machine_bits, 1 << 5 | 3);
+    /// assert_eq!(
+    ///     template.with_thread_index(4).unwrap_err(),
+    ///     SnowflakeError::InvalidThreadIndex(4)
+    /// );
+    /// ```
+    pub fn with_thread_index(
+        mut self,
+        index: u32,
+    ) -> Result<SnowflakeIdGenerator, SnowflakeError> {
+        let thread_bits = self.layout.thread_bits();
+        if i64::from(index) >> thread_bits != 0 {
+            return Err(SnowflakeError::InvalidThreadIndex(index));
+        }
+
+        self.machine_bits = fold_machine_suffix(self.machine_bits, i64::from(index), thread_bits);
+        Ok(self)
+    }
 
-        let machine_bits = numerize(ip_split[2]) << 8 | numerize(ip_split[3]);
-        
+    /// Keep generating when the clock can't be read, instead of panicking.
+    ///
+    /// On some embedded or sandboxed targets the wall clock isn't
+    /// available, or reads before the Unix epoch. The generator then
+    /// continues from the last known time: the auto-increment record counts
+    /// up and the time moves one unit forward every time it wraps, as with
+    /// `lazy_generate`, until the clock is back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::SnowflakeIdGenerator;
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string())
+    ///     .with_clock_fallback();
+    ///
+    /// id_generator.real_time_generate();
+    /// ```
+    pub fn with_clock_fallback(mut self) -> SnowflakeIdGenerator {
+        self.clock_fallback = true;
+        self
+    }
+
+    /// Wait for the next time unit with `wait_strategy` once the current
+    /// one is exhausted, instead of spinning.
+    ///
+    /// Takes a `'static` reference so the generator stays `Copy`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{Sleep, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new(1, 1).with_wait_strategy(&Sleep);
+    ///
+    /// id_generator.real_time_generate();
+    /// ```
+    pub fn with_wait_strategy(
+        mut self,
+        wait_strategy: &'static dyn WaitStrategy,
+    ) -> SnowflakeIdGenerator {
+        self.wait_strategy = Some(wait_strategy);
+        self
+    }
+
+    /// Read the clock from `time_source` instead of `SystemTime::now()`.
+    ///
+    /// Starts from the time of `time_source`, or from the epoch if it can't
+    /// be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{SnowflakeIdGenerator, TimeSource};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new(1, 1).with_time_source(TimeSource::System);
+    ///
+    /// id_generator.real_time_generate();
+    /// ```
+    pub fn with_time_source(mut self, time_source: TimeSource) -> SnowflakeIdGenerator {
+        self.last_time_millis = time_source.try_now(self.layout.time_unit()).unwrap_or(0);
+        self.time_source = time_source;
+        self
+    }
+
+    /// A snapshot of the counters of the generator.
+    pub fn stats(&self) -> GeneratorStats {
+        self.stats
+    }
+
+    /// Clone the generator, layout and options included, with another machine id.
+    ///
+    /// The clone starts fresh, the clock is read again and the
+    /// auto-increment record restarts. Fails with
+    /// `SnowflakeError::InvalidMachineId` if the id doesn't fit the machine
+    /// bits of the layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{Layout, SnowflakeIdGenerator};
+    ///
+    /// let template = SnowflakeIdGenerator::with_machine_id(0)
+    ///     .unwrap()
+    ///     .with_layout(Layout::JS_SAFE);
+    ///
+    /// let shard = template.clone_with_machine_id(3).unwrap();
+    ///
+    /// assert_eq!(shard.machine_bits, 3);
+    /// assert_eq!(shard.layout, Layout::JS_SAFE);
+    /// assert!(template.clone_with_machine_id(16).is_err());
+    /// ```
+    pub fn clone_with_machine_id(
+        &self,
+        machine_id: u32,
+    ) -> Result<SnowflakeIdGenerator, SnowflakeError> {
+        let machine_id = i64::from(machine_id);
+        if machine_id > self.layout.machine_mask() >> self.layout.machine_shift() {
+            return Err(SnowflakeError::InvalidMachineId(machine_id));
+        }
+
+        let now_millis = self.time_source.try_now(self.layout.time_unit());
+        let generator = SnowflakeIdGenerator {
+            last_time_millis: now_millis.unwrap_or(0),
+            machine_bits: machine_id,
+            idx: 0,
+            first_idx: 0,
+            stats: GeneratorStats::default(),
+            clock_reference: None,
+            ..*self
+        };
+
+        if generator.idx_rng.is_some() {
+            Ok(generator.with_random_idx_start())
+        } else {
+            Ok(generator)
+        }
+    }
+
+    /// Constructs a `SnowflakeIdGenerator` in a `const` context, e.g. to
+    /// embed it in a `static`.
+    ///
+    /// The clock isn't read until the first id is generated.
+    ///
+    /// # Panics
+    ///
+    /// Panics, at compile time in a `const` context, if `machine_bits`
+    /// doesn't fit the machine bits of `layout`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Mutex;
+    ///
+    /// use snowflake::{Layout, SnowflakeIdGenerator};
+    ///
+    /// static GENERATOR: Mutex<SnowflakeIdGenerator> =
+    ///     Mutex::new(SnowflakeIdGenerator::new_static(Layout::DEFAULT, 42));
+    ///
+    /// let id = GENERATOR.lock().unwrap().generate();
+    ///
+    /// assert_eq!(Layout::DEFAULT.decode(id).machine_bits, 42);
+    /// assert!(Layout::DEFAULT.decode(id).timestamp > 0);
+    /// ```
+    pub const fn new_static(layout: Layout, machine_bits: i64) -> SnowflakeIdGenerator {
+        assert!(
+            machine_bits >= 0 && machine_bits <= layout.machine_mask() >> layout.machine_shift(),
+            "machine_bits doesn't fit the layout"
+        );
+
+        SnowflakeIdGenerator {
+            last_time_millis: 0,
+            machine_bits,
+            idx: 0,
+            first_idx: 0,
+            idx_rng: None,
+            layout,
+            clock_fallback: false,
+            stats: GeneratorStats {
+                issued: 0,
+                exhaustion_waits: 0,
+                clock_anomalies: 0,
+                buffered: 0,
+            },
+            clock_events: None,
+            clock_reference: None,
+            wait_strategy: None,
+            time_source: TimeSource::System,
+            past: None,
+        }
+    }
+
+    /// Constructs a `SnowflakeIdGenerator` from already computed machine bits.
+    ///
+    /// Starts from the epoch if the clock can't be read, the first id reads
+    /// the clock again.
+    pub(crate) fn from_machine_bits(machine_bits: i64) -> SnowflakeIdGenerator {
         SnowflakeIdGenerator {
-            last_time_millis,
+            last_time_millis: TimeUnit::Milliseconds.try_now().unwrap_or(0),
             machine_bits,
-            idx: 0
+            idx: 0,
+            first_idx: 0,
+            idx_rng: None,
+            layout: Layout::default(),
+            clock_fallback: false,
+            stats: GeneratorStats::default(),
+            clock_events: None,
+            clock_reference: None,
+            wait_strategy: None,
+            time_source: TimeSource::System,
+            past: None,
         }
     }
 
@@ -61,39 +677,159 @@ impl SnowflakeIdGenerator {
     /// id_generator.real_time_generate();
     /// ```
     pub fn real_time_generate(&mut self) -> i64 {
-        self.idx = (self.idx + 1) % 2048;
+        let mut now_millis = match self.read_clock() {
+            Some(now_millis) => now_millis,
+            None => {
+                self.stats.clock_anomalies += 1;
+                return self.lazy_generate();
+            }
+        };
 
-        let mut now_millis = get_time_millis();
+        self.next_idx();
+        self.stats.issued += 1;
 
         //supplement code for 'clock is moving backwards situation'.
 
         // If the milliseconds of the current clock are equal to
         // the number of milliseconds of the most recently generated id,
-        // then check if enough 2048 are generated,
-        // if enough then busy wait until the next millisecond.
+        // then check if the auto-increment record wrapped around,
+        // if so then busy wait until the next millisecond.
         if now_millis == self.last_time_millis {
-            if self.idx == 0 {
-                now_millis = biding_time_conditions(self.last_time_millis);
+            if self.idx == self.first_idx {
+                now_millis = self.wait_next_unit();
                 self.last_time_millis = now_millis;
+                self.restart_idx();
             }
         } else {
+            if now_millis < self.last_time_millis {
+                self.clock_regressed(now_millis);
+            }
             self.last_time_millis = now_millis;
-            self.idx = 0;
+            self.restart_idx();
         }
 
-        // last_time_millis is 64 bits，left shift 23 bit，store 41 bits 
-        // machine is 20 bits, left shift 10 bit, store 10 bits
-        // idx complementing bits.
-        self.last_time_millis << 22
-            | ((self.machine_bits << 12) as i64)
-            | (self.idx as i64)
+        // Pack last_time_millis, machine bits and idx as the layout says.
+        self.layout.compose(self.last_time_millis, self.machine_bits, self.idx)
+    }
+
+    /// Like `real_time_generate`, but fails instead of issuing ids when the
+    /// clock is behind the last id issued or past the epoch of the layout.
+    ///
+    /// The clock fallback doesn't apply, a clock that can't be read fails
+    /// with `SnowflakeError::ClockBeforeEpoch`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{SnowflakeError, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    /// let id = id_generator.try_real_time_generate().unwrap();
+    ///
+    /// // The clock seems to have gone back 10 seconds.
+    /// id_generator.last_time_millis += 10_000;
+    ///
+    /// assert!(matches!(
+    ///     id_generator.try_real_time_generate(),
+    ///     Err(SnowflakeError::ClockMovedBackwards { .. })
+    /// ));
+    /// ```
+    pub fn try_real_time_generate(&mut self) -> Result<SnowflakeId, SnowflakeError> {
+        let time_unit = self.layout.time_unit();
+        let now = match self.time_source.try_now(time_unit) {
+            Some(now) => now,
+            None => return Err(self.clock_lost()),
+        };
+        self.observe_clock(now);
+
+        if now < self.last_time_millis {
+            self.clock_regressed(now);
+            return Err(SnowflakeError::ClockMovedBackwards {
+                last_millis: time_unit.to_millis(self.last_time_millis),
+                now_millis: time_unit.to_millis(now),
+            });
+        }
+        self.layout.try_compose(now, self.machine_bits, 0)?;
+
+        let last_idx = self.idx;
+        self.next_idx();
+        if now != self.last_time_millis {
+            self.last_time_millis = now;
+            self.restart_idx();
+        } else if self.idx == self.first_idx {
+            match self.try_wait_next_unit() {
+                Some(now) => self.last_time_millis = now,
+                None => {
+                    self.idx = last_idx;
+                    return Err(self.clock_lost());
+                }
+            }
+            self.restart_idx();
+        }
+
+        self.stats.issued += 1;
+        Ok(SnowflakeId(self.layout.compose(
+            self.last_time_millis,
+            self.machine_bits,
+            self.idx,
+        )))
+    }
+
+    /// Like `real_time_generate`, but returns `None` instead of waiting
+    /// when the current time unit has run out of ids.
+    ///
+    /// Latency-critical paths can shed load rather than spin until the
+    /// next millisecond.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{Layout, SnowflakeIdGenerator};
+    ///
+    /// // 4 ids per millisecond.
+    /// let mut id_generator = SnowflakeIdGenerator::new(1, 1).with_layout(Layout::new(41, 10, 2));
+    ///
+    /// let issued = (0..100).filter_map(|_| id_generator.try_generate()).count();
+    ///
+    /// assert!(issued < 100);
+    /// ```
+    pub fn try_generate(&mut self) -> Option<SnowflakeId> {
+        let now_millis = match self.read_clock() {
+            Some(now_millis) => now_millis,
+            None => {
+                self.stats.clock_anomalies += 1;
+                return Some(SnowflakeId(self.lazy_generate()));
+            }
+        };
+
+        if now_millis == self.last_time_millis {
+            let last_idx = self.idx;
+            self.next_idx();
+            if self.idx == self.first_idx {
+                self.idx = last_idx;
+                return None;
+            }
+        } else {
+            if now_millis < self.last_time_millis {
+                self.clock_regressed(now_millis);
+            }
+            self.last_time_millis = now_millis;
+            self.restart_idx();
+        }
+
+        self.stats.issued += 1;
+        Some(SnowflakeId(self.layout.compose(
+            self.last_time_millis,
+            self.machine_bits,
+            self.idx,
+        )))
     }
 
     /// The basic guarantee time punctuality.
     ///
     /// Basic guarantee time punctuality.
-    /// sometimes one millis can't use up 2048 ID, the property of the ID isn't real-time.
-    /// But setting time after every 2048 calls.
+    /// sometimes one millis can't use up all its ID, the property of the ID isn't real-time.
+    /// But setting time every time the auto-increment record wraps around.
     /// # Examples
     ///
     /// ```
@@ -103,25 +839,32 @@ impl SnowflakeIdGenerator {
     /// id_generator.generate();
     /// ```
     pub fn generate(&mut self) -> i64 {
-        self.idx = (self.idx + 1) % 2048;
-
-        // Maintenance `last_time_millis` for every 2048 ids generated.
-        if self.idx == 0 {
-            let mut now_millis = get_time_millis();
+        self.start_clock();
+        self.next_idx();
+        self.stats.issued += 1;
 
-            if now_millis == self.last_time_millis {
-                now_millis = biding_time_conditions(self.last_time_millis);
-            }
+        // Maintenance `last_time_millis` every time the auto-increment record wraps around.
+        if self.idx == self.first_idx {
+            let now_millis = match self.read_clock() {
+                Some(now_millis) if now_millis == self.last_time_millis => self.wait_next_unit(),
+                Some(now_millis) => {
+                    if now_millis < self.last_time_millis {
+                        self.clock_regressed(now_millis);
+                    }
+                    now_millis
+                }
+                None => {
+                    self.stats.clock_anomalies += 1;
+                    self.last_time_millis + 1
+                }
+            };
 
             self.last_time_millis = now_millis;
+            self.restart_idx();
         }
 
-        // last_time_millis is 64 bits，left shift 23 bit，store 41 bits 
-        // machine is 28 bits, left shift 12 bit, store 16 bits
-        // idx complementing bits.
-        self.last_time_millis << 22
-            | ((self.machine_bits << 12) as i64)
-            | (self.idx as i64)
+        // Pack last_time_millis, machine bits and idx as the layout says.
+        self.layout.compose(self.last_time_millis, self.machine_bits, self.idx)
     }
 
     /// The lazy generate.
@@ -138,18 +881,223 @@ impl SnowflakeIdGenerator {
     /// id_generator.lazy_generate();
     /// ```
     pub fn lazy_generate(&mut self) -> i64 {
-        self.idx = (self.idx + 1) % 2048;
+        self.start_clock();
+        self.next_idx();
+        self.stats.issued += 1;
 
-        if self.idx == 0 {
+        if self.idx == self.first_idx {
             self.last_time_millis += 1;
+            self.restart_idx();
+        }
+
+        // Pack last_time_millis, machine bits and idx as the layout says.
+        self.layout.compose(self.last_time_millis, self.machine_bits, self.idx)
+    }
+
+    #[inline(always)]
+    // Read the clock once for generators built without it, by `new_static`,
+    // so `generate` and `lazy_generate` don't count from the epoch.
+    fn start_clock(&mut self) {
+        if self.last_time_millis == 0 {
+            let now = self.time_source.try_now(self.layout.time_unit());
+            self.last_time_millis = now.unwrap_or(0);
+        }
+    }
+
+    #[inline(always)]
+    // Read the clock in the unit of the layout, `None` if it can't be read
+    // and the clock fallback is enabled.
+    fn read_clock(&mut self) -> Option<i64> {
+        match self.time_source.try_now(self.layout.time_unit()) {
+            None if !self.clock_fallback => panic!("Time went mackward"),
+            None => None,
+            Some(now_millis) => {
+                self.observe_clock(now_millis);
+                Some(now_millis)
+            }
+        }
+    }
+
+    #[inline(always)]
+    // Wait for the clock to pass `last_time_millis`, or move one unit
+    // forward if the clock is lost and the clock fallback is enabled.
+    fn wait_next_unit(&mut self) -> i64 {
+        match self.try_wait_next_unit() {
+            Some(now_millis) => now_millis,
+            None if self.clock_fallback => self.last_time_millis + 1,
+            None => panic!("Time went mackward"),
+        }
+    }
+
+    #[inline(always)]
+    // Wait for the clock to pass `last_time_millis`, `None` if the clock
+    // is lost.
+    fn try_wait_next_unit(&mut self) -> Option<i64> {
+        self.stats.exhaustion_waits += 1;
+        let started = self.clock_events.map(|_| Instant::now());
+
+        let now_millis = biding_time_conditions(
+            self.last_time_millis,
+            self.layout.time_unit(),
+            self.time_source,
+            self.wait_strategy,
+        );
+        self.exhaustion_waited(started);
+        now_millis
+    }
+
+    // The error of a clock that can't be read.
+    fn clock_lost(&self) -> SnowflakeError {
+        match self.time_source {
+            TimeSource::Manual(clock) => {
+                SnowflakeError::ClockBeforeEpoch(clock.load(std::sync::atomic::Ordering::SeqCst))
+            }
+            _ => SnowflakeError::ClockBeforeEpoch(signed_time_millis()),
+        }
+    }
+
+    #[inline(always)]
+    // Advance the auto-increment record, wrapping around at the layout's capacity.
+    fn next_idx(&mut self) {
+        self.idx = if self.idx >= self.layout.max_idx() {
+            0
+        } else {
+            self.idx + 1
+        };
+    }
+
+    #[inline(always)]
+    // Start the auto-increment record of a new millisecond,
+    // at a random offset if enabled.
+    fn restart_idx(&mut self) {
+        if let Some(state) = self.idx_rng.as_mut() {
+            *state = state.wrapping_add(SPLIT_MIX64_GAMMA);
+            self.first_idx = split_mix64(*state) as u16 & self.layout.max_idx();
         }
 
-        // last_time_millis is 64 bits，left shift 32 bit，store 42 bits 
-        // machine is 28 bits, left shift 12 bit, store 16 bits
-        // idx complementing bits.
-        self.last_time_millis << 22
-            | ((self.machine_bits << 12) as i64)
-            | (self.idx as i64)
+        self.idx = self.first_idx;
+    }
+
+    /// Generate an id for `timestamp`, instead of the clock.
+    ///
+    /// Takes any `UnixMillis` type: `SystemTime`, or with the features of
+    /// the same name, chrono's `DateTime` and time's `OffsetDateTime`.
+    ///
+    /// The timestamp becomes the generator's high-water mark when it is
+    /// later than the last one used. Fails with
+    /// `SnowflakeError::BeforeHighWaterMark` for earlier timestamps, whose
+    /// ids could sort before ids already issued or repeat them, see
+    /// `generate_at_allow_past`. Fails with
+    /// `SnowflakeError::SequenceExhausted` once the time unit is used up,
+    /// and with `SnowflakeError::EpochOverflow` if the timestamp doesn't fit
+    /// the timestamp bits of the layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// use snowflake::{SnowflakeError, SnowflakeIdGenerator};
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    /// let now = SystemTime::now();
+    ///
+    /// assert!(id_generator.generate_at(now).is_ok());
+    /// assert!(matches!(
+    ///     id_generator.generate_at(now - Duration::from_secs(1)),
+    ///     Err(SnowflakeError::BeforeHighWaterMark { .. })
+    /// ));
+    /// ```
+    pub fn generate_at<T: UnixMillis>(
+        &mut self,
+        timestamp: T,
+    ) -> Result<SnowflakeId, SnowflakeError> {
+        let timestamp = self.layout.time_unit().from_millis(timestamp.unix_millis());
+        self.layout.try_compose(timestamp, self.machine_bits, 0)?;
+
+        if timestamp < self.last_time_millis {
+            return Err(SnowflakeError::BeforeHighWaterMark {
+                timestamp,
+                high_water_mark: self.last_time_millis,
+            });
+        }
+
+        if timestamp == self.last_time_millis {
+            let last_idx = self.idx;
+            self.next_idx();
+            if self.idx == self.first_idx {
+                self.idx = last_idx;
+                return Err(SnowflakeError::SequenceExhausted(timestamp));
+            }
+        } else {
+            self.last_time_millis = timestamp;
+            self.restart_idx();
+        }
+
+        self.stats.issued += 1;
+        Ok(SnowflakeId(self.layout.compose(timestamp, self.machine_bits, self.idx)))
+    }
+
+    /// Like `generate_at`, but also mints ids before the high-water mark.
+    ///
+    /// Earlier timestamps take their own auto-increment record, apart from
+    /// the one of the high-water mark, which they don't move. They may
+    /// still repeat ids issued in that time unit by the real-time methods:
+    /// only use it where that can't happen, such as backfilling a time
+    /// range no generator issued ids for.
+    ///
+    /// Past timestamps must come in order: fails with
+    /// `SnowflakeError::BeforeHighWaterMark` for a timestamp before the
+    /// last past one, and with `SnowflakeError::SequenceExhausted` once its
+    /// time unit is used up. Backfills out of order should use
+    /// `BackfillGenerator`, which keeps a record per timestamp.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// use snowflake::SnowflakeIdGenerator;
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    /// let now = SystemTime::now();
+    /// let yesterday = now - Duration::from_secs(86_400);
+    ///
+    /// let today = id_generator.generate_at(now).unwrap();
+    /// let backfilled = id_generator.generate_at_allow_past(yesterday).unwrap();
+    ///
+    /// assert!(backfilled < today);
+    /// ```
+    pub fn generate_at_allow_past<T: UnixMillis>(
+        &mut self,
+        timestamp: T,
+    ) -> Result<SnowflakeId, SnowflakeError> {
+        let timestamp = match self.generate_at(timestamp) {
+            Err(SnowflakeError::BeforeHighWaterMark { timestamp, .. }) => timestamp,
+            result => return result,
+        };
+
+        let idx = match self.past {
+            Some((past, _)) if timestamp < past => {
+                return Err(SnowflakeError::BeforeHighWaterMark {
+                    timestamp,
+                    high_water_mark: past,
+                });
+            }
+            Some((past, idx)) if timestamp == past => {
+                if idx >= self.layout.max_idx() {
+                    return Err(SnowflakeError::SequenceExhausted(timestamp));
+                }
+                idx + 1
+            }
+            _ => 0,
+        };
+
+        self.past = Some((timestamp, idx));
+        self.stats.issued += 1;
+        self.layout
+            .try_compose(timestamp, self.machine_bits, idx)
+            .map(SnowflakeId)
     }
 
     /// Generate with timestamp
@@ -159,14 +1107,16 @@ impl SnowflakeIdGenerator {
     /// # Examples
     /// 
     /// ```
+    /// use chrono::Utc;
     /// use snowflake::SnowflakeIdGenerator;
     ///
     /// let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
     /// 
     /// let timestamp = Utc::now();
     /// 
-    /// id_generator.generate_with_timestamp(timestamp);
+    /// id_generator.generate_with_timestmap(timestamp);
     /// ```
+    #[cfg(feature = "chrono")]
     pub fn generate_with_timestmap(&self, timestamp: DateTime<Utc>) -> i64 {
         let timestamp = timestamp.timestamp();
         self.generate_with_unix(timestamp)
@@ -183,29 +1133,80 @@ impl SnowflakeIdGenerator {
     ///
     /// let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
     /// 
-    /// let timestamp = Utc::now();
+    /// let timestamp = snowflake::get_time_millis();
     /// 
-    /// id_generator.generate_with_timestamp(timestamp.timestamp());
+    /// id_generator.generate_with_unix(timestamp);
     /// ```
     pub fn generate_with_unix(&self, timestamp: i64) -> i64 {
-        timestamp << 22 | ((self.machine_bits << 12) as i64) | 0 as i64
+        self.layout.compose(timestamp, self.machine_bits, 0)
     }
     
     pub fn reverse(&self, snowflake: u64) -> Snowflake {
-        let timestamp_mask: u64 = 0x7FFFFFFFFFC00000;
-        let ip_mask: u64 = 0x3FF000;
-        let sequence_mask: u64 = 0x3FF;
+        self.layout.decode(snowflake as i64)
+    }
 
-        let timestamp = ((snowflake & timestamp_mask) >> 22) as i64;
-        let machine = ((snowflake & ip_mask) >> 12) as i64;
-        let sequence = (snowflake & sequence_mask) as u16;
+    /// Split a slice of ids into their fields, like `reverse` in bulk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::SnowflakeIdGenerator;
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new(1, 7);
+    /// let ids: Vec<i64> = (0..100).map(|_| id_generator.real_time_generate()).collect();
+    ///
+    /// let snowflakes = id_generator.reverse_many(&ids);
+    ///
+    /// assert!(snowflakes.iter().all(|snowflake| snowflake.machine_bits == 1 << 5 | 7));
+    /// ```
+    pub fn reverse_many(&self, ids: &[i64]) -> Vec<Snowflake> {
+        self.layout.decode_slice(ids)
+    }
 
-        Snowflake { timestamp, machine_bits: machine, idx: sequence }
+    /// Lazily split ids into their fields as they are iterated.
+    ///
+    /// The iterator copies the layout, it doesn't borrow the generator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::SnowflakeIdGenerator;
+    ///
+    /// let mut id_generator = SnowflakeIdGenerator::new(1, 7);
+    /// let ids: Vec<i64> = (0..100).map(|_| id_generator.real_time_generate()).collect();
+    ///
+    /// let mut snowflakes = id_generator.reverse_iter(ids.iter().copied());
+    /// id_generator.real_time_generate();
+    ///
+    /// assert!(snowflakes.all(|snowflake| snowflake.machine_bits == 1 << 5 | 7));
+    /// ```
+    pub fn reverse_iter<I: IntoIterator<Item = i64>>(&self, ids: I) -> DecodeIter<I::IntoIter> {
+        self.layout.decode_iter(ids)
     }
 }
 
+/// Split a slice of ids into their fields using the default `Layout`.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::SnowflakeIdGenerator;
+///
+/// let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+/// let ids: Vec<i64> = (0..100).map(|_| id_generator.real_time_generate()).collect();
+///
+/// let snowflakes = snowflake::decode_slice(&ids);
+/// ```
+pub fn decode_slice(ids: &[i64]) -> Vec<Snowflake> {
+    Layout::default().decode_slice(ids)
+}
+
 #[inline(always)]
 /// Get the latest milliseconds of the clock.
+///
+/// # Panics
+///
+/// Panics if the clock is before the Unix epoch, see `try_get_time_millis`.
 pub fn get_time_millis() -> i64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -213,20 +1214,94 @@ pub fn get_time_millis() -> i64 {
         .as_millis() as i64
 }
 
+/// Get the latest milliseconds of the clock.
+///
+/// Fails with `SnowflakeError::ClockBeforeEpoch` if the clock is before the
+/// Unix epoch.
+///
+/// # Examples
+///
+/// ```
+/// let now_millis = snowflake::try_get_time_millis().unwrap();
+///
+/// assert!(now_millis <= snowflake::get_time_millis());
+/// ```
+pub fn try_get_time_millis() -> Result<i64, SnowflakeError> {
+    since_epoch().map(|elapsed| elapsed.as_millis() as i64)
+}
+
+// Nanoseconds since the Unix epoch, for the 128-bit ids.
+pub(crate) fn try_get_time_nanos() -> Result<u64, SnowflakeError> {
+    since_epoch().map(|elapsed| elapsed.as_nanos() as u64)
+}
+
+// Time since the Unix epoch, `SnowflakeError::ClockBeforeEpoch` if the clock
+// is before it.
+fn since_epoch() -> Result<Duration, SnowflakeError> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|error| SnowflakeError::ClockBeforeEpoch(-(error.duration().as_millis() as i64)))
+}
+
+// Milliseconds since the Unix epoch, negative if the clock is before it.
+fn signed_time_millis() -> i64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_millis() as i64,
+        Err(error) => -(error.duration().as_millis() as i64),
+    }
+}
+
 #[inline(always)]
-// Constantly refreshing the latest milliseconds by busy waiting.
-fn biding_time_conditions(last_time_millis: i64) -> i64 {
+// Constantly refreshing the latest milliseconds, or time units, by busy waiting.
+// Units of a second are slept through rather than spun.
+// `None` if the clock can't be read.
+pub(crate) fn biding_time_conditions(
+    last_time_millis: i64,
+    time_unit: TimeUnit,
+    time_source: TimeSource,
+    wait_strategy: Option<&dyn WaitStrategy>,
+) -> Option<i64> {
     let mut latest_time_millis: i64;
     loop {
-        latest_time_millis = get_time_millis();
+        latest_time_millis = time_source.try_now(time_unit)?;
         if latest_time_millis > last_time_millis {
-            return latest_time_millis;
+            return Some(latest_time_millis);
+        }
+
+        match (wait_strategy, time_unit.millis_to_sleep()) {
+            (Some(wait_strategy), _) => {
+                wait_strategy.wait_until(time_unit.to_millis(last_time_millis + 1))
+            }
+            (None, 0) => spin_loop(),
+            (None, millis) => thread::sleep(Duration::from_millis(millis)),
         }
-        spin_loop();
     }
 }
 
 #[inline(always)]
-fn numerize(part: &str) -> i64 {
-    part.to_string().parse::<i64>().unwrap()
-}
\ No newline at end of file
+// Replace the low `suffix_bits` of the machine bits with `suffix`.
+pub(crate) fn fold_machine_suffix(machine_bits: i64, suffix: i64, suffix_bits: u32) -> i64 {
+    fold_machine_field(machine_bits, suffix, 0, suffix_bits)
+}
+
+#[inline(always)]
+// Replace the `field_bits` of the machine bits above `shift` with `value`.
+pub(crate) fn fold_machine_field(
+    machine_bits: i64,
+    value: i64,
+    shift: u32,
+    field_bits: u32,
+) -> i64 {
+    let mask = ((1 << field_bits) - 1) << shift;
+    (machine_bits & !mask) | ((value << shift) & mask)
+}
+
+pub(crate) const SPLIT_MIX64_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+#[inline(always)]
+// Scramble the bits of `z`, the output function of SplitMix64.
+pub(crate) fn split_mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
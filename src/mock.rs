@@ -0,0 +1,99 @@
+//! A generator of chosen ids for tests.
+
+use std::fmt;
+
+use crate::{IdGenerator, SnowflakeError, SnowflakeId};
+
+/// The `MockIdGenerator` type is a test double yielding ids chosen up
+/// front, so tests can assert on exact ids without depending on the clock.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::{IdGenerator, MockIdGenerator};
+///
+/// fn create_order<G: IdGenerator>(ids: &mut G) -> String {
+///     format!("order-{}", ids.next_id().unwrap().0)
+/// }
+///
+/// let mut ids = MockIdGenerator::from_ids(vec![7, 8]);
+///
+/// assert_eq!(create_order(&mut ids), "order-7");
+/// assert_eq!(create_order(&mut ids), "order-8");
+/// ```
+pub struct MockIdGenerator {
+    next: Box<dyn FnMut() -> Option<Result<SnowflakeId, SnowflakeError>> + Send>,
+    issued: u64,
+}
+
+impl MockIdGenerator {
+    /// Constructs a new `MockIdGenerator` yielding `ids` in order.
+    ///
+    /// # Panics
+    ///
+    /// `next_id` panics once every id was yielded.
+    pub fn from_ids<I>(ids: I) -> MockIdGenerator
+    where
+        I: IntoIterator,
+        I::Item: Into<SnowflakeId>,
+        I::IntoIter: Send + 'static,
+    {
+        let mut ids = ids.into_iter();
+        MockIdGenerator {
+            next: Box::new(move || ids.next().map(|id| Ok(id.into()))),
+            issued: 0,
+        }
+    }
+
+    /// Constructs a new `MockIdGenerator` calling `f` for every id, e.g.
+    /// to count up or to fail on demand.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{IdGenerator, MockIdGenerator, SnowflakeError, SnowflakeId};
+    ///
+    /// let mut next = 0;
+    /// let mut ids = MockIdGenerator::from_fn(move || {
+    ///     next += 1;
+    ///     match next {
+    ///         3 => Err(SnowflakeError::SequenceExhausted(0)),
+    ///         next => Ok(SnowflakeId(next)),
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(ids.next_id(), Ok(SnowflakeId(1)));
+    /// assert_eq!(ids.next_id(), Ok(SnowflakeId(2)));
+    /// assert!(ids.next_id().is_err());
+    /// ```
+    pub fn from_fn<F>(mut f: F) -> MockIdGenerator
+    where
+        F: FnMut() -> Result<SnowflakeId, SnowflakeError> + Send + 'static,
+    {
+        MockIdGenerator {
+            next: Box::new(move || Some(f())),
+            issued: 0,
+        }
+    }
+
+    /// Number of ids yielded so far, errors included.
+    pub fn issued(&self) -> u64 {
+        self.issued
+    }
+}
+
+impl IdGenerator for MockIdGenerator {
+    fn next_id(&mut self) -> Result<SnowflakeId, SnowflakeError> {
+        let id = (self.next)().expect("MockIdGenerator ran out of ids");
+        self.issued += 1;
+        id
+    }
+}
+
+impl fmt::Debug for MockIdGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockIdGenerator")
+            .field("issued", &self.issued)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,300 @@
+//! Reversible obfuscation of public ids.
+//!
+//! Ids hand out their creation time and how busy the issuer was, and
+//! consecutive ids are easy to enumerate. The permutations here map ids to
+//! random looking non-negative `i64`s and back, given the same key, so a
+//! single column keeps storing the real id.
+
+use std::fmt;
+
+use crate::{split_mix64, SPLIT_MIX64_GAMMA};
+
+// Rounds of the Feistel network.
+const FEISTEL_ROUNDS: usize = 6;
+
+// The ids permuted, every non-negative i64.
+const ID_MASK: u64 = i64::MAX as u64;
+
+/// The `Feistel` type is a keyed permutation of the 63-bit ids.
+///
+/// A balanced Feistel network over 64 bits, with a SplitMix64 round
+/// function, walked until it lands back in the non-negative range. It
+/// hides the ids from casual inspection and enumeration, it isn't meant to
+/// resist cryptanalysis.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::obfuscate::Feistel;
+///
+/// let feistel = Feistel::new(0x5EC2E7);
+/// let id = 6_725_650_666_386_976_769;
+///
+/// let public = feistel.encode(id);
+///
+/// assert_ne!(public, id);
+/// assert!(public >= 0);
+/// assert_eq!(feistel.decode(public), id);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Feistel {
+    round_keys: [u64; FEISTEL_ROUNDS],
+}
+
+impl Feistel {
+    /// Constructs a new `Feistel` permutation from `key`.
+    pub fn new(key: u64) -> Feistel {
+        let mut state = key;
+        let mut round_keys = [0; FEISTEL_ROUNDS];
+        for round_key in round_keys.iter_mut() {
+            state = state.wrapping_add(SPLIT_MIX64_GAMMA);
+            *round_key = split_mix64(state);
+        }
+
+        Feistel { round_keys }
+    }
+
+    /// Map an id to its public form.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is negative.
+    pub fn encode(&self, id: i64) -> i64 {
+        assert!(id >= 0, "ids must be non-negative");
+
+        let mut value = id as u64;
+        loop {
+            value = self.encrypt(value);
+            if value <= ID_MASK {
+                return value as i64;
+            }
+        }
+    }
+
+    /// Map a public id back to the id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `public` is negative.
+    pub fn decode(&self, public: i64) -> i64 {
+        assert!(public >= 0, "ids must be non-negative");
+
+        let mut value = public as u64;
+        loop {
+            value = self.decrypt(value);
+            if value <= ID_MASK {
+                return value as i64;
+            }
+        }
+    }
+
+    fn encrypt(&self, value: u64) -> u64 {
+        let (mut left, mut right) = ((value >> 32) as u32, value as u32);
+        for &round_key in self.round_keys.iter() {
+            let next = left ^ round(round_key, right);
+            left = right;
+            right = next;
+        }
+
+        (left as u64) << 32 | right as u64
+    }
+
+    fn decrypt(&self, value: u64) -> u64 {
+        let (mut left, mut right) = ((value >> 32) as u32, value as u32);
+        for &round_key in self.round_keys.iter().rev() {
+            let previous = right ^ round(round_key, left);
+            right = left;
+            left = previous;
+        }
+
+        (left as u64) << 32 | right as u64
+    }
+}
+
+/// The `Optimus` type is a lighter permutation of the 63-bit ids, the
+/// Optimus scheme.
+///
+/// Ids are multiplied by an odd prime modulo 2^63 and XORed with a random mask,
+/// decoding multiplies by the modular inverse of the prime. Cheaper than
+/// `Feistel`, but consecutive ids differ in a predictable way, so it only
+/// deters casual enumeration.
+///
+/// # Examples
+///
+/// Parameters are validated at compile time in a constant:
+///
+/// ```
+/// use snowflake::obfuscate::Optimus;
+///
+/// const OPTIMUS: Optimus = Optimus::new(2_305_843_009_213_693_951, 0x1234_5678_9ABC_DEF0);
+///
+/// let public = OPTIMUS.encode(42);
+///
+/// assert_eq!(OPTIMUS.decode(public), 42);
+/// ```
+///
+/// or at run time when they come from configuration:
+///
+/// ```
+/// use snowflake::obfuscate::{Optimus, OptimusError};
+///
+/// assert_eq!(Optimus::try_new(15, 0), Err(OptimusError::NotPrime(15)));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Optimus {
+    prime: u64,
+    inverse: u64,
+    xor: u64,
+}
+
+/// The `OptimusError` type explains why `Optimus` parameters were rejected.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OptimusError {
+    /// The multiplier isn't an odd prime below 2^63, even multipliers have
+    /// no inverse modulo 2^63.
+    NotPrime(u64),
+    /// The XOR mask doesn't fit the 63-bit ids.
+    XorOutOfRange(u64),
+}
+
+impl Optimus {
+    /// Constructs a new `Optimus` from an odd prime below 2^63 and a random
+    /// XOR mask below 2^63.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parameters are invalid, see `try_new`. In a constant
+    /// this fails the build.
+    pub const fn new(prime: u64, xor: u64) -> Optimus {
+        match Optimus::try_new(prime, xor) {
+            Ok(optimus) => optimus,
+            Err(OptimusError::NotPrime(_)) => {
+                panic!("Optimus multiplier is not an odd prime below 2^63")
+            }
+            Err(OptimusError::XorOutOfRange(_)) => panic!("Optimus XOR mask is not below 2^63"),
+        }
+    }
+
+    /// Constructs a new `Optimus`, failing if `prime` isn't an odd prime
+    /// below 2^63 or `xor` doesn't fit the 63-bit ids.
+    pub const fn try_new(prime: u64, xor: u64) -> Result<Optimus, OptimusError> {
+        if prime > ID_MASK || prime & 1 == 0 || !is_prime(prime) {
+            return Err(OptimusError::NotPrime(prime));
+        }
+        if xor > ID_MASK {
+            return Err(OptimusError::XorOutOfRange(xor));
+        }
+
+        // Newton's iteration doubles the correct low bits of the inverse,
+        // an odd number is its own inverse modulo 8.
+        let mut inverse = prime;
+        let mut i = 0;
+        while i < 5 {
+            inverse = inverse.wrapping_mul(2u64.wrapping_sub(prime.wrapping_mul(inverse)));
+            i += 1;
+        }
+
+        Ok(Optimus {
+            prime,
+            inverse: inverse & ID_MASK,
+            xor,
+        })
+    }
+
+    /// Map an id to its public form.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is negative.
+    pub const fn encode(&self, id: i64) -> i64 {
+        assert!(id >= 0, "ids must be non-negative");
+
+        (((id as u64).wrapping_mul(self.prime) & ID_MASK) ^ self.xor) as i64
+    }
+
+    /// Map a public id back to the id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `public` is negative.
+    pub const fn decode(&self, public: i64) -> i64 {
+        assert!(public >= 0, "ids must be non-negative");
+
+        (((public as u64) ^ self.xor).wrapping_mul(self.inverse) & ID_MASK) as i64
+    }
+}
+
+impl fmt::Display for OptimusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptimusError::NotPrime(prime) => {
+                write!(f, "{} is not an odd prime below 2^63", prime)
+            }
+            OptimusError::XorOutOfRange(xor) => write!(f, "XOR mask {} is not below 2^63", xor),
+        }
+    }
+}
+
+impl std::error::Error for OptimusError {}
+
+// Deterministic Miller-Rabin, these bases cover every u64.
+const fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    if n < 2 {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < BASES.len() {
+        if n.is_multiple_of(BASES[i]) {
+            return n == BASES[i];
+        }
+        i += 1;
+    }
+
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+
+    let mut i = 0;
+    while i < BASES.len() {
+        let mut x = pow_mod(BASES[i], d, n);
+        i += 1;
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+
+        let mut r = 1;
+        while r < s && x != n - 1 {
+            x = mul_mod(x, x, n);
+            r += 1;
+        }
+        if x != n - 1 {
+            return false;
+        }
+    }
+
+    true
+}
+
+const fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+const fn pow_mod(mut base: u64, mut exp: u64, m: u64) -> u64 {
+    let mut result = 1;
+    base %= m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+#[inline(always)]
+fn round(round_key: u64, half: u32) -> u32 {
+    split_mix64(round_key ^ half as u64) as u32
+}
@@ -0,0 +1,80 @@
+//! Generators for rayon workers.
+
+use std::sync::Mutex;
+
+use crate::{
+    fold_machine_suffix, IdGenerator, SnowflakeError, SnowflakeId, SnowflakeIdGenerator,
+};
+
+/// The `GeneratorPool` type hands every rayon worker its own generator.
+///
+/// Each generator gets a distinct suffix in the low `suffix_bits` of the
+/// machine bits, so workers never produce the same id. Calls made outside
+/// of the rayon pool share one extra generator.
+#[derive(Debug)]
+pub struct GeneratorPool {
+    generators: Vec<Mutex<SnowflakeIdGenerator>>,
+}
+
+impl GeneratorPool {
+    /// Constructs a new `GeneratorPool` sized for the current rayon pool.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `suffix_bits` can't give every worker, plus the shared
+    /// generator, a distinct suffix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::GeneratorPool;
+    ///
+    /// let pool = GeneratorPool::new(0x200, 8);
+    /// ```
+    pub fn new(machine_bits: i64, suffix_bits: u32) -> GeneratorPool {
+        let slots = rayon::current_num_threads() + 1;
+        assert!(
+            slots <= 1 << suffix_bits,
+            "{} suffix bits can't cover {} generators",
+            suffix_bits,
+            slots
+        );
+
+        let generators = (0..slots)
+            .map(|slot| {
+                let machine_bits = fold_machine_suffix(machine_bits, slot as i64, suffix_bits);
+                Mutex::new(SnowflakeIdGenerator::from_machine_bits(machine_bits))
+            })
+            .collect();
+
+        GeneratorPool { generators }
+    }
+
+    /// Generate an id with the generator of the current rayon worker.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rayon::prelude::*;
+    /// use snowflake::GeneratorPool;
+    ///
+    /// let pool = GeneratorPool::new(0x200, 8);
+    /// let ids: Vec<i64> = (0..100).into_par_iter().map(|_| pool.generate()).collect();
+    /// ```
+    pub fn generate(&self) -> i64 {
+        let slot = rayon::current_thread_index()
+            .filter(|index| index + 1 < self.generators.len())
+            .unwrap_or(self.generators.len() - 1);
+
+        self.generators[slot]
+            .lock()
+            .expect("Generator lock poisoned")
+            .real_time_generate()
+    }
+}
+
+impl IdGenerator for GeneratorPool {
+    fn next_id(&mut self) -> Result<SnowflakeId, SnowflakeError> {
+        Ok(SnowflakeId(self.generate()))
+    }
+}
@@ -0,0 +1,122 @@
+//! Ids that don't reveal how many were issued.
+
+use std::collections::HashSet;
+
+use crate::{
+    biding_time_conditions, IdGenerator, SnowflakeError, SnowflakeId, SnowflakeIdGenerator,
+};
+
+// Random bytes fetched from the OS at once.
+const RANDOM_BUFFER_LEN: usize = 256;
+
+/// The `PrivateIdGenerator` type fills the auto-increment record with
+/// random bits from the OS CSPRNG.
+///
+/// Sequential records tell anyone holding two ids of the same millisecond
+/// how many were issued in between. Random records don't: the records
+/// already issued in the current millisecond are remembered and drawn
+/// again on collision, so ids stay unique, and ids of the same millisecond
+/// no longer sort in issue order.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::{PrivateIdGenerator, SnowflakeIdGenerator};
+///
+/// let mut private = PrivateIdGenerator::new(SnowflakeIdGenerator::new(1, 1));
+///
+/// assert_ne!(private.generate(), private.generate());
+/// ```
+#[derive(Clone, Debug)]
+pub struct PrivateIdGenerator {
+    generator: SnowflakeIdGenerator,
+    issued: HashSet<u16>,
+    random: [u8; RANDOM_BUFFER_LEN],
+    random_pos: usize,
+}
+
+impl PrivateIdGenerator {
+    /// Constructs a new `PrivateIdGenerator` issuing the ids of `generator`,
+    /// with random auto-increment records.
+    pub fn new(generator: SnowflakeIdGenerator) -> PrivateIdGenerator {
+        PrivateIdGenerator {
+            generator,
+            issued: HashSet::new(),
+            random: [0; RANDOM_BUFFER_LEN],
+            random_pos: RANDOM_BUFFER_LEN,
+        }
+    }
+
+    /// Generate an id in real time.
+    ///
+    /// Once every record of the current millisecond is used, busy waits
+    /// until the next one. A clock moving backwards keeps issuing in the
+    /// last millisecond until it catches up.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the OS random source fails.
+    pub fn generate(&mut self) -> i64 {
+        let time_unit = self.generator.layout.time_unit();
+        let max_idx = self.generator.layout.max_idx();
+
+        let mut now_millis = self
+            .generator
+            .time_source
+            .try_now(time_unit)
+            .expect("Time went mackward");
+        if now_millis <= self.generator.last_time_millis && self.issued.len() > max_idx as usize {
+            now_millis = biding_time_conditions(
+                self.generator.last_time_millis,
+                time_unit,
+                self.generator.time_source,
+                self.generator.wait_strategy,
+            )
+            .expect("Time went mackward");
+        }
+
+        if now_millis > self.generator.last_time_millis {
+            self.generator.last_time_millis = now_millis;
+            self.issued.clear();
+        }
+
+        let idx = loop {
+            let idx = self.next_random() & max_idx;
+            if self.issued.insert(idx) {
+                break idx;
+            }
+        };
+        self.generator.idx = idx;
+
+        self.generator.layout.compose(
+            self.generator.last_time_millis,
+            self.generator.machine_bits,
+            idx,
+        )
+    }
+
+    /// The wrapped generator.
+    pub fn generator(&self) -> &SnowflakeIdGenerator {
+        &self.generator
+    }
+
+    fn next_random(&mut self) -> u16 {
+        if self.random_pos == RANDOM_BUFFER_LEN {
+            getrandom::getrandom(&mut self.random).expect("OS random source failed");
+            self.random_pos = 0;
+        }
+
+        let value = u16::from_le_bytes([
+            self.random[self.random_pos],
+            self.random[self.random_pos + 1],
+        ]);
+        self.random_pos += 2;
+        value
+    }
+}
+
+impl IdGenerator for PrivateIdGenerator {
+    fn next_id(&mut self) -> Result<SnowflakeId, SnowflakeError> {
+        Ok(SnowflakeId(self.generate()))
+    }
+}
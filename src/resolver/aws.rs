@@ -0,0 +1,123 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::{
+    cached, hash_machine_bits, InstanceKey, WorkerIdResolver, METADATA_ENDPOINT, METADATA_TIMEOUT,
+};
+use crate::{http, Layout};
+
+// Lifetime asked for the IMDSv2 session token, only one request is made.
+const TOKEN_TTL_SECONDS: &str = "60";
+
+/// The `AwsMetadataResolver` type derives machine bits from the EC2
+/// instance metadata service.
+///
+/// Reads the instance id or private IP through IMDSv2 and hashes it into
+/// the machine bits, for EC2 and ECS deployments without explicit worker
+/// ids. The value is fetched once and cached by the resolver.
+///
+/// Hashing can map two instances to the same bits, the odds grow with the
+/// size of the fleet relative to `2^machine_bits`.
+#[derive(Debug)]
+pub struct AwsMetadataResolver {
+    endpoint: SocketAddr,
+    key: InstanceKey,
+    timeout: Duration,
+    cached: Mutex<Option<String>>,
+}
+
+impl AwsMetadataResolver {
+    /// Constructs a new `AwsMetadataResolver` reading the instance id
+    /// from the metadata service at `169.254.169.254`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{AwsMetadataResolver, InstanceKey};
+    ///
+    /// let resolver = AwsMetadataResolver::new().with_key(InstanceKey::PrivateIp);
+    /// ```
+    pub fn new() -> AwsMetadataResolver {
+        AwsMetadataResolver {
+            endpoint: METADATA_ENDPOINT,
+            key: InstanceKey::InstanceId,
+            timeout: METADATA_TIMEOUT,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Query the metadata service at `endpoint` instead.
+    pub fn with_endpoint(mut self, endpoint: SocketAddr) -> AwsMetadataResolver {
+        self.endpoint = endpoint;
+        self
+    }
+
+    /// Hash `key` instead of the instance id.
+    pub fn with_key(mut self, key: InstanceKey) -> AwsMetadataResolver {
+        self.key = key;
+        self
+    }
+
+    /// Give up on each request after `timeout`, one second by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> AwsMetadataResolver {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The instance id or private IP of the instance.
+    ///
+    /// Fails with the error of the metadata service, e.g. outside of EC2.
+    /// Failures aren't cached, the next call asks again.
+    pub fn instance_key(&self) -> io::Result<String> {
+        cached(&self.cached, || self.fetch())
+    }
+
+    fn fetch(&self) -> io::Result<String> {
+        let token = http::request(
+            self.endpoint,
+            "PUT",
+            "/latest/api/token",
+            &[("X-aws-ec2-metadata-token-ttl-seconds", TOKEN_TTL_SECONDS)],
+            "",
+            self.timeout,
+        )?;
+        let path = match self.key {
+            InstanceKey::InstanceId => "/latest/meta-data/instance-id",
+            InstanceKey::PrivateIp => "/latest/meta-data/local-ipv4",
+        };
+
+        let value = http::request(
+            self.endpoint,
+            "GET",
+            path,
+            &[("X-aws-ec2-metadata-token", token.trim())],
+            "",
+            self.timeout,
+        )?;
+        Ok(value.trim().to_string())
+    }
+}
+
+impl WorkerIdResolver for AwsMetadataResolver {
+    /// The machine bits of `layout` derived from the instance.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use snowflake::{AwsMetadataResolver, Layout, SnowflakeIdGenerator, WorkerIdResolver};
+    ///
+    /// let machine_bits = AwsMetadataResolver::new().machine_bits(Layout::default()).unwrap();
+    /// let id_generator = SnowflakeIdGenerator::with_machine_id(machine_bits as u32).unwrap();
+    /// ```
+    fn machine_bits(&self, layout: Layout) -> io::Result<i64> {
+        Ok(hash_machine_bits(self.instance_key()?.as_bytes(), layout))
+    }
+}
+
+impl Default for AwsMetadataResolver {
+    fn default() -> AwsMetadataResolver {
+        AwsMetadataResolver::new()
+    }
+}
@@ -0,0 +1,95 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::{cached, hash_machine_bits, WorkerIdResolver, METADATA_ENDPOINT, METADATA_TIMEOUT};
+use crate::{http, Layout};
+
+/// The `AzureMetadataResolver` type derives machine bits from the Azure
+/// instance metadata service.
+///
+/// Reads the unique id of the VM and hashes it into the machine bits, for
+/// Azure VMs and scale sets without explicit worker ids. The value is
+/// fetched once and cached by the resolver.
+///
+/// Hashing can map two VMs to the same bits, the odds grow with the size of
+/// the fleet relative to `2^machine_bits`.
+#[derive(Debug)]
+pub struct AzureMetadataResolver {
+    endpoint: SocketAddr,
+    timeout: Duration,
+    cached: Mutex<Option<String>>,
+}
+
+impl AzureMetadataResolver {
+    /// Constructs a new `AzureMetadataResolver` reading the metadata
+    /// service at `169.254.169.254`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::AzureMetadataResolver;
+    ///
+    /// let resolver = AzureMetadataResolver::new();
+    /// ```
+    pub fn new() -> AzureMetadataResolver {
+        AzureMetadataResolver {
+            endpoint: METADATA_ENDPOINT,
+            timeout: METADATA_TIMEOUT,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Query the metadata service at `endpoint` instead.
+    pub fn with_endpoint(mut self, endpoint: SocketAddr) -> AzureMetadataResolver {
+        self.endpoint = endpoint;
+        self
+    }
+
+    /// Give up on the request after `timeout`, one second by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> AzureMetadataResolver {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The unique id of the VM.
+    ///
+    /// Fails with the error of the metadata service, e.g. outside of Azure.
+    /// Failures aren't cached, the next call asks again.
+    pub fn vm_id(&self) -> io::Result<String> {
+        cached(&self.cached, || {
+            let value = http::request(
+                self.endpoint,
+                "GET",
+                "/metadata/instance/compute/vmId?api-version=2021-02-01&format=text",
+                &[("Metadata", "true")],
+                "",
+                self.timeout,
+            )?;
+            Ok(value.trim().to_string())
+        })
+    }
+}
+
+impl WorkerIdResolver for AzureMetadataResolver {
+    /// The machine bits of `layout` derived from the VM id.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use snowflake::{AzureMetadataResolver, Layout, SnowflakeIdGenerator, WorkerIdResolver};
+    ///
+    /// let machine_bits = AzureMetadataResolver::new().machine_bits(Layout::default()).unwrap();
+    /// let id_generator = SnowflakeIdGenerator::with_machine_id(machine_bits as u32).unwrap();
+    /// ```
+    fn machine_bits(&self, layout: Layout) -> io::Result<i64> {
+        Ok(hash_machine_bits(self.vm_id()?.as_bytes(), layout))
+    }
+}
+
+impl Default for AzureMetadataResolver {
+    fn default() -> AzureMetadataResolver {
+        AzureMetadataResolver::new()
+    }
+}
@@ -0,0 +1,111 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::{hash_machine_bits, WorkerIdResolver};
+use crate::Layout;
+
+// Length of a Docker, containerd or CRI-O container id.
+const CONTAINER_ID_LEN: usize = 64;
+
+/// The `ContainerIdResolver` type derives machine bits from the id of the
+/// container the process runs in.
+///
+/// Sidecars and the main container of a pod share the pod IP, so
+/// `new_from_ip` gives them all the same machine bits. The container id is
+/// distinct per container: it is read from `/proc/self/cgroup`, or from
+/// `/proc/self/mountinfo` under cgroup v2 where the cgroup path is hidden,
+/// and hashed into the machine bits.
+///
+/// Hashing can map two containers to the same bits, the odds grow with the
+/// number of containers sharing an IP relative to `2^machine_bits`.
+#[derive(Clone, Debug)]
+pub struct ContainerIdResolver {
+    cgroup_path: PathBuf,
+    mountinfo_path: PathBuf,
+}
+
+impl ContainerIdResolver {
+    /// Constructs a new `ContainerIdResolver` reading the files of the
+    /// current process.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::ContainerIdResolver;
+    ///
+    /// let resolver = ContainerIdResolver::new();
+    /// ```
+    pub fn new() -> ContainerIdResolver {
+        ContainerIdResolver::with_paths("/proc/self/cgroup", "/proc/self/mountinfo")
+    }
+
+    /// Constructs a new `ContainerIdResolver` reading the cgroup and
+    /// mountinfo files at the given paths.
+    pub fn with_paths(
+        cgroup_path: impl Into<PathBuf>,
+        mountinfo_path: impl Into<PathBuf>,
+    ) -> ContainerIdResolver {
+        ContainerIdResolver {
+            cgroup_path: cgroup_path.into(),
+            mountinfo_path: mountinfo_path.into(),
+        }
+    }
+
+    /// The 64 hex digit id of the container.
+    ///
+    /// Fails with `io::ErrorKind::NotFound` if neither file names a
+    /// container, e.g. outside of one.
+    pub fn container_id(&self) -> io::Result<String> {
+        let cgroup = read_optional(&self.cgroup_path)?;
+        if let Some(id) = cgroup.lines().find_map(|line| find_container_id(line)) {
+            return Ok(id.to_string());
+        }
+
+        let mountinfo = read_optional(&self.mountinfo_path)?;
+        mountinfo
+            .lines()
+            .filter(|line| line.contains("/containers/"))
+            .find_map(|line| find_container_id(line))
+            .map(str::to_string)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no container id found"))
+    }
+}
+
+impl WorkerIdResolver for ContainerIdResolver {
+    /// The machine bits of `layout` derived from the container id.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use snowflake::{ContainerIdResolver, Layout, SnowflakeIdGenerator, WorkerIdResolver};
+    ///
+    /// let machine_bits = ContainerIdResolver::new().machine_bits(Layout::default()).unwrap();
+    /// let id_generator = SnowflakeIdGenerator::with_machine_id(machine_bits as u32).unwrap();
+    /// ```
+    fn machine_bits(&self, layout: Layout) -> io::Result<i64> {
+        Ok(hash_machine_bits(self.container_id()?.as_bytes(), layout))
+    }
+}
+
+impl Default for ContainerIdResolver {
+    fn default() -> ContainerIdResolver {
+        ContainerIdResolver::new()
+    }
+}
+
+// A missing file reads as empty, the other file may still have the id.
+fn read_optional(path: &Path) -> io::Result<String> {
+    match fs::read_to_string(path) {
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        result => result,
+    }
+}
+
+// The first run of exactly 64 hex digits in `line`, which covers
+// `/docker/<id>`, `docker-<id>.scope`, `cri-containerd-<id>.scope`,
+// `crio-<id>.scope` and `/docker/containers/<id>/hostname`.
+fn find_container_id(line: &str) -> Option<&str> {
+    line.split(|character: char| !character.is_ascii_hexdigit())
+        .find(|run| run.len() == CONTAINER_ID_LEN)
+}
@@ -0,0 +1,112 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::{
+    cached, hash_machine_bits, InstanceKey, WorkerIdResolver, METADATA_ENDPOINT, METADATA_TIMEOUT,
+};
+use crate::{http, Layout};
+
+/// The `GcpMetadataResolver` type derives machine bits from the GCE
+/// metadata server.
+///
+/// Reads the instance id or internal IP and hashes it into the machine
+/// bits, so GCE and GKE deployments get worker ids without configuration.
+/// The value is fetched once and cached by the resolver.
+///
+/// Hashing can map two instances to the same bits, the odds grow with the
+/// size of the fleet relative to `2^machine_bits`.
+#[derive(Debug)]
+pub struct GcpMetadataResolver {
+    endpoint: SocketAddr,
+    key: InstanceKey,
+    timeout: Duration,
+    cached: Mutex<Option<String>>,
+}
+
+impl GcpMetadataResolver {
+    /// Constructs a new `GcpMetadataResolver` reading the instance id
+    /// from the metadata server at `169.254.169.254`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{GcpMetadataResolver, InstanceKey};
+    ///
+    /// let resolver = GcpMetadataResolver::new().with_key(InstanceKey::PrivateIp);
+    /// ```
+    pub fn new() -> GcpMetadataResolver {
+        GcpMetadataResolver {
+            endpoint: METADATA_ENDPOINT,
+            key: InstanceKey::InstanceId,
+            timeout: METADATA_TIMEOUT,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Query the metadata server at `endpoint` instead.
+    pub fn with_endpoint(mut self, endpoint: SocketAddr) -> GcpMetadataResolver {
+        self.endpoint = endpoint;
+        self
+    }
+
+    /// Hash `key` instead of the instance id.
+    pub fn with_key(mut self, key: InstanceKey) -> GcpMetadataResolver {
+        self.key = key;
+        self
+    }
+
+    /// Give up on the request after `timeout`, one second by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> GcpMetadataResolver {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The instance id or internal IP of the instance.
+    ///
+    /// Fails with the error of the metadata server, e.g. outside of GCP.
+    /// Failures aren't cached, the next call asks again.
+    pub fn instance_key(&self) -> io::Result<String> {
+        cached(&self.cached, || self.fetch())
+    }
+
+    fn fetch(&self) -> io::Result<String> {
+        let path = match self.key {
+            InstanceKey::InstanceId => "/computeMetadata/v1/instance/id",
+            InstanceKey::PrivateIp => "/computeMetadata/v1/instance/network-interfaces/0/ip",
+        };
+
+        let value = http::request(
+            self.endpoint,
+            "GET",
+            path,
+            &[("Metadata-Flavor", "Google")],
+            "",
+            self.timeout,
+        )?;
+        Ok(value.trim().to_string())
+    }
+}
+
+impl WorkerIdResolver for GcpMetadataResolver {
+    /// The machine bits of `layout` derived from the instance.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use snowflake::{GcpMetadataResolver, Layout, SnowflakeIdGenerator, WorkerIdResolver};
+    ///
+    /// let machine_bits = GcpMetadataResolver::new().machine_bits(Layout::default()).unwrap();
+    /// let id_generator = SnowflakeIdGenerator::with_machine_id(machine_bits as u32).unwrap();
+    /// ```
+    fn machine_bits(&self, layout: Layout) -> io::Result<i64> {
+        Ok(hash_machine_bits(self.instance_key()?.as_bytes(), layout))
+    }
+}
+
+impl Default for GcpMetadataResolver {
+    fn default() -> GcpMetadataResolver {
+        GcpMetadataResolver::new()
+    }
+}
@@ -0,0 +1,101 @@
+//! Machine bits derived from the environment the process runs in.
+
+#[cfg(feature = "aws")]
+mod aws;
+#[cfg(feature = "azure")]
+mod azure;
+mod container;
+#[cfg(feature = "gcp")]
+mod gcp;
+
+#[cfg(feature = "aws")]
+pub use aws::AwsMetadataResolver;
+#[cfg(feature = "azure")]
+pub use azure::AzureMetadataResolver;
+pub use container::ContainerIdResolver;
+#[cfg(feature = "gcp")]
+pub use gcp::GcpMetadataResolver;
+
+use std::io;
+#[cfg(any(feature = "aws", feature = "azure", feature = "gcp"))]
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+#[cfg(any(feature = "aws", feature = "azure", feature = "gcp"))]
+use std::sync::Mutex;
+#[cfg(any(feature = "aws", feature = "azure", feature = "gcp"))]
+use std::time::Duration;
+
+use crate::{split_mix64, Layout};
+
+// The link-local address cloud providers serve instance metadata on.
+#[cfg(any(feature = "aws", feature = "azure", feature = "gcp"))]
+const METADATA_ENDPOINT: SocketAddr =
+    SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(169, 254, 169, 254), 80));
+
+// Metadata servers answer in milliseconds, anything slower means there
+// is none.
+#[cfg(any(feature = "aws", feature = "azure", feature = "gcp"))]
+const METADATA_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The `WorkerIdResolver` trait derives machine bits from the environment.
+///
+/// Implemented by every resolver, so the source can be picked at runtime.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::{ContainerIdResolver, Layout, WorkerIdResolver};
+///
+/// let resolvers: Vec<Box<dyn WorkerIdResolver>> = vec![Box::new(ContainerIdResolver::new())];
+/// let machine_bits = resolvers
+///     .iter()
+///     .find_map(|resolver| resolver.machine_bits(Layout::default()).ok())
+///     .unwrap_or(0);
+/// ```
+pub trait WorkerIdResolver {
+    /// The machine bits of `layout` for the current process.
+    fn machine_bits(&self, layout: Layout) -> io::Result<i64>;
+}
+
+/// The `InstanceKey` type is the value of the cloud instance hashed into
+/// the machine bits.
+#[cfg(any(feature = "aws", feature = "gcp"))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InstanceKey {
+    /// The id the provider assigned to the instance.
+    InstanceId,
+    /// The private IPv4 address of the instance.
+    PrivateIp,
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+// Hash `key` into the machine bits of `layout`.
+//
+// FNV-1a then the SplitMix64 output function, stable across releases and
+// platforms so every process derives the same bits from the same key.
+pub(crate) fn hash_machine_bits(key: &[u8], layout: Layout) -> i64 {
+    let hash = key.iter().fold(FNV_OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    });
+    let mask = layout.machine_mask() >> layout.machine_shift();
+
+    split_mix64(hash) as i64 & mask
+}
+
+// The value in `cache`, fetched on first use. Failures aren't cached, the
+// next call fetches again.
+#[cfg(any(feature = "aws", feature = "azure", feature = "gcp"))]
+pub(crate) fn cached(
+    cache: &Mutex<Option<String>>,
+    fetch: impl FnOnce() -> io::Result<String>,
+) -> io::Result<String> {
+    let mut cache = cache.lock().expect("Metadata cache lock poisoned");
+    if let Some(value) = cache.as_ref() {
+        return Ok(value.clone());
+    }
+
+    let value = fetch()?;
+    *cache = Some(value.clone());
+    Ok(value)
+}
@@ -0,0 +1,235 @@
+//! Plain sequences from segments of a store, Leaf's segment mode.
+
+use std::ops::Range;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+
+use crate::{GeneratorStats, IdGenerator, SegmentStore, SnowflakeError, SnowflakeId};
+
+/// The `SegmentGenerator` type hands out plain sequence ids from segments
+/// reserved in a `SegmentStore`, such as a SQL table or a Redis counter.
+///
+/// One store call reserves a segment of `segment_size` ids, so the store
+/// is hit once per segment rather than once per id. Segments are double
+/// buffered: once a tenth of the current segment is used, a background
+/// thread reserves the next one, and callers move on to it without waiting
+/// for the store. A store error only reaches callers once both segments
+/// are used up, until then the store has the rest of the segment to
+/// recover.
+///
+/// Ids increase within a segment, and across segments if the store hands
+/// them out in order. Clones share the segments.
+///
+/// # Examples
+///
+/// ```
+/// use std::ops::Range;
+///
+/// use snowflake::{SegmentGenerator, SegmentStore};
+///
+/// struct Sequence(i64);
+///
+/// impl SegmentStore for Sequence {
+///     type Error = ();
+///
+///     fn next_segment(&mut self, size: u64) -> Result<Range<i64>, ()> {
+///         let start = self.0;
+///         self.0 += size as i64;
+///         Ok(start..self.0)
+///     }
+/// }
+///
+/// let generator = SegmentGenerator::new(Sequence(1), 1000);
+///
+/// assert_eq!(generator.generate(), Ok(1));
+/// assert_eq!(generator.generate(), Ok(2));
+/// ```
+#[derive(Debug)]
+pub struct SegmentGenerator<S> {
+    shared: Arc<Shared<S>>,
+}
+
+#[derive(Debug)]
+struct Shared<S> {
+    store: Mutex<S>,
+    segment_size: u64,
+    segments: Mutex<Segments>,
+    loaded: Condvar,
+}
+
+#[derive(Debug)]
+struct Segments {
+    current: Range<i64>,
+    next: Option<Range<i64>>,
+    /// A store call is in flight.
+    loading: bool,
+    /// The prefetch for the current segment failed, the next segment is
+    /// fetched once the current one is used up.
+    prefetch_failed: bool,
+    issued: u64,
+}
+
+impl<S> SegmentGenerator<S>
+where
+    S: SegmentStore + Send + 'static,
+{
+    /// Constructs a new `SegmentGenerator`, reserving segments of
+    /// `segment_size` ids from `store`.
+    ///
+    /// The first segment is reserved by the first `generate`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `segment_size` is zero.
+    pub fn new(store: S, segment_size: u64) -> SegmentGenerator<S> {
+        assert!(segment_size > 0, "segment_size must be positive");
+
+        SegmentGenerator {
+            shared: Arc::new(Shared {
+                store: Mutex::new(store),
+                segment_size,
+                segments: Mutex::new(Segments {
+                    current: 0..0,
+                    next: None,
+                    loading: false,
+                    prefetch_failed: false,
+                    issued: 0,
+                }),
+                loaded: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Take the next id, calling the store if both segments are used up.
+    ///
+    /// # Errors
+    ///
+    /// Returns the store's error if the segment it was called for failed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the store hands out an empty segment.
+    pub fn generate(&self) -> Result<i64, S::Error> {
+        let mut segments = self.shared.lock();
+        loop {
+            if let Some(id) = segments.current.next() {
+                segments.issued += 1;
+                if self.shared.should_prefetch(&segments) {
+                    segments.loading = true;
+                    self.prefetch();
+                }
+                return Ok(id);
+            }
+
+            if let Some(next) = segments.next.take() {
+                segments.current = next;
+                segments.prefetch_failed = false;
+                continue;
+            }
+
+            if segments.loading {
+                segments = self
+                    .shared
+                    .loaded
+                    .wait(segments)
+                    .expect("Segment generator lock poisoned");
+                continue;
+            }
+
+            segments.loading = true;
+            drop(segments);
+            let fetched = self.shared.fetch();
+
+            segments = self.shared.lock();
+            segments.loading = false;
+            self.shared.loaded.notify_all();
+
+            let segment = fetched?;
+            assert!(
+                !segment.is_empty(),
+                "SegmentStore handed out an empty segment"
+            );
+            segments.current = segment;
+            segments.prefetch_failed = false;
+        }
+    }
+
+    /// A snapshot of the counters, `buffered` counts the ids left in both
+    /// segments.
+    pub fn stats(&self) -> GeneratorStats {
+        let segments = self.shared.lock();
+        let next = segments.next.as_ref().map_or(0, segment_len);
+
+        GeneratorStats {
+            issued: segments.issued,
+            buffered: segment_len(&segments.current) + next,
+            ..GeneratorStats::default()
+        }
+    }
+
+    // Reserve the next segment on a background thread.
+    fn prefetch(&self) {
+        let shared = Arc::clone(&self.shared);
+        thread::spawn(move || {
+            let fetched = shared.fetch();
+
+            let mut segments = shared.lock();
+            segments.loading = false;
+            match fetched {
+                Ok(segment) => segments.next = Some(segment),
+                Err(_) => segments.prefetch_failed = true,
+            }
+            shared.loaded.notify_all();
+        });
+    }
+}
+
+/// Generates with `SegmentGenerator::generate`, a failure of the store is
+/// `SnowflakeError::SegmentStoreFailed`.
+impl<S> IdGenerator for SegmentGenerator<S>
+where
+    S: SegmentStore + Send + 'static,
+{
+    fn next_id(&mut self) -> Result<SnowflakeId, SnowflakeError> {
+        self.generate()
+            .map(SnowflakeId)
+            .map_err(|_| SnowflakeError::SegmentStoreFailed)
+    }
+}
+
+impl<S> Clone for SegmentGenerator<S> {
+    fn clone(&self) -> SegmentGenerator<S> {
+        SegmentGenerator {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<S: SegmentStore> Shared<S> {
+    fn lock(&self) -> MutexGuard<'_, Segments> {
+        self.segments
+            .lock()
+            .expect("Segment generator lock poisoned")
+    }
+
+    fn fetch(&self) -> Result<Range<i64>, S::Error> {
+        self.store
+            .lock()
+            .expect("Segment store lock poisoned")
+            .next_segment(self.segment_size)
+    }
+
+    // Whether to reserve the next segment: a tenth of the current one is
+    // used, and no segment is waiting or on its way.
+    fn should_prefetch(&self, segments: &Segments) -> bool {
+        let remaining = segment_len(&segments.current) as u64;
+        segments.next.is_none()
+            && !segments.loading
+            && !segments.prefetch_failed
+            && remaining <= self.segment_size - self.segment_size / 10
+    }
+}
+
+fn segment_len(segment: &Range<i64>) -> usize {
+    (segment.end - segment.start).max(0) as usize
+}
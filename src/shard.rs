@@ -0,0 +1,160 @@
+//! Shards carried in the ids.
+
+use crate::{split_mix64, Layout, SnowflakeError, SnowflakeId, SnowflakeIdGenerator};
+
+/// The `ShardKey` type is the field of an id holding a shard.
+///
+/// Taken from a layout with `Layout::shard_key`, or placed anywhere with
+/// `ShardKey::new` for ids sharded by other means.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::{Layout, SnowflakeIdGenerator};
+///
+/// let layout = Layout::default().with_shard_bits(4);
+/// let mut id_generator = SnowflakeIdGenerator::new(0, 7).with_layout(layout);
+///
+/// let id = id_generator.generate_for_shard(11).unwrap();
+///
+/// assert_eq!(layout.shard_key().shard_of(id.0), 11);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ShardKey {
+    shift: u32,
+    bits: u32,
+}
+
+impl ShardKey {
+    /// Constructs a new `ShardKey` of `bits`, starting at bit `shift`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field goes past the sign bit.
+    pub const fn new(shift: u32, bits: u32) -> ShardKey {
+        assert!(shift + bits < 64, "shard key past the sign bit");
+        ShardKey { shift, bits }
+    }
+
+    /// Position of the lowest shard bit.
+    pub const fn shift(&self) -> u32 {
+        self.shift
+    }
+
+    /// Width of the shard field.
+    pub const fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Number of shards the field holds.
+    pub const fn shard_count(&self) -> u64 {
+        1 << self.bits
+    }
+
+    /// Mask selecting the shard bits of an id.
+    pub const fn mask(&self) -> i64 {
+        ((1 << self.bits) - 1) << self.shift
+    }
+
+    /// The shard of `id`.
+    pub const fn shard_of(&self, id: i64) -> u64 {
+        ((id & self.mask()) >> self.shift) as u64
+    }
+
+    /// The shard of `tenant_id`, its hash modulo the shard count.
+    ///
+    /// Hashing spreads sequential tenant ids over the shards. Routers use
+    /// it to find a tenant's shard without an id at hand.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::ShardKey;
+    ///
+    /// let shard_key = ShardKey::new(12, 4);
+    ///
+    /// assert!(shard_key.shard_for(42) < 16);
+    /// assert_eq!(shard_key.shard_for(42), shard_key.shard_for(42));
+    /// ```
+    pub fn shard_for(&self, tenant_id: u64) -> u64 {
+        split_mix64(tenant_id) % self.shard_count()
+    }
+
+    /// `id` moved to `shard`.
+    ///
+    /// Fails with `SnowflakeError::InvalidShard` if the shard doesn't fit
+    /// the field.
+    pub fn with_shard(&self, id: i64, shard: u64) -> Result<i64, SnowflakeError> {
+        if shard >= self.shard_count() {
+            return Err(SnowflakeError::InvalidShard(shard));
+        }
+
+        Ok(id & !self.mask() | (shard as i64) << self.shift)
+    }
+}
+
+impl Layout {
+    /// The shard field reserved by `with_shard_bits`, the highest machine
+    /// bits. Holds a single shard, 0, without shard bits.
+    pub const fn shard_key(&self) -> ShardKey {
+        ShardKey::new(
+            self.machine_shift() + self.machine_bits() - self.shard_bits(),
+            self.shard_bits(),
+        )
+    }
+}
+
+impl SnowflakeIdGenerator {
+    /// Generate an id for `shard`, in the shard bits of the layout, see
+    /// `Layout::with_shard_bits`.
+    ///
+    /// The ids of every shard share the auto-increment record, so they
+    /// stay unique and in time order.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `SnowflakeError::InvalidShard` if the shard doesn't fit
+    /// the shard bits, with `SnowflakeError::InvalidMachineId` if the
+    /// machine id of the generator reaches into the shard or tag bits, and
+    /// otherwise as `try_real_time_generate`.
+    pub fn generate_for_shard(&mut self, shard: u64) -> Result<SnowflakeId, SnowflakeError> {
+        let shard_key = self.layout.shard_key();
+        if shard >= shard_key.shard_count() {
+            return Err(SnowflakeError::InvalidShard(shard));
+        }
+        if self.machine_bits >> self.layout.host_bits() != 0 {
+            return Err(SnowflakeError::InvalidMachineId(self.machine_bits));
+        }
+
+        let id = self.try_real_time_generate()?;
+        shard_key.with_shard(id.0, shard).map(SnowflakeId)
+    }
+
+    /// Generate an id on the shard of `tenant_id`, see `ShardKey::shard_for`.
+    ///
+    /// The Instagram scheme: every row of a tenant lands on one shard, and
+    /// its ids still sort by time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{Layout, SnowflakeIdGenerator};
+    ///
+    /// let layout = Layout::default().with_shard_bits(4);
+    /// let mut id_generator = SnowflakeIdGenerator::new(0, 7).with_layout(layout);
+    ///
+    /// let first = id_generator.generate_sharded(42).unwrap();
+    /// let second = id_generator.generate_sharded(42).unwrap();
+    ///
+    /// assert!(first < second);
+    /// assert_eq!(layout.shard_key().shard_of(first.0), layout.shard_key().shard_for(42));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails as `generate_for_shard`.
+    pub fn generate_sharded(&mut self, tenant_id: u64) -> Result<SnowflakeId, SnowflakeError> {
+        let shard = self.layout.shard_key().shard_for(tenant_id);
+        self.generate_for_shard(shard)
+    }
+}
@@ -0,0 +1,60 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{GeneratorStats, IdGenerator, SnowflakeError, SnowflakeId, SnowflakeIdGenerator};
+
+/// A cloneable, thread-safe handle to a single generator.
+///
+/// Every clone draws from the same sequence, so ids stay unique across
+/// threads without each thread needing its own machine id.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::{SharedGenerator, SnowflakeIdGenerator};
+/// use std::thread;
+///
+/// let shared = SharedGenerator::new(SnowflakeIdGenerator::new(1, 1));
+/// let handle = {
+///     let shared = shared.clone();
+///     thread::spawn(move || shared.generate())
+/// };
+///
+/// assert_ne!(shared.generate(), handle.join().unwrap());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SharedGenerator {
+    inner: Arc<Mutex<SnowflakeIdGenerator>>,
+}
+
+impl SharedGenerator {
+    /// Wrap `generator` so it can be shared between threads.
+    pub fn new(generator: SnowflakeIdGenerator) -> SharedGenerator {
+        SharedGenerator {
+            inner: Arc::new(Mutex::new(generator)),
+        }
+    }
+
+    /// Generate an id with `real_time_generate`, holding the lock for the
+    /// duration of the call.
+    pub fn generate(&self) -> i64 {
+        self.with_generator(SnowflakeIdGenerator::real_time_generate)
+    }
+
+    /// A snapshot of the counters of the shared generator.
+    pub fn stats(&self) -> GeneratorStats {
+        self.with_generator(|generator| generator.stats())
+    }
+
+    // Run `f` with exclusive access to the underlying generator.
+    pub(crate) fn with_generator<R>(&self, f: impl FnOnce(&mut SnowflakeIdGenerator) -> R) -> R {
+        f(&mut self.inner.lock().expect("Shared generator lock poisoned"))
+    }
+}
+
+/// Generates with `try_real_time_generate`, holding the lock for the
+/// duration of the call.
+impl IdGenerator for SharedGenerator {
+    fn next_id(&mut self) -> Result<SnowflakeId, SnowflakeError> {
+        self.with_generator(SnowflakeIdGenerator::try_real_time_generate)
+    }
+}
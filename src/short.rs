@@ -0,0 +1,214 @@
+//! 32-bit ids for in-memory use.
+
+use crate::{
+    biding_time_conditions, signed_time_millis, try_get_time_millis, IdGenerator, Layout,
+    Snowflake, SnowflakeError, TimeSource, TimeUnit,
+};
+
+/// The `ShortIdGenerator` type generates 32-bit ids.
+///
+/// Ids hold the seconds since a custom epoch, machine bits and an
+/// auto-increment record, packed with a `Layout` of at most 32 bits, and
+/// sort by creation time like full size ids. With `Layout::SHORT` the clock
+/// overflows 194 days after the epoch, so keep the epoch recent, e.g. the
+/// start of the process.
+#[derive(Copy, Clone, Debug)]
+pub struct ShortIdGenerator {
+    /// epoch_seconds, Unix seconds the timestamps count from.
+    pub epoch_seconds: i64,
+
+    /// last_time_seconds, last time generate id is used, since the epoch.
+    pub last_time_seconds: i64,
+
+    pub machine_bits: i64,
+
+    /// auto-increment record.
+    pub idx: u16,
+
+    /// How the fields are packed into the id.
+    pub layout: Layout,
+}
+
+impl Layout {
+    /// A 32-bit layout for `ShortIdGenerator`.
+    ///
+    /// 24 bits of seconds, 2 machine bits and 6 bits of auto-increment
+    /// record: 4 machines issuing up to 64 ids per second each.
+    pub const SHORT: Layout = Layout::new(24, 2, 6);
+}
+
+impl ShortIdGenerator {
+    /// Constructs a new `ShortIdGenerator` counting seconds from `epoch_seconds`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the clock reads before the Unix epoch, see `try_new`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::ShortIdGenerator;
+    ///
+    /// let epoch_seconds = snowflake::get_time_millis() / 1000;
+    /// let id_generator = ShortIdGenerator::new(epoch_seconds, 1);
+    /// ```
+    pub fn new(epoch_seconds: i64, machine_bits: i64) -> ShortIdGenerator {
+        ShortIdGenerator::try_new(epoch_seconds, machine_bits)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `new`, but fails with `SnowflakeError::ClockBeforeEpoch` instead
+    /// of panicking if the clock reads before the Unix epoch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::ShortIdGenerator;
+    ///
+    /// let epoch_seconds = snowflake::get_time_millis() / 1000;
+    /// let mut id_generator = ShortIdGenerator::try_new(epoch_seconds, 1).unwrap();
+    ///
+    /// assert!(id_generator.try_generate().is_ok());
+    /// ```
+    pub fn try_new(
+        epoch_seconds: i64,
+        machine_bits: i64,
+    ) -> Result<ShortIdGenerator, SnowflakeError> {
+        Ok(ShortIdGenerator {
+            epoch_seconds,
+            last_time_seconds: try_get_time_millis()? / 1000 - epoch_seconds,
+            machine_bits,
+            idx: 0,
+            layout: Layout::SHORT,
+        })
+    }
+
+    /// Pack the ids with `layout` instead of `Layout::SHORT`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layout is wider than 32 bits.
+    pub fn with_layout(mut self, layout: Layout) -> ShortIdGenerator {
+        assert!(
+            layout.timestamp_bits() + layout.machine_bits() + layout.sequence_bits() <= 32,
+            "short id layouts must fit in 32 bits"
+        );
+
+        self.layout = layout;
+        self
+    }
+
+    /// Generate an id.
+    ///
+    /// Sleeps until the next second once the auto-increment record of the
+    /// current second is used up.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the clock is before the epoch or past the timestamp bits of
+    /// the layout, see `try_generate`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::ShortIdGenerator;
+    ///
+    /// let epoch_seconds = snowflake::get_time_millis() / 1000;
+    /// let mut id_generator = ShortIdGenerator::new(epoch_seconds, 1);
+    ///
+    /// let first = id_generator.generate();
+    /// let second = id_generator.generate();
+    ///
+    /// assert!(first < second);
+    /// ```
+    pub fn generate(&mut self) -> u32 {
+        self.try_generate()
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `generate`, but fails instead of issuing ids that wouldn't sort
+    /// by creation time.
+    ///
+    /// Fails with `SnowflakeError::EpochOverflow` if the seconds since the
+    /// epoch are negative or don't fit the timestamp bits of the layout,
+    /// and with `SnowflakeError::ClockBeforeEpoch` if the clock can't be
+    /// read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{ShortIdGenerator, SnowflakeError};
+    ///
+    /// // 2^24 seconds ago, the epoch of `Layout::SHORT` ran out.
+    /// let epoch_seconds = snowflake::get_time_millis() / 1000 - (1 << 24);
+    /// let mut id_generator = ShortIdGenerator::new(epoch_seconds, 1);
+    ///
+    /// assert!(matches!(
+    ///     id_generator.try_generate(),
+    ///     Err(SnowflakeError::EpochOverflow(_))
+    /// ));
+    /// ```
+    pub fn try_generate(&mut self) -> Result<u32, SnowflakeError> {
+        let now_seconds = self.now_seconds().ok_or_else(|| self.clock_lost())?;
+        self.layout.try_compose(now_seconds, self.machine_bits, 0)?;
+
+        if now_seconds > self.last_time_seconds {
+            self.last_time_seconds = now_seconds;
+            self.idx = 0;
+        } else if self.idx >= self.layout.max_idx() {
+            let now_seconds = biding_time_conditions(
+                self.epoch_seconds + self.last_time_seconds,
+                TimeUnit::Seconds,
+                TimeSource::System,
+                None,
+            )
+            .ok_or_else(|| self.clock_lost())?;
+
+            self.last_time_seconds = now_seconds - self.epoch_seconds;
+            self.idx = 0;
+        } else {
+            self.idx += 1;
+        }
+
+        self.layout
+            .try_compose(self.last_time_seconds, self.machine_bits, self.idx)
+            .map(|id| id as u32)
+    }
+
+    /// Split an id into its fields, the timestamp in Unix seconds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::ShortIdGenerator;
+    ///
+    /// let epoch_seconds = snowflake::get_time_millis() / 1000;
+    /// let mut id_generator = ShortIdGenerator::new(epoch_seconds, 1);
+    /// let id = id_generator.generate();
+    ///
+    /// let snowflake = id_generator.decode(id);
+    ///
+    /// assert_eq!(snowflake.machine_bits, 1);
+    /// assert_eq!(snowflake.timestamp, epoch_seconds + id_generator.last_time_seconds);
+    /// ```
+    pub fn decode(&self, id: u32) -> Snowflake {
+        let mut snowflake = self.layout.decode(i64::from(id));
+        snowflake.timestamp += self.epoch_seconds;
+        snowflake
+    }
+
+    #[inline(always)]
+    fn now_seconds(&self) -> Option<i64> {
+        Some(TimeUnit::Seconds.try_now()? - self.epoch_seconds)
+    }
+
+    fn clock_lost(&self) -> SnowflakeError {
+        SnowflakeError::ClockBeforeEpoch(signed_time_millis())
+    }
+}
+
+impl IdGenerator<u32> for ShortIdGenerator {
+    fn next_id(&mut self) -> Result<u32, SnowflakeError> {
+        self.try_generate()
+    }
+}
@@ -0,0 +1,188 @@
+//! 128-bit snowflakes.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{try_get_time_nanos, IdGenerator, SnowflakeError};
+
+/// The `Snowflake128` type is a 128-bit id.
+///
+/// 64 bits of nanoseconds since the Unix epoch, 32 machine bits and 32 bits
+/// of auto-increment record, in that order. Ordered by creation time like
+/// the 64-bit ids.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Snowflake128 {
+    pub timestamp_nanos: u64,
+    pub machine_id: u32,
+    pub sequence: u32,
+}
+
+/// The `Snowflake128Generator` type generates `Snowflake128` ids.
+#[derive(Copy, Clone, Debug)]
+pub struct Snowflake128Generator {
+    /// last_time_nanos, last time generate id is used times nanos.
+    pub last_time_nanos: u64,
+
+    pub machine_id: u32,
+
+    /// auto-increment record.
+    pub sequence: u32,
+}
+
+/// The `ParseSnowflake128Error` type is returned for malformed strings.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParseSnowflake128Error;
+
+impl Snowflake128 {
+    /// The id as a single integer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Snowflake128;
+    ///
+    /// let id = Snowflake128 { timestamp_nanos: 1, machine_id: 2, sequence: 3 };
+    ///
+    /// assert_eq!(id.to_u128(), 1 << 64 | 2 << 32 | 3);
+    /// assert_eq!(Snowflake128::from_u128(id.to_u128()), id);
+    /// ```
+    pub fn to_u128(self) -> u128 {
+        u128::from(self.timestamp_nanos) << 64
+            | u128::from(self.machine_id) << 32
+            | u128::from(self.sequence)
+    }
+
+    /// Split an integer into the id fields.
+    pub fn from_u128(value: u128) -> Snowflake128 {
+        Snowflake128 {
+            timestamp_nanos: (value >> 64) as u64,
+            machine_id: (value >> 32) as u32,
+            sequence: value as u32,
+        }
+    }
+
+    /// The id as 16 big-endian bytes, which sort like the ids.
+    pub fn to_bytes(self) -> [u8; 16] {
+        self.to_u128().to_be_bytes()
+    }
+
+    /// Read an id from 16 big-endian bytes.
+    pub fn from_bytes(bytes: [u8; 16]) -> Snowflake128 {
+        Snowflake128::from_u128(u128::from_be_bytes(bytes))
+    }
+}
+
+impl fmt::Display for Snowflake128 {
+    /// Formats the id as 32 lowercase hex digits, which sort like the ids.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:032x}", self.to_u128())
+    }
+}
+
+impl FromStr for Snowflake128 {
+    type Err = ParseSnowflake128Error;
+
+    /// Parses the 32 hex digits written by `Display`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Snowflake128;
+    ///
+    /// let id = Snowflake128 { timestamp_nanos: 1, machine_id: 2, sequence: 3 };
+    ///
+    /// assert_eq!(id.to_string().parse::<Snowflake128>(), Ok(id));
+    /// ```
+    fn from_str(s: &str) -> Result<Snowflake128, ParseSnowflake128Error> {
+        if s.len() != 32 || !s.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(ParseSnowflake128Error);
+        }
+
+        u128::from_str_radix(s, 16)
+            .map(Snowflake128::from_u128)
+            .map_err(|_| ParseSnowflake128Error)
+    }
+}
+
+impl fmt::Display for ParseSnowflake128Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid 128-bit snowflake string")
+    }
+}
+
+impl std::error::Error for ParseSnowflake128Error {}
+
+impl Snowflake128Generator {
+    /// Constructs a new `Snowflake128Generator`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Snowflake128Generator;
+    ///
+    /// let id_generator = Snowflake128Generator::new(42);
+    /// ```
+    pub fn new(machine_id: u32) -> Snowflake128Generator {
+        Snowflake128Generator {
+            last_time_nanos: try_get_time_nanos().unwrap_or(0),
+            machine_id,
+            sequence: 0,
+        }
+    }
+
+    /// Generate an id.
+    ///
+    /// The auto-increment record restarts every nanosecond. If the clock
+    /// doesn't move forward the record keeps counting, and once it wraps
+    /// around the timestamp moves forward by one nanosecond, so ids always
+    /// increase.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the clock reads before the Unix epoch, see `try_generate`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::Snowflake128Generator;
+    ///
+    /// let mut id_generator = Snowflake128Generator::new(42);
+    ///
+    /// let first = id_generator.generate();
+    /// let second = id_generator.generate();
+    ///
+    /// assert!(first < second);
+    /// ```
+    pub fn generate(&mut self) -> Snowflake128 {
+        self.try_generate()
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `generate`, but fails with `SnowflakeError::ClockBeforeEpoch`
+    /// instead of panicking if the clock reads before the Unix epoch.
+    pub fn try_generate(&mut self) -> Result<Snowflake128, SnowflakeError> {
+        let now_nanos = try_get_time_nanos()?;
+
+        if now_nanos > self.last_time_nanos {
+            self.last_time_nanos = now_nanos;
+            self.sequence = 0;
+        } else if self.sequence == u32::MAX {
+            self.last_time_nanos += 1;
+            self.sequence = 0;
+        } else {
+            self.sequence += 1;
+        }
+
+        Ok(Snowflake128 {
+            timestamp_nanos: self.last_time_nanos,
+            machine_id: self.machine_id,
+            sequence: self.sequence,
+        })
+    }
+}
+
+impl IdGenerator<Snowflake128> for Snowflake128Generator {
+    fn next_id(&mut self) -> Result<Snowflake128, SnowflakeError> {
+        self.try_generate()
+    }
+}
@@ -0,0 +1,34 @@
+/// The `GeneratorStats` type is a snapshot of the counters of a generator.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::SnowflakeIdGenerator;
+///
+/// let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+/// for _ in 0..10 {
+///     id_generator.real_time_generate();
+/// }
+///
+/// let stats = id_generator.stats();
+///
+/// assert_eq!(stats.issued, 10);
+/// assert_eq!(stats.buffered, 0);
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct GeneratorStats {
+    /// Ids issued.
+    pub issued: u64,
+
+    /// Times the auto-increment record ran out and the generator waited
+    /// for the next time unit.
+    pub exhaustion_waits: u64,
+
+    /// Times the clock read earlier than the last id issued, or couldn't
+    /// be read at all.
+    pub clock_anomalies: u64,
+
+    /// Ids generated ahead of time and waiting in a buffer, zero for
+    /// generators without one.
+    pub buffered: usize,
+}
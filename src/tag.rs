@@ -0,0 +1,63 @@
+//! Application-defined tags carried in the ids.
+
+use crate::{Layout, SnowflakeError, SnowflakeId, SnowflakeIdGenerator};
+
+impl Layout {
+    /// Position of the lowest tag bit.
+    pub const fn tag_shift(&self) -> u32 {
+        self.machine_shift() + self.host_bits()
+    }
+
+    /// Mask selecting the tag bits of an id.
+    pub const fn tag_mask(&self) -> i64 {
+        ((1 << self.tag_bits()) - 1) << self.tag_shift()
+    }
+
+    /// The tag of `id`, set by `SnowflakeIdGenerator::generate_tagged`.
+    ///
+    /// Always 0 for layouts without tag bits.
+    pub const fn tag_of(&self, id: i64) -> u64 {
+        ((id & self.tag_mask()) >> self.tag_shift()) as u64
+    }
+}
+
+impl SnowflakeIdGenerator {
+    /// Generate an id carrying `tag` in the tag bits of the layout, see
+    /// `Layout::with_tag_bits`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{Layout, SnowflakeIdGenerator};
+    ///
+    /// const ORDER: u64 = 2;
+    ///
+    /// let layout = Layout::default().with_tag_bits(3);
+    /// let mut id_generator = SnowflakeIdGenerator::new(0, 7).with_layout(layout);
+    ///
+    /// let id = id_generator.generate_tagged(ORDER).unwrap();
+    ///
+    /// assert_eq!(layout.tag_of(id.0), ORDER);
+    /// assert_eq!(layout.decode(id.0).machine_bits & 0x7F, 7);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails with `SnowflakeError::InvalidTag` if the tag doesn't fit the
+    /// tag bits, with `SnowflakeError::InvalidMachineId` if the machine id
+    /// of the generator reaches into the shard or tag bits, and otherwise
+    /// as `try_real_time_generate`.
+    pub fn generate_tagged(&mut self, tag: u64) -> Result<SnowflakeId, SnowflakeError> {
+        if tag >> self.layout.tag_bits() != 0 {
+            return Err(SnowflakeError::InvalidTag(tag));
+        }
+        if self.machine_bits >> self.layout.host_bits() != 0 {
+            return Err(SnowflakeError::InvalidMachineId(self.machine_bits));
+        }
+
+        let id = self.try_real_time_generate()?;
+        Ok(SnowflakeId(
+            id.0 & !self.layout.tag_mask() | (tag as i64) << self.layout.tag_shift(),
+        ))
+    }
+}
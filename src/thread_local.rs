@@ -0,0 +1,132 @@
+//! Per-thread generators.
+//!
+//! Every thread lazily gets its own `SnowflakeIdGenerator`, so generating
+//! never touches a lock. The thread's index is folded into the thread bits
+//! of the layout, which keeps ids from different threads apart.
+//!
+//! The default layout reserves `THREAD_INDEX_BITS`, 16 threads running at
+//! once. The index of a thread that exits goes to the next new thread,
+//! which continues from the last id of the exited one. Larger thread pools
+//! need more thread bits, taken from the machine bits that tell hosts
+//! apart, see `Layout::with_thread_bits`.
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicI64, AtomicU32, Ordering};
+use std::sync::Mutex;
+
+use crate::{Layout, SnowflakeIdGenerator};
+
+/// Number of low machine bits taken by the thread index in the default
+/// layout of the per-thread generators.
+pub const THREAD_INDEX_BITS: u32 = 4;
+
+static BASE_MACHINE_BITS: AtomicI64 = AtomicI64::new(0);
+
+static LAYOUT: Mutex<Layout> = Mutex::new(Layout::DEFAULT.with_thread_bits(THREAD_INDEX_BITS));
+
+static NEXT_THREAD_INDEX: AtomicU32 = AtomicU32::new(0);
+
+// Thread indexes of exited threads and their last generator, free again.
+static RETIRED: Mutex<Vec<(u32, SnowflakeIdGenerator)>> = Mutex::new(Vec::new());
+
+thread_local! {
+    static GENERATOR: RefCell<ThreadGenerator> = RefCell::new(thread_generator());
+}
+
+// The generator of a thread, retired when the thread exits.
+struct ThreadGenerator {
+    generator: SnowflakeIdGenerator,
+    thread_index: u32,
+}
+
+/// Set the machine bits the per-thread generators are built from.
+///
+/// The thread bits of the layout are replaced by the thread index.
+/// Threads that already generated an id keep their generator,
+/// so call it once at startup.
+///
+/// # Examples
+///
+/// ```
+/// snowflake::set_thread_local_machine_bits(0x2A0);
+/// ```
+pub fn set_thread_local_machine_bits(machine_bits: i64) {
+    BASE_MACHINE_BITS.store(machine_bits, Ordering::Relaxed);
+}
+
+/// Set the layout of the per-thread generators, and with it how many
+/// threads they can tell apart.
+///
+/// Threads that already generated an id keep their generator,
+/// so call it once at startup.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::Layout;
+///
+/// // 16 hosts of up to 64 threads.
+/// snowflake::set_thread_local_layout(Layout::default().with_thread_bits(6));
+/// ```
+pub fn set_thread_local_layout(layout: Layout) {
+    *LAYOUT.lock().expect("Layout lock poisoned") = layout;
+}
+
+/// Generate an id with the generator of the current thread.
+///
+/// # Examples
+///
+/// ```
+/// let id = snowflake::thread_local_generate();
+/// ```
+///
+/// # Panics
+///
+/// Panics on a thread that generates for the first time while every thread
+/// index of the layout is taken by a running thread, as ids would no longer
+/// be unique.
+pub fn thread_local_generate() -> i64 {
+    GENERATOR.with(|generator| generator.borrow_mut().generator.real_time_generate())
+}
+
+fn thread_generator() -> ThreadGenerator {
+    let layout = *LAYOUT.lock().expect("Layout lock poisoned");
+    let retired = RETIRED.lock().expect("Retired generators lock poisoned").pop();
+    let thread_index = retired.map_or_else(
+        || NEXT_THREAD_INDEX.fetch_add(1, Ordering::Relaxed),
+        |(thread_index, _)| thread_index,
+    );
+
+    let mut generator =
+        SnowflakeIdGenerator::from_machine_bits(BASE_MACHINE_BITS.load(Ordering::Relaxed))
+            .with_layout(layout)
+            .with_thread_index(thread_index)
+            .unwrap_or_else(|_| {
+                panic!(
+                    "{} thread bits can't cover thread {}",
+                    layout.thread_bits(),
+                    thread_index
+                )
+            });
+
+    // Continue after the last id of the exited thread, which may have been
+    // issued in the current time unit.
+    if let Some((_, retired)) = retired.filter(|(_, retired)| retired.layout == layout) {
+        generator.last_time_millis = retired.last_time_millis;
+        generator.idx = retired.idx;
+        generator.first_idx = retired.first_idx;
+    }
+
+    ThreadGenerator {
+        generator,
+        thread_index,
+    }
+}
+
+impl Drop for ThreadGenerator {
+    fn drop(&mut self) {
+        if let Ok(mut retired) = RETIRED.lock() {
+            retired.push((self.thread_index, self.generator));
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Rate-limited generation.
+
+use crate::{IdGenerator, SnowflakeError, SnowflakeId, SnowflakeIdGenerator, TimeUnit};
+
+/// The `ThrottledGenerator` type caps the ids issued per second.
+///
+/// A token bucket holding up to one second worth of ids refills
+/// continuously, a client looping on id creation runs dry instead of
+/// burning through the sequence space. The bucket follows the time source
+/// of the wrapped generator.
+#[derive(Copy, Clone, Debug)]
+pub struct ThrottledGenerator {
+    generator: SnowflakeIdGenerator,
+
+    /// ids allowed per second, also the size of the bucket.
+    ids_per_second: u64,
+
+    /// Tokens left, in thousandths of an id.
+    milli_tokens: u64,
+
+    /// last_refill_millis, last time the bucket was refilled.
+    last_refill_millis: i64,
+}
+
+impl ThrottledGenerator {
+    /// Constructs a new `ThrottledGenerator` issuing at most `ids_per_second`.
+    ///
+    /// The bucket starts full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ids_per_second` is over `u64::MAX / 1000`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{SnowflakeIdGenerator, ThrottledGenerator};
+    ///
+    /// let id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+    /// let throttled = ThrottledGenerator::new(id_generator, 1000);
+    /// ```
+    pub fn new(generator: SnowflakeIdGenerator, ids_per_second: u64) -> ThrottledGenerator {
+        let milli_tokens = ids_per_second
+            .checked_mul(1000)
+            .expect("ids_per_second overflows the bucket");
+        let now_millis = generator.time_source.try_now(TimeUnit::Milliseconds);
+
+        ThrottledGenerator {
+            last_refill_millis: now_millis.unwrap_or(0),
+            generator,
+            ids_per_second,
+            milli_tokens,
+        }
+    }
+
+    /// Generate an id, `None` if the rate is used up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{SnowflakeIdGenerator, ThrottledGenerator};
+    ///
+    /// let id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+    /// let mut throttled = ThrottledGenerator::new(id_generator, 1);
+    ///
+    /// assert!(throttled.generate().is_some());
+    /// assert!(throttled.generate().is_none());
+    /// ```
+    pub fn generate(&mut self) -> Option<i64> {
+        self.refill();
+
+        if self.milli_tokens < 1000 {
+            return None;
+        }
+
+        self.milli_tokens -= 1000;
+        Some(self.generator.real_time_generate())
+    }
+
+    /// The wrapped generator.
+    pub fn generator(&self) -> &SnowflakeIdGenerator {
+        &self.generator
+    }
+
+    fn refill(&mut self) {
+        let now_millis = match self.generator.time_source.try_now(TimeUnit::Milliseconds) {
+            Some(now_millis) => now_millis,
+            None => return,
+        };
+        let elapsed = (now_millis - self.last_refill_millis).max(0) as u64;
+
+        if elapsed > 0 {
+            let refilled = elapsed.saturating_mul(self.ids_per_second);
+            self.milli_tokens = self
+                .milli_tokens
+                .saturating_add(refilled)
+                .min(self.ids_per_second * 1000);
+            self.last_refill_millis = now_millis;
+        }
+    }
+}
+
+/// Fails with `SnowflakeError::RateLimited` once the rate is used up.
+impl IdGenerator for ThrottledGenerator {
+    fn next_id(&mut self) -> Result<SnowflakeId, SnowflakeError> {
+        self.generate().map(SnowflakeId).ok_or(SnowflakeError::RateLimited)
+    }
+}
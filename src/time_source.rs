@@ -0,0 +1,106 @@
+//! Sources of the current time.
+
+use std::ptr;
+use std::sync::atomic::{AtomicI64, Ordering};
+#[cfg(feature = "quanta")]
+use std::sync::OnceLock;
+#[cfg(feature = "quanta")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::TimeUnit;
+
+/// The `TimeSource` type is where a generator reads the time from.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::{TimeSource, TimeUnit};
+///
+/// let now_millis = TimeSource::System.try_now(TimeUnit::Milliseconds).unwrap();
+///
+/// assert!(now_millis <= snowflake::get_time_millis());
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub enum TimeSource {
+    /// `SystemTime::now()`, following every change of the wall clock.
+    #[default]
+    System,
+    /// The calibrated TSC clock of `quanta`, anchored to the wall clock on
+    /// first use.
+    ///
+    /// Reads skip the system call of `SystemTime::now()`, and with a
+    /// `quanta::Upkeep` thread running they are a single atomic load of the
+    /// cached time, as fresh as the upkeep interval. Being monotonic, the
+    /// source doesn't follow steps of the wall clock made after the anchor.
+    #[cfg(feature = "quanta")]
+    Quanta,
+    /// A clock set by hand, in milliseconds since the Unix epoch, to test
+    /// code depending on the time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicI64, Ordering};
+    ///
+    /// use snowflake::{TimeSource, TimeUnit};
+    ///
+    /// static CLOCK: AtomicI64 = AtomicI64::new(1_700_000_000_000);
+    ///
+    /// let time_source = TimeSource::Manual(&CLOCK);
+    /// CLOCK.fetch_add(1500, Ordering::SeqCst);
+    ///
+    /// assert_eq!(time_source.try_now(TimeUnit::Seconds), Some(1_700_000_001));
+    /// ```
+    Manual(&'static AtomicI64),
+}
+
+impl TimeSource {
+    /// The time since the Unix epoch, in `time_unit`, `None` if the clock
+    /// can't be read, or reads before the Unix epoch.
+    #[inline(always)]
+    pub fn try_now(self, time_unit: TimeUnit) -> Option<i64> {
+        match self {
+            TimeSource::System => time_unit.try_now(),
+            #[cfg(feature = "quanta")]
+            TimeSource::Quanta => Some(time_unit.units_of(quanta_since_epoch()?)),
+            TimeSource::Manual(clock) => {
+                let millis = clock.load(Ordering::SeqCst);
+                (millis >= 0).then(|| time_unit.from_millis(millis))
+            }
+        }
+    }
+}
+
+/// Manual clocks are equal if they are the same clock.
+impl PartialEq for TimeSource {
+    fn eq(&self, other: &TimeSource) -> bool {
+        match (self, other) {
+            (TimeSource::System, TimeSource::System) => true,
+            #[cfg(feature = "quanta")]
+            (TimeSource::Quanta, TimeSource::Quanta) => true,
+            (TimeSource::Manual(clock), TimeSource::Manual(other)) => ptr::eq(*clock, *other),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for TimeSource {}
+
+#[cfg(feature = "quanta")]
+// The quanta clock and the wall clock read at the same time.
+static QUANTA_ANCHOR: OnceLock<(quanta::Instant, Duration)> = OnceLock::new();
+
+#[cfg(feature = "quanta")]
+#[inline(always)]
+// Time since the Unix epoch by the quanta clock, anchoring it first.
+fn quanta_since_epoch() -> Option<Duration> {
+    let (instant, since_epoch) = match QUANTA_ANCHOR.get() {
+        Some(anchor) => *anchor,
+        None => {
+            let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+            *QUANTA_ANCHOR.get_or_init(|| (quanta::Instant::now(), since_epoch))
+        }
+    };
+
+    Some(since_epoch + quanta::Instant::recent().saturating_duration_since(instant))
+}
@@ -0,0 +1,73 @@
+//! Date-time types accepted by the timestamp based APIs.
+//!
+//! `std::time::SystemTime` always works, `chrono` and `time` types are
+//! supported behind the features of the same name.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A point in time that converts to Unix milliseconds.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// use snowflake::UnixMillis;
+///
+/// let timestamp = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+///
+/// assert_eq!(timestamp.unix_millis(), 1_700_000_000_000);
+/// ```
+pub trait UnixMillis {
+    /// Milliseconds since the Unix epoch, negative before it.
+    fn unix_millis(&self) -> i64;
+}
+
+/// A signed span of time that converts to milliseconds.
+pub trait DurationMillis {
+    /// The span in whole milliseconds.
+    fn duration_millis(&self) -> i64;
+}
+
+impl UnixMillis for SystemTime {
+    fn unix_millis(&self) -> i64 {
+        match self.duration_since(UNIX_EPOCH) {
+            Ok(elapsed) => elapsed.as_millis() as i64,
+            Err(before) => -(before.duration().as_millis() as i64),
+        }
+    }
+}
+
+impl DurationMillis for Duration {
+    fn duration_millis(&self) -> i64 {
+        self.as_millis() as i64
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> UnixMillis for chrono::DateTime<Tz> {
+    fn unix_millis(&self) -> i64 {
+        self.timestamp_millis()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl DurationMillis for chrono::Duration {
+    fn duration_millis(&self) -> i64 {
+        self.num_milliseconds()
+    }
+}
+
+#[cfg(feature = "time")]
+impl UnixMillis for time::OffsetDateTime {
+    fn unix_millis(&self) -> i64 {
+        (self.unix_timestamp_nanos() / 1_000_000) as i64
+    }
+}
+
+#[cfg(feature = "time")]
+impl DurationMillis for time::Duration {
+    fn duration_millis(&self) -> i64 {
+        self.whole_milliseconds() as i64
+    }
+}
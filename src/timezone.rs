@@ -0,0 +1,38 @@
+//! Creation times in local time zones.
+
+use chrono::{DateTime, TimeZone};
+use chrono_tz::Tz;
+
+use crate::Snowflake;
+
+impl Snowflake {
+    /// The creation time of the id in the time zone `tz`, such as the zone
+    /// of the user who reported it.
+    ///
+    /// The timestamp is read in milliseconds since the Unix epoch, like
+    /// `Layout::default()` decodes it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the timestamp is out of the range of `DateTime`, some
+    /// 262,000 years either way, only reachable by layouts with era bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono_tz::Asia::Tokyo;
+    /// use snowflake::Layout;
+    ///
+    /// let snowflake = Layout::default().decode(1_717_243_200_123 << 22 | 0x2A7 << 12 | 17);
+    ///
+    /// assert_eq!(
+    ///     snowflake.datetime_in(Tokyo).to_rfc3339(),
+    ///     "2024-06-01T21:00:00.123+09:00"
+    /// );
+    /// ```
+    pub fn datetime_in(&self, tz: Tz) -> DateTime<Tz> {
+        tz.timestamp_millis_opt(self.timestamp)
+            .single()
+            .expect("Snowflake timestamp out of range")
+    }
+}
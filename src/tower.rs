@@ -0,0 +1,100 @@
+//! Request-id layer for tower services, such as axum routers.
+//!
+//! ```
+//! use snowflake::tower::RequestIdLayer;
+//! use snowflake::{AsyncGenerator, SnowflakeIdGenerator};
+//! use tower::ServiceBuilder;
+//!
+//! # let service = tower::service_fn(|_: http::Request<()>| async {
+//! #     Ok::<_, std::convert::Infallible>(http::Response::new(()))
+//! # });
+//! let generator = AsyncGenerator::new(SnowflakeIdGenerator::new(1, 1));
+//! let service = ServiceBuilder::new()
+//!     .layer(RequestIdLayer::new(generator))
+//!     .service(service);
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use http::header::{HeaderName, HeaderValue};
+use http::{Request, Response};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{AsyncGenerator, SnowflakeId};
+
+/// Name of the response header carrying the request id.
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// The id assigned to the current request, stored in the request
+/// extensions by `RequestIdLayer`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RequestId(pub SnowflakeId);
+
+/// Layer stamping every request with a snowflake id.
+///
+/// The id is inserted into the request extensions as a `RequestId` and
+/// echoed back in the `X-Request-Id` response header.
+#[derive(Clone, Debug)]
+pub struct RequestIdLayer {
+    generator: AsyncGenerator,
+}
+
+impl RequestIdLayer {
+    pub fn new(generator: AsyncGenerator) -> RequestIdLayer {
+        RequestIdLayer { generator }
+    }
+}
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestIdService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdService {
+            inner,
+            generator: self.generator.clone(),
+        }
+    }
+}
+
+/// The service produced by `RequestIdLayer`.
+#[derive(Clone, Debug)]
+pub struct RequestIdService<S> {
+    inner: S,
+    generator: AsyncGenerator,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for RequestIdService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    ReqBody: Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        // Keep the service that was driven to readiness for this call.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let generator = self.generator.clone();
+
+        Box::pin(async move {
+            let id = SnowflakeId(generator.generate().await);
+            req.extensions_mut().insert(RequestId(id));
+
+            let mut res = inner.call(req).await?;
+            res.headers_mut()
+                .insert(REQUEST_ID_HEADER, HeaderValue::from(id.0));
+            Ok(res)
+        })
+    }
+}
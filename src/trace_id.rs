@@ -0,0 +1,80 @@
+//! OpenTelemetry compatible trace and span ids.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{split_mix64, Snowflake, SnowflakeIdGenerator, SPLIT_MIX64_GAMMA};
+
+/// The `TraceIdGenerator` type emits W3C / OpenTelemetry trace and span ids
+/// carrying a snowflake.
+///
+/// A span id is the 8 big-endian bytes of a snowflake. A trace id is a
+/// snowflake in its 8 high bytes followed by 8 random bytes, which keeps
+/// the right-most bytes random for ratio based samplers while trace ids
+/// still sort and decode by time. Both are never all zeroes, as the
+/// specification requires.
+#[derive(Copy, Clone, Debug)]
+pub struct TraceIdGenerator {
+    generator: SnowflakeIdGenerator,
+    rng: u64,
+}
+
+impl TraceIdGenerator {
+    /// Constructs a new `TraceIdGenerator` on top of `generator`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::{SnowflakeIdGenerator, TraceIdGenerator};
+    ///
+    /// let mut trace_ids = TraceIdGenerator::new(SnowflakeIdGenerator::new(1, 1));
+    ///
+    /// let trace_id = trace_ids.trace_id();
+    /// let span_id = trace_ids.span_id();
+    ///
+    /// assert!(trace_ids.decode_trace_id(trace_id).timestamp
+    ///     <= trace_ids.decode_span_id(span_id).timestamp);
+    /// ```
+    pub fn new(generator: SnowflakeIdGenerator) -> TraceIdGenerator {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went mackward")
+            .as_nanos() as u64;
+
+        TraceIdGenerator {
+            generator,
+            rng: nanos ^ (generator.machine_bits as u64) << 32,
+        }
+    }
+
+    /// Generate a 16 byte trace id.
+    pub fn trace_id(&mut self) -> [u8; 16] {
+        self.rng = self.rng.wrapping_add(SPLIT_MIX64_GAMMA);
+
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&self.span_id());
+        bytes[8..].copy_from_slice(&split_mix64(self.rng).to_be_bytes());
+        bytes
+    }
+
+    /// Generate an 8 byte span id.
+    pub fn span_id(&mut self) -> [u8; 8] {
+        self.generator.real_time_generate().to_be_bytes()
+    }
+
+    /// Decode the snowflake embedded in a trace id.
+    pub fn decode_trace_id(&self, trace_id: [u8; 16]) -> Snowflake {
+        let mut high = [0; 8];
+        high.copy_from_slice(&trace_id[..8]);
+        self.decode_span_id(high)
+    }
+
+    /// Decode the snowflake a span id is made of.
+    pub fn decode_span_id(&self, span_id: [u8; 8]) -> Snowflake {
+        self.generator.layout.decode(i64::from_be_bytes(span_id))
+    }
+
+    /// The underlying generator.
+    pub fn generator(&self) -> &SnowflakeIdGenerator {
+        &self.generator
+    }
+}
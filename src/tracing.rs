@@ -0,0 +1,36 @@
+//! Correlate generated ids with `tracing` spans.
+//!
+//! Spans only accept fields declared up front, so declare the field as
+//! empty when creating the span:
+//!
+//! ```
+//! use snowflake::SnowflakeIdGenerator;
+//!
+//! let span = tracing::info_span!("create_user", snowflake_id = tracing::field::Empty);
+//! let _guard = span.enter();
+//!
+//! let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+//! let id = id_generator.traced_generate();
+//! ```
+
+use ::tracing::Span;
+
+use crate::SnowflakeIdGenerator;
+
+/// Name of the span field ids are recorded under.
+pub const SPAN_FIELD: &str = "snowflake_id";
+
+/// Record `id` as the `snowflake_id` field of the current span and return
+/// it unchanged.
+pub fn record(id: i64) -> i64 {
+    Span::current().record(SPAN_FIELD, id);
+    id
+}
+
+impl SnowflakeIdGenerator {
+    /// Generate an id with `real_time_generate` and record it on the
+    /// current span, see `snowflake::tracing`.
+    pub fn traced_generate(&mut self) -> i64 {
+        record(self.real_time_generate())
+    }
+}
@@ -0,0 +1,210 @@
+//! Ids typed by the entity they identify.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use crate::{SnowflakeError, SnowflakeId};
+
+/// The `TypedId` type is a `SnowflakeId` tagged with the entity `T` it
+/// identifies, so ids of different entities can't be mixed up.
+///
+/// The tag is a zero-sized marker: a `TypedId` has the size and layout of
+/// an `i64`, whatever `T` is, and `T` needs no trait implementation. With
+/// the `serde` and `sqlx` features it reads and writes exactly like the
+/// `SnowflakeId` it wraps.
+///
+/// # Examples
+///
+/// ```compile_fail
+/// use snowflake::{SnowflakeId, TypedId};
+///
+/// struct User;
+/// struct Order;
+///
+/// type UserId = TypedId<User>;
+/// type OrderId = TypedId<Order>;
+///
+/// fn cancel(order: OrderId) {}
+///
+/// let user = UserId::new(SnowflakeId(42));
+/// cancel(user);
+/// ```
+///
+/// ```
+/// use snowflake::{SnowflakeIdGenerator, TypedId};
+///
+/// struct User;
+///
+/// type UserId = TypedId<User>;
+///
+/// let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+/// let user = UserId::from(id_generator.real_time_generate());
+///
+/// assert_eq!(user.to_string().parse::<UserId>().unwrap(), user);
+/// ```
+pub struct TypedId<T> {
+    id: SnowflakeId,
+    // `fn() -> T` keeps the id `Send`, `Sync` and `Copy` whatever `T` is.
+    entity: PhantomData<fn() -> T>,
+}
+
+impl<T> TypedId<T> {
+    /// Constructs a new `TypedId` for `id`.
+    pub const fn new(id: SnowflakeId) -> TypedId<T> {
+        TypedId {
+            id,
+            entity: PhantomData,
+        }
+    }
+
+    /// The untyped id.
+    pub const fn id(self) -> SnowflakeId {
+        self.id
+    }
+
+    /// The id as an `i64`.
+    pub const fn get(self) -> i64 {
+        self.id.0
+    }
+
+    /// The same id, typed for `U` instead.
+    ///
+    /// For the rare ids that identify two entities, such as a row split
+    /// over two tables.
+    pub const fn cast<U>(self) -> TypedId<U> {
+        TypedId::new(self.id)
+    }
+}
+
+impl<T> Clone for TypedId<T> {
+    fn clone(&self) -> TypedId<T> {
+        *self
+    }
+}
+
+impl<T> Copy for TypedId<T> {}
+
+impl<T> PartialEq for TypedId<T> {
+    fn eq(&self, other: &TypedId<T>) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for TypedId<T> {}
+
+impl<T> PartialOrd for TypedId<T> {
+    fn partial_cmp(&self, other: &TypedId<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for TypedId<T> {
+    fn cmp(&self, other: &TypedId<T>) -> Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+impl<T> Hash for TypedId<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state)
+    }
+}
+
+impl<T> fmt::Debug for TypedId<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TypedId").field(&self.id.0).finish()
+    }
+}
+
+/// Ids are written in decimal.
+impl<T> fmt::Display for TypedId<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.id.0, f)
+    }
+}
+
+impl<T> FromStr for TypedId<T> {
+    type Err = SnowflakeError;
+
+    fn from_str(text: &str) -> Result<TypedId<T>, SnowflakeError> {
+        text.parse().map(TypedId::new)
+    }
+}
+
+impl<T> From<SnowflakeId> for TypedId<T> {
+    fn from(id: SnowflakeId) -> TypedId<T> {
+        TypedId::new(id)
+    }
+}
+
+impl<T> From<i64> for TypedId<T> {
+    fn from(id: i64) -> TypedId<T> {
+        TypedId::new(SnowflakeId(id))
+    }
+}
+
+impl<T> From<TypedId<T>> for SnowflakeId {
+    fn from(id: TypedId<T>) -> SnowflakeId {
+        id.id
+    }
+}
+
+impl<T> From<TypedId<T>> for i64 {
+    fn from(id: TypedId<T>) -> i64 {
+        id.id.0
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for TypedId<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&self.id, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for TypedId<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<TypedId<T>, D::Error> {
+        <SnowflakeId as serde::Deserialize>::deserialize(deserializer).map(TypedId::new)
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl<T, DB: sqlx::Database> sqlx::Type<DB> for TypedId<T>
+where
+    i64: sqlx::Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <i64 as sqlx::Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <i64 as sqlx::Type<DB>>::compatible(ty)
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl<'q, T, DB: sqlx::Database> sqlx::Encode<'q, DB> for TypedId<T>
+where
+    i64: sqlx::Encode<'q, DB>,
+{
+    fn encode_by_ref(
+        &self,
+        buf: &mut DB::ArgumentBuffer<'q>,
+    ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        <i64 as sqlx::Encode<'q, DB>>::encode_by_ref(&self.id.0, buf)
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl<'r, T, DB: sqlx::Database> sqlx::Decode<'r, DB> for TypedId<T>
+where
+    i64: sqlx::Decode<'r, DB>,
+{
+    fn decode(value: DB::ValueRef<'r>) -> Result<TypedId<T>, sqlx::error::BoxDynError> {
+        <i64 as sqlx::Decode<'r, DB>>::decode(value).map(TypedId::from)
+    }
+}
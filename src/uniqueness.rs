@@ -0,0 +1,136 @@
+//! Duplicate detection for soak tests.
+
+use std::collections::HashSet;
+
+use crate::{split_mix64, SPLIT_MIX64_GAMMA};
+
+/// The `UniquenessChecker` type spots duplicate ids among the ones fed
+/// into it.
+///
+/// Feed the ids of every generator of a soak test into one checker: a
+/// duplicate usually means two generators share machine bits.
+///
+/// The exact mode remembers every id. The bloom mode uses a fixed amount of
+/// memory for a planned number of ids, and may report a unique id as a
+/// duplicate with the configured probability, never the other way round.
+///
+/// # Examples
+///
+/// ```
+/// use snowflake::{SnowflakeIdGenerator, UniquenessChecker};
+///
+/// let mut checker = UniquenessChecker::bloom(100_000, 0.001);
+/// let mut first = SnowflakeIdGenerator::new(1, 1);
+/// let mut second = first;
+///
+/// for _ in 0..10_000 {
+///     checker.insert(first.lazy_generate());
+///     checker.insert(second.lazy_generate());
+/// }
+///
+/// assert!(checker.duplicates() > 0);
+/// ```
+#[derive(Clone, Debug)]
+pub struct UniquenessChecker {
+    filter: Filter,
+    checked: u64,
+    duplicates: u64,
+    first_duplicate: Option<i64>,
+}
+
+#[derive(Clone, Debug)]
+enum Filter {
+    Exact(HashSet<i64>),
+    Bloom { bits: Vec<u64>, hashes: u32 },
+}
+
+impl UniquenessChecker {
+    /// Constructs a `UniquenessChecker` remembering every id.
+    pub fn exact() -> UniquenessChecker {
+        UniquenessChecker::with_filter(Filter::Exact(HashSet::new()))
+    }
+
+    /// Constructs a `UniquenessChecker` backed by a bloom filter sized for
+    /// `expected_ids` with the given false positive rate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `false_positive_rate` isn't between 0 and 1 exclusive.
+    pub fn bloom(expected_ids: usize, false_positive_rate: f64) -> UniquenessChecker {
+        assert!(
+            false_positive_rate > 0.0 && false_positive_rate < 1.0,
+            "false_positive_rate must be between 0 and 1"
+        );
+
+        let ln2 = std::f64::consts::LN_2;
+        let expected_ids = expected_ids.max(1) as f64;
+        let bit_count = (-expected_ids * false_positive_rate.ln() / (ln2 * ln2)).ceil();
+        let hashes = (bit_count / expected_ids * ln2).round().max(1.0) as u32;
+        let words = (bit_count as usize).div_ceil(64);
+
+        UniquenessChecker::with_filter(Filter::Bloom {
+            bits: vec![0; words],
+            hashes,
+        })
+    }
+
+    fn with_filter(filter: Filter) -> UniquenessChecker {
+        UniquenessChecker {
+            filter,
+            checked: 0,
+            duplicates: 0,
+            first_duplicate: None,
+        }
+    }
+
+    /// Record `id`, `false` if it was seen before.
+    pub fn insert(&mut self, id: i64) -> bool {
+        self.checked += 1;
+
+        let is_new = match &mut self.filter {
+            Filter::Exact(seen) => seen.insert(id),
+            Filter::Bloom { bits, hashes } => {
+                let bit_count = bits.len() as u64 * 64;
+                let first = split_mix64(id as u64);
+                let second = split_mix64((id as u64).wrapping_add(SPLIT_MIX64_GAMMA)) | 1;
+
+                let mut is_new = false;
+                for i in 0..*hashes as u64 {
+                    let bit = first.wrapping_add(i.wrapping_mul(second)) % bit_count;
+                    let (word, mask) = ((bit / 64) as usize, 1 << (bit % 64));
+                    if bits[word] & mask == 0 {
+                        bits[word] |= mask;
+                        is_new = true;
+                    }
+                }
+                is_new
+            }
+        };
+
+        if !is_new {
+            self.duplicates += 1;
+            self.first_duplicate.get_or_insert(id);
+        }
+        is_new
+    }
+
+    /// Number of ids recorded.
+    pub fn checked(&self) -> u64 {
+        self.checked
+    }
+
+    /// Number of ids recorded that were seen before.
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+
+    /// The first id reported as a duplicate.
+    pub fn first_duplicate(&self) -> Option<i64> {
+        self.first_duplicate
+    }
+
+    /// Whether no duplicate was reported.
+    pub fn is_clean(&self) -> bool {
+        self.duplicates == 0
+    }
+}
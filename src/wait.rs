@@ -0,0 +1,152 @@
+//! Strategies to wait for the clock to reach the next time unit.
+
+use std::fmt;
+#[cfg(any(feature = "async-std", feature = "futures-timer", feature = "tokio"))]
+use std::future::Future;
+use std::hint::spin_loop;
+use std::thread;
+use std::time::Duration;
+
+use crate::TimeUnit;
+
+/// The `WaitStrategy` trait is how a generator waits for the clock once
+/// every auto-increment record of the current time unit is used.
+///
+/// `wait_until` may return early, e.g. when the clock can't be read; the
+/// generator reads the clock again and waits anew until it moved past the
+/// exhausted time unit. Implement it to hand the wait to a scheduler.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// use snowflake::{Layout, SnowflakeIdGenerator, WaitStrategy};
+///
+/// #[derive(Debug)]
+/// struct Nap;
+///
+/// impl WaitStrategy for Nap {
+///     fn wait_until(&self, _target_millis: i64) {
+///         thread::sleep(Duration::from_micros(100));
+///     }
+/// }
+///
+/// let mut id_generator = SnowflakeIdGenerator::new(1, 1)
+///     .with_layout(Layout::new(41, 10, 2))
+///     .with_wait_strategy(&Nap);
+///
+/// let ids: Vec<i64> = (0..16).map(|_| id_generator.real_time_generate()).collect();
+/// assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+/// ```
+pub trait WaitStrategy: fmt::Debug + Sync {
+    /// Wait until the clock reads `target_millis`, milliseconds since the
+    /// Unix epoch, or later.
+    fn wait_until(&self, target_millis: i64);
+}
+
+/// Busy wait, the lowest latency at the cost of a core.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Spin;
+
+/// Yield the thread to the OS scheduler between clock reads.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Yield;
+
+/// Sleep the thread until the target time.
+///
+/// Cheapest on CPU, but the OS may oversleep by a scheduler tick.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Sleep;
+
+impl WaitStrategy for Spin {
+    fn wait_until(&self, target_millis: i64) {
+        while millis_left(target_millis) > 0 {
+            spin_loop();
+        }
+    }
+}
+
+impl WaitStrategy for Yield {
+    fn wait_until(&self, target_millis: i64) {
+        while millis_left(target_millis) > 0 {
+            thread::yield_now();
+        }
+    }
+}
+
+impl WaitStrategy for Sleep {
+    fn wait_until(&self, target_millis: i64) {
+        let millis = millis_left(target_millis);
+        if millis > 0 {
+            thread::sleep(Duration::from_millis(millis as u64));
+        }
+    }
+}
+
+/// The `AsyncWaitStrategy` trait is how an `AsyncGenerator` waits for the
+/// clock without blocking the runtime's worker threads.
+///
+/// `AsyncSleep` waits on the tokio timer, `AsyncStdSleep` on the async-std
+/// one, and `TimerSleep` on `futures-timer`, which works with any runtime,
+/// e.g. smol.
+#[cfg(any(feature = "async-std", feature = "futures-timer", feature = "tokio"))]
+pub trait AsyncWaitStrategy {
+    /// Wait until the clock reads `target_millis`, milliseconds since the
+    /// Unix epoch, or later.
+    fn wait_until(&self, target_millis: i64) -> impl Future<Output = ()> + Send;
+}
+
+/// Sleep the task on the tokio timer until the target time.
+#[cfg(feature = "tokio")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AsyncSleep;
+
+#[cfg(feature = "tokio")]
+impl AsyncWaitStrategy for AsyncSleep {
+    fn wait_until(&self, target_millis: i64) -> impl Future<Output = ()> + Send {
+        tokio::time::sleep(async_sleep_duration(target_millis))
+    }
+}
+
+/// Sleep the task on the async-std timer until the target time.
+#[cfg(feature = "async-std")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AsyncStdSleep;
+
+#[cfg(feature = "async-std")]
+impl AsyncWaitStrategy for AsyncStdSleep {
+    fn wait_until(&self, target_millis: i64) -> impl Future<Output = ()> + Send {
+        async_std::task::sleep(async_sleep_duration(target_millis))
+    }
+}
+
+/// Sleep the task on a `futures-timer` delay until the target time, on
+/// any runtime.
+#[cfg(feature = "futures-timer")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TimerSleep;
+
+#[cfg(feature = "futures-timer")]
+impl AsyncWaitStrategy for TimerSleep {
+    fn wait_until(&self, target_millis: i64) -> impl Future<Output = ()> + Send {
+        futures_timer::Delay::new(async_sleep_duration(target_millis))
+    }
+}
+
+// How long to sleep for the clock to read `target_millis`, at least a
+// millisecond so a task never busy loops on an exhausted time unit.
+#[cfg(any(feature = "async-std", feature = "futures-timer", feature = "tokio"))]
+fn async_sleep_duration(target_millis: i64) -> Duration {
+    Duration::from_millis(millis_left(target_millis).max(1) as u64)
+}
+
+// Milliseconds until the clock reads `target_millis`, zero if the clock
+// can't be read.
+fn millis_left(target_millis: i64) -> i64 {
+    match TimeUnit::Milliseconds.try_now() {
+        Some(now_millis) => target_millis - now_millis,
+        None => 0,
+    }
+}
@@ -0,0 +1,177 @@
+//! In-process worker id pool.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::leased::LeaseState;
+use crate::{Layout, LeasedGenerator};
+
+/// The `WorkerIdPool` type owns a range of machine ids and leases them out.
+///
+/// Every lease holds its machine id exclusively until it is dropped, so
+/// generators built from leases of the same pool never collide. Released
+/// machine ids go to the back of the queue, and stay in quarantine for at
+/// least a time unit of the layout: the next holder can't issue ids in the
+/// time unit the previous one last issued ids in.
+#[derive(Clone, Debug)]
+pub struct WorkerIdPool {
+    free: Arc<Mutex<VecDeque<FreeId>>>,
+    machine_ids: Range<i64>,
+    layout: Layout,
+    quarantine: Duration,
+}
+
+/// The `WorkerIdLease` type is a machine id borrowed from a `WorkerIdPool`.
+///
+/// The machine id goes back to the pool on drop, and the generators of the
+/// lease stop working.
+#[derive(Debug)]
+pub struct WorkerIdLease {
+    state: Arc<LeaseState>,
+    layout: Layout,
+    free: Arc<Mutex<VecDeque<FreeId>>>,
+}
+
+#[derive(Debug)]
+struct FreeId {
+    machine_id: i64,
+    // When the machine id was released, `None` if it was never leased.
+    released: Option<Instant>,
+}
+
+impl WorkerIdPool {
+    /// Constructs a new `WorkerIdPool` owning `machine_ids`, for generators
+    /// of the default layout.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the machine ids don't fit the machine bits of the layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::WorkerIdPool;
+    ///
+    /// let pool = WorkerIdPool::new(0..32);
+    /// ```
+    pub fn new(machine_ids: Range<i64>) -> WorkerIdPool {
+        let free = machine_ids
+            .clone()
+            .map(|machine_id| FreeId {
+                machine_id,
+                released: None,
+            })
+            .collect();
+
+        let pool = WorkerIdPool {
+            free: Arc::new(Mutex::new(free)),
+            machine_ids,
+            layout: Layout::default(),
+            quarantine: Duration::ZERO,
+        };
+        pool.with_layout(Layout::default())
+    }
+
+    /// Build the generators with `layout`, and keep released machine ids
+    /// in quarantine for a time unit of it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the machine ids don't fit the machine bits of the layout,
+    /// below its shard and tag bits.
+    pub fn with_layout(mut self, layout: Layout) -> WorkerIdPool {
+        assert!(
+            layout.fits_machine_ids(&self.machine_ids),
+            "machine ids {:?} don't fit the layout",
+            self.machine_ids
+        );
+
+        self.layout = layout;
+        self.quarantine = self.quarantine.max(layout.time_unit().duration_of(1));
+        self
+    }
+
+    /// Keep released machine ids in quarantine for `quarantine`, at least a
+    /// time unit of the layout, e.g. to outlast a step of the clock.
+    pub fn with_quarantine(mut self, quarantine: Duration) -> WorkerIdPool {
+        self.quarantine = quarantine.max(self.layout.time_unit().duration_of(1));
+        self
+    }
+
+    /// Lease the machine id released the longest ago, `None` if all of
+    /// them are in use or in quarantine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::WorkerIdPool;
+    ///
+    /// let pool = WorkerIdPool::new(0..32);
+    /// let lease = pool.acquire().unwrap();
+    ///
+    /// assert_eq!(lease.machine_id(), 0);
+    /// ```
+    pub fn acquire(&self) -> Option<WorkerIdLease> {
+        let mut free = self.lock();
+        let released = free.front()?.released;
+        if released.is_some_and(|released| released.elapsed() < self.quarantine) {
+            return None;
+        }
+        let machine_id = free.pop_front()?.machine_id;
+
+        Some(WorkerIdLease {
+            state: LeaseState::new(machine_id, None),
+            layout: self.layout,
+            free: Arc::clone(&self.free),
+        })
+    }
+
+    /// The number of machine ids not leased right now, those in quarantine
+    /// included.
+    pub fn available(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<FreeId>> {
+        self.free.lock().expect("Worker id pool lock poisoned")
+    }
+}
+
+impl WorkerIdLease {
+    /// The leased machine id.
+    pub fn machine_id(&self) -> i64 {
+        self.state.machine_id()
+    }
+
+    /// Constructs a generator using the leased machine id, which fails
+    /// once the lease is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use snowflake::WorkerIdPool;
+    ///
+    /// let pool = WorkerIdPool::new(0..32);
+    /// let lease = pool.acquire().unwrap();
+    ///
+    /// let mut id_generator = lease.generator();
+    /// id_generator.generate().unwrap();
+    /// ```
+    pub fn generator(&self) -> LeasedGenerator {
+        LeasedGenerator::new(&self.state, self.layout)
+    }
+}
+
+impl Drop for WorkerIdLease {
+    fn drop(&mut self) {
+        self.state.lose();
+        if let Ok(mut free) = self.free.lock() {
+            free.push_back(FreeId {
+                machine_id: self.state.machine_id(),
+                released: Some(Instant::now()),
+            });
+        }
+    }
+}
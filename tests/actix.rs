@@ -0,0 +1,46 @@
+#![cfg(feature = "actix")]
+
+use actix_web::{test, web, App};
+use snowflake::actix::{RequestId, RequestIdMiddleware, REQUEST_ID_HEADER};
+use snowflake::{SharedGenerator, SnowflakeIdGenerator};
+
+async fn echo(request_id: RequestId) -> String {
+    request_id.id().0.to_string()
+}
+
+#[actix_web::test]
+async fn test_request_id_middleware() {
+    let generator = SharedGenerator::new(SnowflakeIdGenerator::new(1, 1));
+    let app = test::init_service(
+        App::new()
+            .wrap(RequestIdMiddleware::new(generator))
+            .route("/", web::get().to(echo)),
+    )
+    .await;
+
+    let mut seen = Vec::new();
+    for _ in 0..2 {
+        let res = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+        let header = res
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned();
+        let body = test::read_body(res).await;
+
+        assert_eq!(header.as_bytes(), &body[..]);
+        seen.push(header.parse::<i64>().unwrap());
+    }
+
+    assert!(seen[0] < seen[1]);
+}
+
+#[actix_web::test]
+async fn test_extractor_without_middleware() {
+    let app = test::init_service(App::new().route("/", web::get().to(echo))).await;
+    let res = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+
+    assert!(res.status().is_server_error());
+}
@@ -0,0 +1,49 @@
+#![cfg(feature = "serde")]
+
+use serde::{Deserialize, Serialize};
+use snowflake::{SnowflakeId, SnowflakeIdStr};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Order {
+    #[serde(with = "snowflake::as_string")]
+    id: SnowflakeId,
+    parent: Option<SnowflakeIdStr>,
+}
+
+#[test]
+fn test_serialize_as_string() {
+    let order = Order {
+        id: SnowflakeId(6725650666386976769),
+        parent: Some(SnowflakeIdStr(SnowflakeId(6725650666386976770))),
+    };
+
+    let json = serde_json::to_string(&order).unwrap();
+    assert_eq!(
+        json,
+        r#"{"id":"6725650666386976769","parent":"6725650666386976770"}"#
+    );
+    assert_eq!(serde_json::from_str::<Order>(&json).unwrap(), order);
+
+    let numbers = r#"{"id":6725650666386976769,"parent":6725650666386976770}"#;
+    assert_eq!(serde_json::from_str::<Order>(numbers).unwrap(), order);
+
+    assert!(serde_json::from_str::<SnowflakeIdStr>(r#""12ab""#).is_err());
+    assert!(serde_json::from_str::<SnowflakeIdStr>("18446744073709551615").is_err());
+}
+
+#[test]
+fn test_tolerant_deserialize() {
+    let id = SnowflakeId(6725650666386976769);
+
+    assert_eq!(serde_json::to_string(&id).unwrap(), "6725650666386976769");
+    for json in &[
+        "6725650666386976769",
+        r#""6725650666386976769""#,
+        r#""80pYBbV5CzZ""#,
+    ] {
+        assert_eq!(serde_json::from_str::<SnowflakeId>(json).unwrap(), id);
+    }
+
+    assert!(serde_json::from_str::<SnowflakeId>(r#""not an id!""#).is_err());
+    assert!(serde_json::from_str::<SnowflakeId>("-1.5").is_err());
+}
@@ -0,0 +1,49 @@
+#![cfg(feature = "tokio")]
+
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use snowflake::{AsyncGenerator, AsyncSleep, AsyncWaitStrategy, Layout, SnowflakeIdGenerator};
+
+#[tokio::test]
+async fn test_async_generator_waits_for_next_unit() {
+    let layout = Layout::new(41, 10, 2);
+    let generator = AsyncGenerator::new(SnowflakeIdGenerator::new(1, 1).with_layout(layout));
+
+    let mut ids = Vec::new();
+    for _ in 0..64 {
+        ids.push(generator.generate().await);
+    }
+
+    let mut sorted = ids.clone();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(ids, sorted);
+}
+
+#[derive(Clone)]
+struct CountingSleep(Arc<AtomicUsize>);
+
+impl AsyncWaitStrategy for CountingSleep {
+    fn wait_until(&self, target_millis: i64) -> impl Future<Output = ()> + Send {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        AsyncSleep.wait_until(target_millis)
+    }
+}
+
+#[tokio::test]
+async fn test_async_generator_with_wait_strategy() {
+    let waits = Arc::new(AtomicUsize::new(0));
+    let layout = Layout::new(41, 10, 2);
+    let generator = AsyncGenerator::new(SnowflakeIdGenerator::new(1, 1).with_layout(layout))
+        .with_wait_strategy(CountingSleep(waits.clone()));
+
+    let mut ids = Vec::new();
+    for _ in 0..20 {
+        ids.push(generator.generate().await);
+    }
+
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(waits.load(Ordering::SeqCst) >= 4);
+}
@@ -0,0 +1,20 @@
+#![cfg(feature = "async-std")]
+
+use snowflake::{AsyncGenerator, AsyncStdSleep, Layout, SnowflakeIdGenerator};
+
+#[test]
+fn test_async_std_generator_waits_for_next_unit() {
+    let layout = Layout::new(41, 10, 2);
+    let generator = AsyncGenerator::new(SnowflakeIdGenerator::new(1, 1).with_layout(layout))
+        .with_wait_strategy(AsyncStdSleep);
+
+    let ids = async_std::task::block_on(async {
+        let mut ids = Vec::new();
+        for _ in 0..64 {
+            ids.push(generator.generate().await);
+        }
+        ids
+    });
+
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+}
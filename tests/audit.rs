@@ -0,0 +1,33 @@
+use snowflake::audit::Audit;
+use snowflake::Layout;
+
+#[test]
+fn test_audit_report() {
+    let id = |timestamp: i64, machine: i64, idx: i64| timestamp << 22 | machine << 12 | idx;
+
+    let ids = vec![
+        id(1000, 1, 0),
+        id(1000, 2, 0),
+        id(1001, 1, 0),
+        id(1001, 1, 0),
+        id(999, 2, 0),
+        id(5000, 1, 0),
+    ];
+
+    let report = Audit::new(Layout::default())
+        .with_gap_threshold(1000)
+        .run(ids);
+
+    assert_eq!(report.total, 6);
+    assert_eq!(report.duplicates, vec![id(1001, 1, 0)]);
+    assert_eq!(report.per_machine[&1], 4);
+    assert_eq!(report.per_machine[&2], 2);
+
+    assert_eq!(report.regressions.len(), 1);
+    assert_eq!(report.regressions[0].machine_bits, 2);
+    assert_eq!(report.regressions[0].millis, -1);
+
+    assert_eq!(report.gaps.len(), 1);
+    assert_eq!(report.gaps[0].id, id(5000, 1, 0));
+    assert!(!report.is_clean());
+}
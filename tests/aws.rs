@@ -0,0 +1,86 @@
+#![cfg(feature = "aws")]
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::thread;
+
+use snowflake::{AwsMetadataResolver, InstanceKey, Layout, WorkerIdResolver};
+
+// Serve `requests` connections, answering IMDSv2 like EC2 does.
+fn serve_imds(requests: usize) -> (SocketAddr, thread::JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = listener.local_addr().unwrap();
+
+    let handle = thread::spawn(move || {
+        let mut seen = Vec::new();
+        for stream in listener.incoming().take(requests) {
+            let mut stream = stream.unwrap();
+            let mut buffer = [0u8; 1024];
+            let read = stream.read(&mut buffer).unwrap();
+            let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+
+            let (status, body) = if request.starts_with("PUT /latest/api/token") {
+                ("200 OK", "token-123")
+            } else if !request.contains("X-aws-ec2-metadata-token: token-123") {
+                ("401 Unauthorized", "")
+            } else if request.starts_with("GET /latest/meta-data/instance-id") {
+                ("200 OK", "i-0123456789abcdef0")
+            } else if request.starts_with("GET /latest/meta-data/local-ipv4") {
+                ("200 OK", "10.0.12.34")
+            } else {
+                ("404 Not Found", "")
+            };
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+            seen.push(request);
+        }
+        seen
+    });
+
+    (endpoint, handle)
+}
+
+#[test]
+fn test_aws_instance_id() {
+    let (endpoint, server) = serve_imds(2);
+    let resolver = AwsMetadataResolver::new().with_endpoint(endpoint);
+
+    assert_eq!(resolver.instance_key().unwrap(), "i-0123456789abcdef0");
+    let machine_bits = resolver.machine_bits(Layout::default()).unwrap();
+    assert!(machine_bits < 1 << 10);
+    assert_eq!(
+        resolver.machine_bits(Layout::default()).unwrap(),
+        machine_bits
+    );
+
+    // Both answers were cached, so the server only saw one exchange.
+    let requests = server.join().unwrap();
+    assert!(requests[0].contains("X-aws-ec2-metadata-token-ttl-seconds"));
+}
+
+#[test]
+fn test_aws_private_ip() {
+    let (endpoint, server) = serve_imds(2);
+    let resolver = AwsMetadataResolver::new()
+        .with_endpoint(endpoint)
+        .with_key(InstanceKey::PrivateIp);
+
+    assert_eq!(resolver.instance_key().unwrap(), "10.0.12.34");
+    server.join().unwrap();
+}
+
+#[test]
+fn test_aws_unreachable() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = listener.local_addr().unwrap();
+    drop(listener);
+
+    let resolver = AwsMetadataResolver::new().with_endpoint(endpoint);
+    assert!(resolver.machine_bits(Layout::default()).is_err());
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "azure")]
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::thread;
+
+use snowflake::{AzureMetadataResolver, Layout, WorkerIdResolver};
+
+// Serve `requests` connections, answering like the Azure metadata service.
+fn serve_imds(requests: usize) -> (SocketAddr, thread::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = listener.local_addr().unwrap();
+
+    let handle = thread::spawn(move || {
+        for stream in listener.incoming().take(requests) {
+            let mut stream = stream.unwrap();
+            let mut buffer = [0u8; 1024];
+            let read = stream.read(&mut buffer).unwrap();
+            let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+
+            let (status, body) = if !request.contains("Metadata: true") {
+                ("400 Bad Request", "")
+            } else if request.starts_with("GET /metadata/instance/compute/vmId?") {
+                ("200 OK", "02aab8a4-74ef-476e-8182-f6d2ba4166a6")
+            } else {
+                ("404 Not Found", "")
+            };
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+    });
+
+    (endpoint, handle)
+}
+
+#[test]
+fn test_azure_vm_id() {
+    let (endpoint, server) = serve_imds(1);
+    let resolver = AzureMetadataResolver::new().with_endpoint(endpoint);
+
+    assert_eq!(
+        resolver.vm_id().unwrap(),
+        "02aab8a4-74ef-476e-8182-f6d2ba4166a6"
+    );
+    let machine_bits = resolver.machine_bits(Layout::default()).unwrap();
+    assert!(machine_bits < 1 << 10);
+    assert_eq!(
+        resolver.machine_bits(Layout::default()).unwrap(),
+        machine_bits
+    );
+    server.join().unwrap();
+}
+
+#[test]
+fn test_azure_as_resolver_object() {
+    let (endpoint, server) = serve_imds(1);
+    let resolver: Box<dyn WorkerIdResolver> =
+        Box::new(AzureMetadataResolver::new().with_endpoint(endpoint));
+
+    assert!(resolver.machine_bits(Layout::JS_SAFE).unwrap() < 1 << 4);
+    server.join().unwrap();
+}
+
+#[test]
+fn test_azure_unreachable() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = listener.local_addr().unwrap();
+    drop(listener);
+
+    let resolver = AzureMetadataResolver::new().with_endpoint(endpoint);
+    assert!(resolver.machine_bits(Layout::default()).is_err());
+}
@@ -0,0 +1,50 @@
+use snowflake::{BackfillGenerator, SnowflakeError, SnowflakeId};
+
+#[test]
+fn test_backfill_unique_per_timestamp() {
+    let mut backfill = BackfillGenerator::new(0x3FF);
+    let mut ids = Vec::new();
+
+    for timestamp in &[1_600_000_000_000, 1_600_000_000_001, 1_600_000_000_000] {
+        for _ in 0..1000 {
+            ids.push(backfill.generate_with_unix_millis(*timestamp).unwrap());
+        }
+    }
+
+    assert!(ids
+        .iter()
+        .all(|&id| SnowflakeId(id).machine_bits() == 0x3FF));
+
+    ids.sort();
+    ids.dedup();
+    assert_eq!(3000, ids.len());
+}
+
+#[test]
+fn test_backfill_exhaustion() {
+    let mut backfill = BackfillGenerator::new(0);
+
+    let issued = (0..5000)
+        .filter_map(|_| backfill.generate_with_unix_millis(1_600_000_000_000).ok())
+        .count();
+    assert_eq!(issued, 4096);
+    assert_eq!(
+        backfill.generate_with_unix_millis(1_600_000_000_000),
+        Err(SnowflakeError::SequenceExhausted(1_600_000_000_000))
+    );
+
+    backfill.forget_before(1_600_000_000_001);
+    assert!(backfill
+        .generate_with_unix_millis(1_600_000_000_000)
+        .is_ok());
+}
+
+#[test]
+fn test_backfill_rejects_machine_bits_past_layout() {
+    let mut backfill = BackfillGenerator::new(0x400);
+
+    assert_eq!(
+        backfill.generate_with_unix_millis(1_600_000_000_000),
+        Err(SnowflakeError::InvalidMachineId(0x400))
+    );
+}
@@ -1,4 +1,12 @@
-use snowflake::{SnowflakeIdGenerator};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use snowflake::{
+    ClockEvent, Layout, SnowflakeError, SnowflakeIdGenerator, TimeSource, UnixMillis, WaitStrategy,
+    Yield,
+};
 
 #[test]
 fn test_reversable_ts() {
@@ -76,3 +84,419 @@ fn test_lazy_generate() {
         ids.clear();
     }
 }
+
+#[test]
+fn test_snowflake_ordering() {
+    let ip = "102.65.2.123".to_string();
+    let mut id_generator = SnowflakeIdGenerator::new_from_ip(ip);
+
+    let ids: Vec<i64> = (0..10000)
+        .map(|_| id_generator.real_time_generate())
+        .collect();
+    let mut snowflakes: Vec<_> = ids
+        .iter()
+        .rev()
+        .map(|&id| id_generator.reverse(id as u64))
+        .collect();
+
+    snowflakes.sort();
+
+    let sorted: Vec<_> = ids.iter().map(|&id| id_generator.reverse(id as u64)).collect();
+    assert_eq!(sorted, snowflakes);
+
+    let unique: HashSet<_> = snowflakes.into_iter().collect();
+    assert_eq!(10000, unique.len());
+}
+
+#[test]
+fn test_random_idx_start() {
+    let ip = "102.65.2.123".to_string();
+    let mut id_generator = SnowflakeIdGenerator::new_from_ip(ip).with_random_idx_start();
+    let mut ids = Vec::with_capacity(10000);
+
+    for _ in 0..99 {
+        for _ in 0..10000 {
+            ids.push(id_generator.real_time_generate());
+        }
+
+        ids.sort();
+        ids.dedup();
+
+        assert_eq!(10000, ids.len());
+
+        ids.clear();
+    }
+
+    let mut lazy_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string())
+        .with_random_idx_start();
+    let mut first_idxs = HashSet::new();
+
+    let lazy_ids: HashSet<i64> = (0..4096 * 16)
+        .map(|_| {
+            let id = lazy_generator.lazy_generate();
+            first_idxs.insert(lazy_generator.first_idx);
+            id
+        })
+        .collect();
+
+    assert_eq!(lazy_ids.len(), 4096 * 16);
+    assert!(first_idxs.len() > 8);
+}
+
+#[test]
+fn test_datacenter_worker_generate() {
+    let mut first = SnowflakeIdGenerator::new(1, 7);
+    let mut second = SnowflakeIdGenerator::new(7, 1);
+
+    let first_id = first.real_time_generate();
+    let second_id = second.real_time_generate();
+
+    assert_eq!(first.reverse(first_id as u64).machine_bits, 0b00001_00111);
+    assert_eq!(second.reverse(second_id as u64).machine_bits, 0b00111_00001);
+}
+
+#[test]
+#[should_panic]
+fn test_datacenter_out_of_range() {
+    SnowflakeIdGenerator::new(32, 0);
+}
+
+#[test]
+fn test_with_machine_id() {
+    let mut id_generator = SnowflakeIdGenerator::with_machine_id(1000).unwrap();
+    let id = id_generator.real_time_generate();
+
+    assert_eq!(id_generator.reverse(id as u64).machine_bits, 1000);
+    assert_eq!(
+        SnowflakeIdGenerator::with_machine_id(u32::MAX).unwrap_err(),
+        SnowflakeError::InvalidMachineId(u32::MAX as i64)
+    );
+}
+
+#[test]
+fn test_clone_with_machine_id() {
+    let template = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string())
+        .with_layout(Layout::SECONDS)
+        .with_random_idx_start();
+
+    let mut shard = template.clone_with_machine_id(40000).unwrap();
+    let id = shard.real_time_generate();
+
+    assert_eq!(Layout::SECONDS.decode(id).machine_bits, 40000);
+    assert!(shard.idx_rng.is_some());
+    assert_eq!(
+        template.clone_with_machine_id(1 << 16).unwrap_err(),
+        SnowflakeError::InvalidMachineId(1 << 16)
+    );
+}
+
+#[test]
+fn test_clock_fallback_with_working_clock() {
+    let mut id_generator =
+        SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string()).with_clock_fallback();
+
+    let ids: Vec<i64> = (0..10000).map(|_| id_generator.real_time_generate()).collect();
+
+    assert!(id_generator.clock_fallback);
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(id_generator.last_time_millis <= snowflake::get_time_millis());
+}
+
+#[test]
+fn test_stats() {
+    let layout = Layout::new(41, 10, 2);
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1).with_layout(layout);
+
+    for _ in 0..20 {
+        id_generator.real_time_generate();
+    }
+
+    let stats = id_generator.stats();
+    assert_eq!(stats.issued, 20);
+    assert!(stats.exhaustion_waits >= 4);
+    assert_eq!(stats.clock_anomalies, 0);
+
+    id_generator.last_time_millis += 10_000;
+    id_generator.real_time_generate();
+    assert_eq!(id_generator.stats().clock_anomalies, 1);
+    assert_eq!(id_generator.clone_with_machine_id(2).unwrap().stats().issued, 0);
+}
+
+#[test]
+fn test_try_generate() {
+    let layout = Layout::new(41, 10, 2);
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1).with_layout(layout);
+
+    let mut ids = Vec::new();
+    while ids.len() < 12 {
+        if let Some(id) = id_generator.try_generate() {
+            ids.push(id);
+        }
+    }
+
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(id_generator.stats().exhaustion_waits, 0);
+}
+
+#[test]
+fn test_generate_at() {
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1).with_layout(Layout::new(41, 10, 2));
+    let now = SystemTime::now();
+    let later = now + Duration::from_secs(10);
+
+    let ids: Vec<_> = (0..4).map(|_| id_generator.generate_at(later).unwrap()).collect();
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(
+        id_generator.generate_at(later),
+        Err(SnowflakeError::SequenceExhausted(later.unix_millis()))
+    );
+    assert_eq!(
+        id_generator.generate_at(now),
+        Err(SnowflakeError::BeforeHighWaterMark {
+            timestamp: now.unix_millis(),
+            high_water_mark: later.unix_millis(),
+        })
+    );
+
+    let past = id_generator.generate_at_allow_past(now).unwrap();
+    assert!(past < ids[0]);
+    assert_eq!(id_generator.last_time_millis, later.unix_millis());
+}
+
+#[test]
+fn test_generate_at_allow_past_keeps_ids_unique() {
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1).with_layout(Layout::new(41, 10, 2));
+    let earlier = SystemTime::now();
+    let now = earlier + Duration::from_secs(10);
+
+    let mut ids = vec![id_generator.generate_at(now).unwrap()];
+    for _ in 0..8 {
+        ids.extend(id_generator.generate_at_allow_past(earlier).ok());
+        ids.extend(id_generator.generate_at(now).ok());
+    }
+    assert_eq!(ids.len(), 8);
+    assert_eq!(
+        id_generator.generate_at_allow_past(earlier),
+        Err(SnowflakeError::SequenceExhausted(earlier.unix_millis()))
+    );
+    assert_eq!(
+        id_generator.generate_at_allow_past(earlier - Duration::from_secs(1)),
+        Err(SnowflakeError::BeforeHighWaterMark {
+            timestamp: (earlier - Duration::from_secs(1)).unix_millis(),
+            high_water_mark: earlier.unix_millis(),
+        })
+    );
+
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 8);
+}
+
+#[test]
+fn test_generate_at_epoch_overflow() {
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    let overflowing = UNIX_EPOCH + Duration::from_millis(1 << 42);
+
+    assert_eq!(
+        id_generator.generate_at(overflowing),
+        Err(SnowflakeError::EpochOverflow(1 << 42))
+    );
+    assert!(id_generator.generate_at(SystemTime::now()).is_ok());
+}
+
+#[test]
+fn test_with_process_id() {
+    let layout = Layout::default().with_pid_bits(3);
+    let mut id_generator = SnowflakeIdGenerator::new(1, 7).with_layout(layout).with_process_id();
+    let id = id_generator.real_time_generate();
+
+    let machine_bits = layout.decode(id).machine_bits;
+    assert_eq!(machine_bits >> 3, (1 << 5 | 7) >> 3);
+    assert_eq!(machine_bits & 0b111, i64::from(std::process::id() % 8));
+
+    let unchanged = SnowflakeIdGenerator::new(1, 7).with_process_id();
+    assert_eq!(unchanged.machine_bits, 1 << 5 | 7);
+}
+
+#[test]
+#[should_panic]
+fn test_pid_bits_out_of_range() {
+    Layout::new(41, 10, 12).with_pid_bits(11);
+}
+
+#[test]
+fn test_with_thread_index() {
+    let layout = Layout::default().with_pid_bits(2).with_thread_bits(3);
+    let template = SnowflakeIdGenerator::new(1, 7).with_layout(layout).with_process_id();
+
+    let machine_bits: HashSet<i64> = (0..8)
+        .map(|index| template.with_thread_index(index).unwrap().machine_bits)
+        .collect();
+    assert_eq!(machine_bits.len(), 8);
+    for bits in machine_bits {
+        assert_eq!(bits >> 5, 1);
+        assert_eq!(bits >> 3 & 0b11, i64::from(std::process::id() % 4));
+    }
+
+    assert_eq!(
+        template.with_thread_index(8).unwrap_err(),
+        SnowflakeError::InvalidThreadIndex(8)
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_thread_bits_out_of_range() {
+    Layout::new(41, 10, 12).with_pid_bits(4).with_thread_bits(7);
+}
+
+static CLOCK_EVENTS: Mutex<Vec<ClockEvent>> = Mutex::new(Vec::new());
+
+fn record_clock_event(event: ClockEvent) {
+    CLOCK_EVENTS.lock().unwrap().push(event);
+}
+
+#[test]
+fn test_on_clock_event() {
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1).on_clock_event(record_clock_event);
+    id_generator.real_time_generate();
+
+    // The clock seems to have gone back 10 seconds.
+    let last_time_millis = id_generator.last_time_millis + 10_000;
+    id_generator.last_time_millis = last_time_millis;
+    id_generator.real_time_generate();
+
+    for _ in 0..10000 {
+        id_generator.real_time_generate();
+    }
+
+    let events = CLOCK_EVENTS.lock().unwrap().clone();
+    assert!(events.iter().any(|event| matches!(
+        event,
+        ClockEvent::Regression { last_millis, now_millis }
+            if *last_millis == last_time_millis && *now_millis < last_time_millis
+    )));
+    assert!(!events.iter().any(|event| matches!(event, ClockEvent::ForwardJump { .. })));
+}
+
+#[derive(Debug)]
+struct CountingWait(AtomicUsize);
+
+impl WaitStrategy for CountingWait {
+    fn wait_until(&self, target_millis: i64) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Yield.wait_until(target_millis);
+    }
+}
+
+static COUNTING_WAIT: CountingWait = CountingWait(AtomicUsize::new(0));
+
+#[test]
+fn test_with_wait_strategy() {
+    let layout = Layout::new(41, 10, 2);
+    let mut id_generator =
+        SnowflakeIdGenerator::new(1, 1).with_layout(layout).with_wait_strategy(&COUNTING_WAIT);
+
+    let ids: Vec<i64> = (0..20).map(|_| id_generator.real_time_generate()).collect();
+
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(COUNTING_WAIT.0.load(Ordering::SeqCst) >= 4);
+}
+
+#[test]
+fn test_reverse_many() {
+    let mut id_generator = SnowflakeIdGenerator::new(1, 7).with_layout(Layout::SECONDS);
+    let ids: Vec<i64> = (0..100).map(|_| id_generator.real_time_generate()).collect();
+
+    let one_by_one: Vec<_> = ids.iter().map(|&id| id_generator.reverse(id as u64)).collect();
+    assert_eq!(id_generator.reverse_many(&ids), one_by_one);
+
+    let lazy = id_generator.reverse_iter(ids.clone());
+    assert_eq!(lazy.len(), 100);
+    assert_eq!(lazy.rev().collect::<Vec<_>>(), one_by_one.into_iter().rev().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_try_real_time_generate() {
+    let mut id_generator = SnowflakeIdGenerator::try_new_from_ip("102.65.2.123").unwrap();
+    let ids: Vec<_> = (0..10000).map(|_| id_generator.try_real_time_generate().unwrap()).collect();
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+
+    let last_time_millis = id_generator.last_time_millis + 10_000;
+    id_generator.last_time_millis = last_time_millis;
+    match id_generator.try_real_time_generate() {
+        Err(SnowflakeError::ClockMovedBackwards {
+            last_millis,
+            now_millis,
+        }) => {
+            assert_eq!(last_millis, last_time_millis);
+            assert!(now_millis < last_millis);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(id_generator.stats().clock_anomalies, 1);
+
+    let mut overflowing = SnowflakeIdGenerator::new(1, 1).with_layout(Layout::new(30, 10, 12));
+    assert!(matches!(
+        overflowing.try_real_time_generate(),
+        Err(SnowflakeError::EpochOverflow(_))
+    ));
+    assert_eq!(
+        SnowflakeIdGenerator::try_new_from_ip("102.65.2.256").unwrap_err(),
+        SnowflakeError::InvalidIp
+    );
+    assert_eq!(
+        SnowflakeIdGenerator::try_new_from_ip("102.65.4.1").unwrap_err(),
+        SnowflakeError::InvalidMachineId(1025)
+    );
+}
+
+#[test]
+fn test_try_real_time_generate_before_epoch() {
+    static CLOCK: AtomicI64 = AtomicI64::new(-5);
+    let mut id_generator =
+        SnowflakeIdGenerator::new(1, 1).with_time_source(TimeSource::Manual(&CLOCK));
+
+    assert_eq!(
+        id_generator.try_real_time_generate(),
+        Err(SnowflakeError::ClockBeforeEpoch(-5))
+    );
+}
+
+#[test]
+#[should_panic(expected = "invalid IPv4 address")]
+fn test_new_from_invalid_ip() {
+    SnowflakeIdGenerator::new_from_ip("102.65".to_string());
+}
+
+#[test]
+fn test_try_get_time_millis() {
+    let before = snowflake::get_time_millis();
+    let now_millis = snowflake::try_get_time_millis().unwrap();
+
+    assert!(before <= now_millis && now_millis <= snowflake::get_time_millis());
+}
+
+#[test]
+fn test_try_real_time_generate_waits() {
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1).with_layout(Layout::new(41, 10, 2));
+
+    let ids: Vec<_> = (0..20).map(|_| id_generator.try_real_time_generate().unwrap()).collect();
+
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(id_generator.stats().exhaustion_waits >= 4);
+}
+
+static STATIC_GENERATOR: Mutex<SnowflakeIdGenerator> =
+    Mutex::new(SnowflakeIdGenerator::new_static(Layout::SECONDS, 40000));
+
+#[test]
+fn test_new_static() {
+    let mut id_generator = STATIC_GENERATOR.lock().unwrap();
+    let lazy_id = id_generator.lazy_generate();
+    let id = id_generator.real_time_generate();
+
+    assert!(lazy_id < id);
+    assert_eq!(Layout::SECONDS.decode(id).machine_bits, 40000);
+    assert!(Layout::SECONDS.decode(lazy_id).timestamp >= snowflake::get_time_millis() / 1000 - 1);
+}
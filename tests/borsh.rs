@@ -0,0 +1,61 @@
+#![cfg(feature = "borsh")]
+
+use snowflake::{Layout, Snowflake, Snowflake128, SnowflakeId, TypedId};
+
+struct User;
+
+#[test]
+fn test_id() {
+    let id = SnowflakeId(6725650666386976769);
+    let bytes = borsh::to_vec(&id).unwrap();
+
+    assert_eq!(bytes, borsh::to_vec(&id.0).unwrap());
+    assert_eq!(borsh::from_slice::<SnowflakeId>(&bytes).unwrap(), id);
+}
+
+#[test]
+fn test_typed_id() {
+    let user = TypedId::<User>::from(6725650666386976769);
+    let bytes = borsh::to_vec(&user).unwrap();
+
+    assert_eq!(bytes, borsh::to_vec(&user.id()).unwrap());
+    assert_eq!(borsh::from_slice::<TypedId<User>>(&bytes).unwrap(), user);
+}
+
+#[test]
+fn test_snowflake() {
+    let decoded = Layout::default().decode(6725650666386976769);
+    let bytes = borsh::to_vec(&decoded).unwrap();
+
+    assert_eq!(bytes.len(), 8 + 8 + 2);
+    assert_eq!(bytes[..8], decoded.timestamp.to_le_bytes());
+    assert_eq!(borsh::from_slice::<Snowflake>(&bytes).unwrap(), decoded);
+}
+
+#[test]
+fn test_snowflake128() {
+    let id = Snowflake128 {
+        timestamp_nanos: 1_700_000_000_000_000_000,
+        machine_id: 7,
+        sequence: 42,
+    };
+    let bytes = borsh::to_vec(&id).unwrap();
+
+    assert_eq!(bytes.len(), 8 + 4 + 4);
+    assert_eq!(borsh::from_slice::<Snowflake128>(&bytes).unwrap(), id);
+}
+
+#[test]
+fn test_truncated() {
+    assert!(borsh::from_slice::<SnowflakeId>(&[0; 7]).is_err());
+    assert!(borsh::from_slice::<SnowflakeId>(&[0; 9]).is_err());
+}
+
+#[test]
+fn test_vec() {
+    let ids = vec![SnowflakeId(1), SnowflakeId(2)];
+    let bytes = borsh::to_vec(&ids).unwrap();
+
+    assert_eq!(bytes.len(), 4 + 2 * 8);
+    assert_eq!(borsh::from_slice::<Vec<SnowflakeId>>(&bytes).unwrap(), ids);
+}
@@ -0,0 +1,42 @@
+use snowflake::{BoundaryFlake, BoundaryFlakeGenerator};
+
+#[test]
+fn test_boundary_flake_generate() {
+    let mac_address = [0x02, 0x42, 0xac, 0x12, 0x00, 0x02];
+    let mut id_generator = BoundaryFlakeGenerator::new(mac_address);
+
+    let ids: Vec<BoundaryFlake> = (0..10000).map(|_| id_generator.generate()).collect();
+
+    let mut sorted = ids.clone();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(sorted, ids);
+
+    for id in ids {
+        assert_eq!(id.mac_address(), mac_address);
+        assert_eq!(BoundaryFlake::from_bytes(id.to_bytes()), id);
+        assert_eq!(BoundaryFlake::from_base62(&id.to_base62()), Some(id));
+    }
+}
+
+#[test]
+fn test_boundary_flake_base62() {
+    let id = BoundaryFlake {
+        timestamp: 1_372_457_220_124,
+        worker_id: 0x0242_ac12_0002,
+        sequence: 7,
+    };
+
+    assert_eq!(id.to_base62(), "8Z4JqYYeCScEGrV4fn");
+    assert_eq!(BoundaryFlake::from_base62("8Z4JqYYeCScEGrV4fn"), Some(id));
+    assert_eq!(
+        BoundaryFlake::from_base62("00008Z4JqYYeCScEGrV4fn"),
+        Some(id)
+    );
+    assert_eq!(BoundaryFlake::from_u128(0).to_base62(), "0");
+
+    assert_eq!(BoundaryFlake::from_base62(""), None);
+    assert_eq!(BoundaryFlake::from_base62("8Z4JqYYeCScEGrV4f-"), None);
+    assert_eq!(BoundaryFlake::from_base62("zzzzzzzzzzzzzzzzzzzzzz"), None);
+    assert_eq!(BoundaryFlake::from_base62("000008Z4JqYYeCScEGrV4fn"), None);
+}
@@ -0,0 +1,76 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use snowflake::{get_time_millis, BufferedGenerator, IdGenerator, Layout, SnowflakeIdGenerator};
+
+#[test]
+fn test_buffered_generate() {
+    let buffered = BufferedGenerator::spawn(SnowflakeIdGenerator::new(1, 1), 1000);
+
+    let ids: Vec<i64> = (0..10_000).map(|_| buffered.generate()).collect();
+
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    let stats = buffered.stats();
+    assert_eq!(stats.issued, 10_000);
+    assert!(stats.buffered <= 2000);
+}
+
+#[test]
+fn test_buffered_starts_after_generator() {
+    let layout = Layout::new(41, 10, 2);
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1).with_layout(layout);
+    let last = id_generator.real_time_generate();
+
+    let mut buffered = BufferedGenerator::spawn(id_generator, 3);
+    let ids: Vec<i64> = (0..10).map(|_| buffered.next_id().unwrap().0).collect();
+
+    assert!(last < ids[0]);
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn test_buffered_across_threads() {
+    let buffered = Arc::new(BufferedGenerator::spawn(
+        SnowflakeIdGenerator::new(1, 1),
+        512,
+    ));
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let buffered = Arc::clone(&buffered);
+            thread::spawn(move || (0..5000).map(|_| buffered.generate()).collect::<Vec<_>>())
+        })
+        .collect();
+
+    let ids: HashSet<i64> = handles
+        .into_iter()
+        .flat_map(|handle| handle.join().unwrap())
+        .collect();
+    assert_eq!(ids.len(), 20_000);
+}
+
+#[test]
+fn test_buffered_stays_close_to_clock() {
+    let layout = Layout::new(41, 10, 2);
+    let buffered = BufferedGenerator::spawn(SnowflakeIdGenerator::new(1, 1).with_layout(layout), 8);
+
+    for _ in 0..2000 {
+        let id = buffered.generate();
+        // Two buffers of two time units each, at most.
+        assert!(layout.decode(id).timestamp <= get_time_millis() + 4);
+    }
+}
+
+#[test]
+fn test_buffered_replaces_stale_ids() {
+    let buffered = BufferedGenerator::spawn(SnowflakeIdGenerator::new(1, 1), 16);
+    let first = buffered.generate();
+
+    thread::sleep(Duration::from_millis(300));
+    let id = buffered.generate();
+
+    assert!(first < id);
+    assert!(Layout::default().decode(id).timestamp >= get_time_millis() - 150);
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "chrono")]
+
+use chrono::{Duration, TimeZone, Utc};
+use snowflake::{SnowflakeId, SnowflakeIdGenerator};
+
+#[test]
+fn test_time_arithmetic() {
+    let ip = "102.65.2.123".to_string();
+    let mut id_generator = SnowflakeIdGenerator::new_from_ip(ip);
+    let id = SnowflakeId(id_generator.real_time_generate());
+
+    let earlier = id.offset(Duration::hours(-1));
+    assert_eq!(earlier.timestamp(), id.timestamp() - 3_600_000);
+    assert_eq!(earlier.machine_bits(), id.machine_bits());
+    assert_eq!(earlier.idx(), id.idx());
+    assert!(earlier < id);
+
+    let moved = id.with_timestamp(Utc.timestamp_millis_opt(id.timestamp()).unwrap());
+    assert_eq!(moved, id);
+
+    assert!(id.floor() <= id);
+    assert_eq!(id.floor().timestamp(), id.timestamp());
+    assert_eq!(id.floor().machine_bits(), 0);
+}
+
+#[test]
+fn test_time_between() {
+    let first = SnowflakeId(1_700_000_000_000 << 22 | 1 << 12);
+    let later = first.offset(Duration::minutes(5));
+
+    assert_eq!(
+        SnowflakeId::time_between(first, later),
+        Duration::minutes(5)
+    );
+    assert_eq!(
+        SnowflakeId::time_between(later, first),
+        Duration::minutes(-5)
+    );
+}
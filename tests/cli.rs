@@ -0,0 +1,115 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use snowflake::Layout;
+
+fn snowflake() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_snowflake"))
+}
+
+#[test]
+fn test_generate() {
+    let output = snowflake()
+        .args(["generate", "3", "--machine-id", "7"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let ids: Vec<i64> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| line.parse().unwrap())
+        .collect();
+    assert_eq!(ids.len(), 3);
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(ids
+        .iter()
+        .all(|&id| Layout::default().decode(id).machine_bits == 7));
+}
+
+#[test]
+fn test_decode_stdin() {
+    let mut child = snowflake()
+        .args(["decode", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"7202640023251480593\n\nnot-an-id\n4194305\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "7202640023251480593\t1717243200123\t679\t17\n4194305\t1\t0\t1\n"
+    );
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("invalid id: not-an-id"));
+}
+
+#[test]
+fn test_decode_args() {
+    let output = snowflake().args(["decode", "4194305"]).output().unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "4194305\t1\t0\t1\n"
+    );
+}
+
+#[test]
+fn test_decode_formats() {
+    let csv = snowflake()
+        .args(["decode", "--format", "csv", "4194305"])
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(csv.stdout).unwrap(),
+        "id,timestamp,machine,sequence\n4194305,1,0,1\n"
+    );
+
+    let json = snowflake()
+        .args(["decode", "4194305", "--format=json"])
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(json.stdout).unwrap(),
+        "{\"id\":\"4194305\",\"timestamp\":1,\"machine\":0,\"sequence\":1}\n"
+    );
+
+    let invalid = snowflake()
+        .args(["decode", "--format", "xml", "4194305"])
+        .output()
+        .unwrap();
+    assert!(!invalid.status.success());
+}
+
+#[test]
+fn test_generate_formats() {
+    let output = snowflake()
+        .args(["generate", "2", "--format", "json"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(stdout.lines().count(), 2);
+    assert!(stdout
+        .lines()
+        .all(|line| line.starts_with("{\"id\":\"") && line.ends_with("\"}")));
+
+    let output = snowflake()
+        .args(["generate", "--format", "csv"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .starts_with("id\n"));
+}
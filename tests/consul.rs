@@ -0,0 +1,138 @@
+#![cfg(feature = "consul")]
+
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use snowflake::{ConsulAllocator, LeaseKeeper, SnowflakeError};
+
+#[derive(Default)]
+struct Consul {
+    sessions: Vec<String>,
+    locks: HashMap<String, String>,
+}
+
+// Answer the session and KV endpoints like a Consul agent, until the test
+// process exits.
+fn serve_consul() -> (SocketAddr, Arc<Mutex<Consul>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = listener.local_addr().unwrap();
+    let state = Arc::new(Mutex::new(Consul::default()));
+    let consul = Arc::clone(&state);
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut buffer = [0u8; 2048];
+            let read = stream.read(&mut buffer).unwrap();
+            let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+            let path = request.split(' ').nth(1).unwrap().to_string();
+
+            let mut consul = consul.lock().unwrap();
+            let (status, body) = if path == "/v1/session/create" {
+                let session = format!("session-{}", consul.sessions.len());
+                consul.sessions.push(session.clone());
+                ("200 OK", format!(r#"{{"ID": "{}"}}"#, session))
+            } else if let Some(session) = path.strip_prefix("/v1/session/renew/") {
+                if consul.sessions.iter().any(|known| known == session) {
+                    ("200 OK", "[]".to_string())
+                } else {
+                    ("404 Not Found", String::new())
+                }
+            } else if let Some(session) = path.strip_prefix("/v1/session/destroy/") {
+                let session = session.to_string();
+                consul.sessions.retain(|known| *known != session);
+                consul.locks.retain(|_, holder| *holder != session);
+                ("200 OK", "true".to_string())
+            } else if let Some(rest) = path.strip_prefix("/v1/kv/") {
+                let (key, query) = rest.split_once('?').unwrap();
+                let (action, session) = query.split_once('=').unwrap();
+                let holder = consul.locks.get(key).cloned();
+
+                let done = match (action, holder) {
+                    ("acquire", None) => {
+                        consul.locks.insert(key.to_string(), session.to_string());
+                        true
+                    }
+                    ("acquire", Some(holder)) => holder == session,
+                    ("release", Some(ref holder)) if holder == session => {
+                        consul.locks.remove(key);
+                        true
+                    }
+                    _ => false,
+                };
+                ("200 OK", done.to_string())
+            } else {
+                ("404 Not Found", String::new())
+            };
+
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+    });
+
+    (endpoint, state)
+}
+
+#[test]
+fn test_consul_leases() {
+    let (endpoint, consul) = serve_consul();
+    let allocator = ConsulAllocator::new(endpoint, 0..2).with_prefix("/ids/");
+
+    let first = allocator.acquire().unwrap();
+    let second = allocator.acquire().unwrap();
+    assert_eq!(first.machine_id(), 0);
+    assert_eq!(second.machine_id(), 1);
+    assert_ne!(first.session(), second.session());
+    assert_eq!(consul.lock().unwrap().locks["ids/1"], second.session());
+
+    let full = allocator.acquire().unwrap_err();
+    assert_eq!(full.kind(), ErrorKind::AddrInUse);
+    assert_eq!(consul.lock().unwrap().sessions.len(), 2);
+
+    first.renew().unwrap();
+    drop(first);
+    assert_eq!(allocator.acquire().unwrap().machine_id(), 0);
+}
+
+#[test]
+fn test_consul_expired_session() {
+    let (endpoint, consul) = serve_consul();
+    let lease = ConsulAllocator::new(endpoint, 0..4).acquire().unwrap();
+    let mut id_generator = lease.generator();
+
+    assert_eq!(id_generator.machine_id(), 0);
+    id_generator.generate().unwrap();
+
+    consul.lock().unwrap().sessions.clear();
+    assert!(lease.renew().is_err());
+    assert_eq!(id_generator.generate(), Err(SnowflakeError::LeaseLost(0)));
+}
+
+#[test]
+fn test_consul_lease_keeper() {
+    let (endpoint, consul) = serve_consul();
+    let lease = ConsulAllocator::new(endpoint, 0..4).acquire().unwrap();
+
+    let (keeper, lost) = LeaseKeeper::spawn(lease, Duration::from_millis(10));
+    std::thread::sleep(Duration::from_millis(50));
+    assert!(!keeper.is_lost());
+
+    consul.lock().unwrap().sessions.clear();
+    assert_eq!(
+        lost.recv_timeout(Duration::from_secs(5))
+            .unwrap()
+            .machine_id,
+        0
+    );
+    assert!(keeper.is_lost());
+}
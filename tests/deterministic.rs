@@ -0,0 +1,31 @@
+use snowflake::{DeterministicGenerator, SnowflakeId};
+
+#[test]
+fn test_deterministic_sequence() {
+    let mut id_generator = DeterministicGenerator::with_seed(42);
+    let ids: Vec<i64> = (0..5).map(|_| id_generator.generate()).collect();
+
+    assert_eq!(
+        ids,
+        vec![
+            6725650666386976769,
+            6725650666386976770,
+            6725650666386976771,
+            6725650666386976772,
+            6725650666386976773,
+        ]
+    );
+
+    let mut other = DeterministicGenerator::with_seed(43);
+    assert_ne!(other.generate(), ids[0]);
+}
+
+#[test]
+fn test_deterministic_clock_advances() {
+    let mut id_generator = DeterministicGenerator::with_seed(42);
+    let first = SnowflakeId(id_generator.generate());
+
+    let last = (0..10000).map(|_| id_generator.generate()).last().unwrap();
+
+    assert!(SnowflakeId(last).timestamp() > first.timestamp());
+}
@@ -0,0 +1,66 @@
+use snowflake::{Entity, EntityRegistry, Layout, SnowflakeIdGenerator, TypedId};
+
+struct User;
+struct Order;
+struct Invoice;
+
+impl Entity for User {
+    const TAG: u64 = 1;
+}
+
+impl Entity for Order {
+    const TAG: u64 = 2;
+
+    fn name() -> &'static str {
+        "order"
+    }
+}
+
+impl Entity for Invoice {
+    const TAG: u64 = 1;
+}
+
+fn layout() -> Layout {
+    Layout::default().with_tag_bits(3)
+}
+
+#[test]
+fn test_decode_any() {
+    let registry = EntityRegistry::new(layout())
+        .register::<User>()
+        .register::<Order>();
+    let mut id_generator = SnowflakeIdGenerator::new(0, 7).with_layout(layout());
+
+    let user = TypedId::<User>::generate(&mut id_generator).unwrap();
+    let order = TypedId::<Order>::generate(&mut id_generator).unwrap();
+
+    let kind = registry.decode_any(user.get()).unwrap();
+    assert!(kind.is::<User>());
+    assert!(!kind.is::<Order>());
+    assert_eq!(kind.tag(), 1);
+    assert!(kind.name().ends_with("User"));
+
+    let kind = registry.decode_any(order.get()).unwrap();
+    assert!(kind.is::<Order>());
+    assert_eq!(kind.name(), "order");
+
+    assert_eq!(registry.decode::<Order>(order.get()), Some(order));
+    assert_eq!(registry.decode::<Order>(user.get()), None);
+
+    let untagged = id_generator.generate_tagged(5).unwrap();
+    assert_eq!(registry.decode_any(untagged.0), None);
+}
+
+#[test]
+#[should_panic(expected = "share tag 1")]
+fn test_register_duplicate_tag() {
+    EntityRegistry::new(layout())
+        .register::<User>()
+        .register::<Invoice>();
+}
+
+#[test]
+#[should_panic(expected = "doesn't fit the tag bits")]
+fn test_register_tag_out_of_range() {
+    EntityRegistry::new(Layout::default()).register::<Order>();
+}
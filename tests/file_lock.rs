@@ -0,0 +1,56 @@
+use std::fs;
+use std::io::ErrorKind;
+
+use snowflake::{FileLockAllocator, Layout, SnowflakeError};
+
+#[test]
+fn test_file_lock_leases() {
+    let directory = std::env::temp_dir().join(format!("snowflake-slots-{}", std::process::id()));
+    let allocator = FileLockAllocator::new(&directory, 0..2);
+
+    let first = allocator.acquire().unwrap();
+    let second = allocator.acquire().unwrap();
+    assert_eq!(first.machine_id(), 0);
+    assert_eq!(second.machine_id(), 1);
+    assert_eq!(
+        fs::read_to_string(second.path()).unwrap().trim(),
+        std::process::id().to_string()
+    );
+    assert_eq!(
+        allocator.acquire().unwrap_err().kind(),
+        ErrorKind::AddrInUse
+    );
+
+    let mut id_generator = second.generator();
+    assert_eq!(id_generator.machine_id(), 1);
+    id_generator.generate().unwrap();
+
+    drop(first);
+    assert_eq!(allocator.acquire().unwrap().machine_id(), 0);
+
+    drop(second);
+    assert_eq!(id_generator.generate(), Err(SnowflakeError::LeaseLost(1)));
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_file_lock_deleted_slot() {
+    let directory = std::env::temp_dir().join(format!("snowflake-deleted-{}", std::process::id()));
+    let mut lease = FileLockAllocator::new(&directory, 0..2).acquire().unwrap();
+    let mut id_generator = lease.generator();
+
+    lease.renew().unwrap();
+    fs::remove_file(lease.path()).unwrap();
+    assert_eq!(lease.renew().unwrap_err().kind(), ErrorKind::NotFound);
+    assert_eq!(id_generator.generate(), Err(SnowflakeError::LeaseLost(0)));
+
+    drop(lease);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "don't fit the layout")]
+fn test_file_lock_rejects_machine_ids_past_layout() {
+    FileLockAllocator::new(std::env::temp_dir(), 0..32).with_layout(Layout::JS_SAFE);
+}
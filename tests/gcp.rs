@@ -0,0 +1,82 @@
+#![cfg(feature = "gcp")]
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::thread;
+
+use snowflake::{GcpMetadataResolver, InstanceKey, Layout, WorkerIdResolver};
+
+// Serve `requests` connections, answering like the GCE metadata server.
+fn serve_metadata(requests: usize) -> (SocketAddr, thread::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = listener.local_addr().unwrap();
+
+    let handle = thread::spawn(move || {
+        for stream in listener.incoming().take(requests) {
+            let mut stream = stream.unwrap();
+            let mut buffer = [0u8; 1024];
+            let read = stream.read(&mut buffer).unwrap();
+            let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+
+            let (status, body) = if !request.contains("Metadata-Flavor: Google") {
+                ("403 Forbidden", "")
+            } else if request.starts_with("GET /computeMetadata/v1/instance/id ") {
+                ("200 OK", "4520031799277581759")
+            } else if request
+                .starts_with("GET /computeMetadata/v1/instance/network-interfaces/0/ip ")
+            {
+                ("200 OK", "10.128.0.7")
+            } else {
+                ("404 Not Found", "")
+            };
+            // The metadata server streams its answers.
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nMetadata-Flavor: Google\r\nTransfer-Encoding: chunked\r\n\r\n\
+                 {:x}\r\n{}\r\n0\r\n\r\n",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+    });
+
+    (endpoint, handle)
+}
+
+#[test]
+fn test_gcp_instance_id() {
+    let (endpoint, server) = serve_metadata(1);
+    let resolver = GcpMetadataResolver::new().with_endpoint(endpoint);
+
+    assert_eq!(resolver.instance_key().unwrap(), "4520031799277581759");
+    let machine_bits = resolver.machine_bits(Layout::default()).unwrap();
+    assert!(machine_bits < 1 << 10);
+    assert_eq!(
+        resolver.machine_bits(Layout::default()).unwrap(),
+        machine_bits
+    );
+    server.join().unwrap();
+}
+
+#[test]
+fn test_gcp_internal_ip() {
+    let (endpoint, server) = serve_metadata(1);
+    let resolver = GcpMetadataResolver::new()
+        .with_endpoint(endpoint)
+        .with_key(InstanceKey::PrivateIp);
+
+    assert_eq!(resolver.instance_key().unwrap(), "10.128.0.7");
+    server.join().unwrap();
+}
+
+#[test]
+fn test_gcp_unreachable() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = listener.local_addr().unwrap();
+    drop(listener);
+
+    let resolver = GcpMetadataResolver::new().with_endpoint(endpoint);
+    assert!(resolver.machine_bits(Layout::default()).is_err());
+}
@@ -0,0 +1,25 @@
+use std::thread;
+
+use snowflake::{snowflake_id, SnowflakeError, SnowflakeIdGenerator};
+
+#[test]
+fn test_global_generator() {
+    snowflake::init(SnowflakeIdGenerator::new(1, 7)).unwrap();
+    assert_eq!(
+        snowflake::init(SnowflakeIdGenerator::new(1, 8)),
+        Err(SnowflakeError::AlreadyInitialized)
+    );
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| thread::spawn(|| (0..10000).map(|_| snowflake_id!()).collect::<Vec<_>>()))
+        .collect();
+
+    let mut ids: Vec<i64> = handles
+        .into_iter()
+        .flat_map(|handle| handle.join().unwrap())
+        .collect();
+
+    ids.sort();
+    ids.dedup();
+    assert_eq!(40000, ids.len());
+}
@@ -0,0 +1,84 @@
+use std::net::UdpSocket;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use snowflake::{DuplicateWorker, GossipDetector};
+
+// Two detectors on the loopback interface announcing to each other.
+fn spawn_pair(
+    first_id: i64,
+    second_id: i64,
+) -> (
+    GossipDetector,
+    GossipDetector,
+    mpsc::Receiver<DuplicateWorker>,
+) {
+    let first_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let second_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let first_addr = first_socket.local_addr().unwrap();
+    let second_addr = second_socket.local_addr().unwrap();
+    let (duplicates, received) = mpsc::channel();
+    let interval = Duration::from_millis(20);
+
+    let first = {
+        let duplicates = duplicates.clone();
+        GossipDetector::spawn(
+            first_id,
+            first_socket,
+            second_addr,
+            interval,
+            move |duplicate| {
+                let _ = duplicates.send(duplicate);
+            },
+        )
+        .unwrap()
+    };
+    let second = GossipDetector::spawn(
+        second_id,
+        second_socket,
+        first_addr,
+        interval,
+        move |duplicate| {
+            let _ = duplicates.send(duplicate);
+        },
+    )
+    .unwrap();
+
+    (first, second, received)
+}
+
+#[test]
+fn test_gossip_detects_duplicates() {
+    let (first, second, duplicates) = spawn_pair(42, 42);
+
+    let duplicate = duplicates.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(duplicate.machine_id, 42);
+    assert!(duplicate.peer == first.local_addr() || duplicate.peer == second.local_addr());
+}
+
+#[test]
+fn test_gossip_ignores_distinct_ids() {
+    let (_first, _second, duplicates) = spawn_pair(42, 43);
+
+    assert!(duplicates.recv_timeout(Duration::from_millis(200)).is_err());
+}
+
+#[test]
+fn test_gossip_ignores_own_announcements() {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let target = socket.local_addr().unwrap();
+    let (duplicates, received) = mpsc::channel();
+
+    let _detector = GossipDetector::spawn(
+        42,
+        socket,
+        target,
+        Duration::from_millis(20),
+        move |duplicate| {
+            let _ = duplicates.send(duplicate);
+        },
+    )
+    .unwrap();
+
+    assert!(received.recv_timeout(Duration::from_millis(200)).is_err());
+}
@@ -0,0 +1,62 @@
+use std::time::{Duration, SystemTime};
+
+use snowflake::{
+    FileLockAllocator, Health, LeaseKeeper, LeaseStatus, SharedGenerator, SnowflakeIdGenerator,
+};
+
+#[test]
+fn test_health_of_fresh_generator() {
+    let health = SnowflakeIdGenerator::new(1, 1).health();
+
+    assert_eq!(health.clock_skew_millis, None);
+    assert_eq!(health.since_last_generation, None);
+    assert_eq!(health.lease, LeaseStatus::Unmanaged);
+    assert_eq!(health.buffered, 0);
+    assert!(health.is_ready());
+    assert!(health
+        .to_string()
+        .contains("clock_skew_ms=unknown ahead_of_clock_ms="));
+}
+
+#[test]
+fn test_health_after_generation() {
+    let shared = SharedGenerator::new(SnowflakeIdGenerator::new(1, 1));
+    shared.generate();
+
+    let health = shared.health();
+    assert!(health.since_last_generation.unwrap() < Duration::from_secs(1));
+    assert!(health.ahead_of_clock_millis <= 0);
+}
+
+#[test]
+fn test_health_not_ready() {
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    id_generator.real_time_generate();
+
+    let skewed = id_generator
+        .health()
+        .with_reference(SystemTime::now() + Duration::from_secs(5));
+    assert!(skewed.clock_skew_millis.unwrap() <= -4900);
+    assert!(!skewed.is_ready());
+    assert!(skewed.to_string().starts_with("status=not_ready "));
+
+    let lost: Health = id_generator.health().with_lease(LeaseStatus::Lost);
+    assert!(!lost.is_ready());
+    assert!(lost.to_string().contains(" lease=lost "));
+
+    id_generator.last_time_millis += 10_000;
+    assert!(!id_generator.health().is_ready());
+}
+
+#[test]
+fn test_health_with_lease_keeper() {
+    let directory = std::env::temp_dir().join(format!("snowflake-health-{}", std::process::id()));
+    let lease = FileLockAllocator::new(&directory, 0..4).acquire().unwrap();
+    let (keeper, _lost) = LeaseKeeper::spawn(lease, Duration::from_secs(60));
+
+    let health = keeper.generator().health();
+    assert_eq!(health.lease, LeaseStatus::Held);
+
+    drop(keeper);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
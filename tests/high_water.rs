@@ -0,0 +1,101 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use snowflake::{
+    get_time_millis, wait_until_clock_reaches, wait_until_clock_reaches_with, HighWaterMarkFile,
+    SnowflakeError, SnowflakeIdGenerator,
+};
+
+fn mark_file(name: &str) -> HighWaterMarkFile {
+    let path = std::env::temp_dir().join(format!("snowflake-{}-{}", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    HighWaterMarkFile::new(path)
+}
+
+#[test]
+fn test_high_water_mark_file_round_trip() {
+    let mark = mark_file("round-trip");
+    assert_eq!(mark.load().unwrap(), None);
+
+    mark.save(1_700_000_000_000).unwrap();
+    assert_eq!(mark.load().unwrap(), Some(1_700_000_000_000));
+
+    mark.save(1_700_000_000_001).unwrap();
+    assert_eq!(mark.load().unwrap(), Some(1_700_000_000_001));
+
+    fs::write(mark.path(), "garbage").unwrap();
+    assert_eq!(mark.load().unwrap_err().kind(), ErrorKind::InvalidData);
+    fs::remove_file(mark.path()).unwrap();
+}
+
+#[test]
+fn test_resume_after_past_mark() {
+    let mark_millis = get_time_millis() - 1000;
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1)
+        .resume_after(mark_millis)
+        .unwrap();
+
+    let id = id_generator.real_time_generate();
+    assert!(id_generator.reverse(id as u64).timestamp > mark_millis);
+    assert!(id_generator.high_water_millis() > mark_millis);
+}
+
+#[test]
+fn test_resume_after_refuses_mark_ahead_of_clock() {
+    let mark_millis = get_time_millis() + 60_000;
+
+    match SnowflakeIdGenerator::new(1, 1).resume_after(mark_millis) {
+        Err(SnowflakeError::ClockMovedBackwards {
+            last_millis,
+            now_millis,
+        }) => {
+            assert_eq!(last_millis, mark_millis);
+            assert!(now_millis < mark_millis);
+        }
+        result => panic!("unexpected {:?}", result),
+    }
+
+    assert!(SnowflakeIdGenerator::new(1, 1)
+        .resume_after_waiting(mark_millis, Duration::from_millis(10))
+        .is_err());
+}
+
+#[test]
+fn test_resume_after_waiting_for_clock() {
+    let mark_millis = get_time_millis() + 50;
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1)
+        .resume_after_waiting(mark_millis, Duration::from_secs(5))
+        .unwrap();
+
+    assert!(get_time_millis() > mark_millis);
+    let id = id_generator.real_time_generate();
+    assert!(id_generator.reverse(id as u64).timestamp > mark_millis);
+}
+
+#[test]
+fn test_wait_until_clock_reaches() {
+    let target_millis = get_time_millis() + 250;
+    let mut reports = Vec::new();
+
+    let now_millis = wait_until_clock_reaches_with(target_millis, Duration::from_secs(5), |left| {
+        reports.push(left)
+    })
+    .unwrap();
+
+    assert!(now_millis >= target_millis);
+    assert!(reports.len() >= 2);
+    assert!(reports[0] <= Duration::from_millis(250));
+    assert!(reports.windows(2).all(|pair| pair[0] > pair[1]));
+}
+
+#[test]
+fn test_wait_until_clock_reaches_gives_up() {
+    let target_millis = get_time_millis() + 60_000;
+
+    assert!(matches!(
+        wait_until_clock_reaches(target_millis, Duration::from_secs(1)),
+        Err(SnowflakeError::ClockMovedBackwards { last_millis, .. }) if last_millis == target_millis
+    ));
+    assert!(wait_until_clock_reaches(get_time_millis(), Duration::ZERO).is_ok());
+}
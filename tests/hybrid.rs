@@ -0,0 +1,59 @@
+use std::ops::Range;
+
+use snowflake::{get_time_millis, HybridGenerator, SegmentStore, SnowflakeIdGenerator};
+
+struct Sequence {
+    next: i64,
+}
+
+impl SegmentStore for Sequence {
+    type Error = &'static str;
+
+    fn next_segment(&mut self, size: u64) -> Result<Range<i64>, Self::Error> {
+        let start = self.next;
+        self.next += size as i64;
+        Ok(start..self.next)
+    }
+}
+
+#[test]
+fn test_hybrid_falls_back_while_clock_is_behind() {
+    let store = Sequence { next: 1 };
+    let mut hybrid = HybridGenerator::new(SnowflakeIdGenerator::new(1, 1), store, 3);
+
+    let snowflake = hybrid.generate().unwrap();
+    assert!(snowflake > 1 << 22);
+    assert_eq!(hybrid.segment_remaining(), 3);
+
+    // The clock jumps ten seconds back.
+    hybrid.generator_mut().last_time_millis = get_time_millis() + 10_000;
+    assert!(!hybrid.is_clock_healthy());
+
+    let fallback: Vec<i64> = (0..5).map(|_| hybrid.generate().unwrap()).collect();
+    assert_eq!(fallback, vec![1, 2, 3, 4, 5]);
+    assert_eq!(hybrid.segment_remaining(), 1);
+
+    hybrid.generator_mut().last_time_millis = get_time_millis();
+    assert!(hybrid.generate().unwrap() > snowflake);
+}
+
+#[test]
+fn test_hybrid_surfaces_store_errors() {
+    struct Unavailable;
+
+    impl SegmentStore for Unavailable {
+        type Error = &'static str;
+
+        fn next_segment(&mut self, _: u64) -> Result<Range<i64>, Self::Error> {
+            Err("unavailable")
+        }
+    }
+
+    let mut hybrid = HybridGenerator::new(SnowflakeIdGenerator::new(1, 1), Unavailable, 10);
+
+    // The prefetch failing doesn't keep the clock from serving.
+    assert!(hybrid.generate().unwrap() > 1 << 22);
+
+    hybrid.generator_mut().last_time_millis = get_time_millis() + 10_000;
+    assert_eq!(hybrid.generate(), Err("unavailable"));
+}
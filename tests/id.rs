@@ -0,0 +1,257 @@
+use std::convert::TryFrom;
+use std::time::{Duration, UNIX_EPOCH};
+
+use snowflake::{
+    Granularity, PartitionStrategy, Snowflake, SnowflakeError, SnowflakeId, SnowflakeIdGenerator,
+    ValidationError,
+};
+
+#[test]
+fn test_partition_key() {
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    let mut counts = [0; 8];
+
+    for _ in 0..8000 {
+        let id = SnowflakeId(id_generator.real_time_generate());
+        assert_eq!(id.partition_key(8), id.partition_key(8));
+        assert_eq!(
+            id.partition_key_with(8, PartitionStrategy::MachineBits),
+            33 % 8
+        );
+        counts[id.partition_key(8) as usize] += 1;
+    }
+
+    // Every partition gets its share, give or take.
+    assert!(counts.iter().all(|&count| count > 800), "{:?}", counts);
+}
+
+#[test]
+fn test_base62() {
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    let ids: Vec<SnowflakeId> = (0..1000)
+        .map(|_| SnowflakeId(id_generator.real_time_generate()))
+        .collect();
+
+    let encoded: Vec<String> = ids.iter().map(|id| id.to_base62()).collect();
+    assert!(encoded.windows(2).all(|pair| pair[0] < pair[1]));
+    for (id, text) in ids.iter().zip(&encoded) {
+        assert_eq!(SnowflakeId::from_base62(text), Some(*id));
+    }
+
+    assert_eq!(SnowflakeId(0).to_base62(), "00000000000");
+    assert_eq!(
+        SnowflakeId::from_base62(&SnowflakeId(i64::MAX).to_base62()),
+        Some(SnowflakeId(i64::MAX))
+    );
+    assert_eq!(SnowflakeId::from_base62("zzzzzzzzzzz"), None);
+    assert_eq!(SnowflakeId::from_base62("AzL8n0Y58m8"), None);
+    assert_eq!(SnowflakeId::from_base62(""), None);
+}
+
+#[test]
+fn test_base58() {
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    let ids: Vec<SnowflakeId> = (0..1000)
+        .map(|_| SnowflakeId(id_generator.real_time_generate()))
+        .collect();
+
+    let encoded: Vec<String> = ids.iter().map(|id| id.to_base58()).collect();
+    assert!(encoded.windows(2).all(|pair| pair[0] < pair[1]));
+    for (id, text) in ids.iter().zip(&encoded) {
+        assert!(!text.contains(|c| "0OIl".contains(c)));
+        assert_eq!(SnowflakeId::from_base58(text), Some(*id));
+    }
+
+    assert_eq!(SnowflakeId(0).to_base58(), "11111111111");
+    assert_eq!(SnowflakeId(i64::MAX).to_base58(), "NQm6nKp8qFC");
+    assert_eq!(SnowflakeId::from_base58("NQm6nKp8qFC"), Some(SnowflakeId(i64::MAX)));
+    assert_eq!(SnowflakeId::from_base58("NQm6nKp8qFD"), None);
+    assert_eq!(SnowflakeId::from_base58("GcVGHK4uX0c"), None);
+    assert_eq!(SnowflakeId::from_base58(""), None);
+}
+
+#[test]
+fn test_base64url() {
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    for _ in 0..1000 {
+        let id = SnowflakeId(id_generator.real_time_generate());
+        assert_eq!(SnowflakeId::from_base64url(&id.to_base64url()), Some(id));
+    }
+
+    assert_eq!(SnowflakeId(0).to_base64url(), "AAAAAAAAAAA");
+    assert_eq!(SnowflakeId(i64::MAX).to_base64url(), "f_________8");
+    assert_eq!(SnowflakeId::from_base64url("f_________8"), Some(SnowflakeId(i64::MAX)));
+
+    // Sign bit, unused bits, padding, trailing garbage, standard base64.
+    assert_eq!(SnowflakeId::from_base64url("gAAAAAAAAAA"), None);
+    assert_eq!(SnowflakeId::from_base64url("XVZPpaVdcAF"), None);
+    assert_eq!(SnowflakeId::from_base64url("XVZPpaVdcAE="), None);
+    assert_eq!(SnowflakeId::from_base64url("XVZPpaVdcAEx"), None);
+    assert_eq!(SnowflakeId::from_base64url("XVZPpaVdc/E"), None);
+    assert_eq!(SnowflakeId::from_base64url("XVZPpaVdcA"), None);
+}
+
+#[test]
+fn test_created_before_after() {
+    let id = SnowflakeId(1_700_000_000_000 << 22 | 42);
+    let at = |millis| UNIX_EPOCH + Duration::from_millis(millis);
+
+    assert!(id.created_before(at(1_700_000_000_001)));
+    assert!(!id.created_before(at(1_700_000_000_000)));
+    assert!(id.created_after(at(1_699_999_999_999)));
+    assert!(!id.created_after(at(1_700_000_000_000)));
+    assert!(id.created_between(at(1_700_000_000_000), at(1_700_000_000_001)));
+    assert!(!id.created_between(at(1_699_000_000_000), at(1_700_000_000_000)));
+
+    assert!(!id.created_within(Duration::from_secs(3600)));
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    assert!(SnowflakeId(id_generator.real_time_generate()).created_within(Duration::from_secs(1)));
+}
+
+#[test]
+fn test_time_between() {
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    let first = SnowflakeId(id_generator.real_time_generate());
+    let later = first.offset(Duration::from_millis(250));
+
+    assert_eq!(SnowflakeId::millis_between(first, later), 250);
+    assert_eq!(SnowflakeId::millis_between(later, first), -250);
+    assert!(first.elapsed() < Duration::from_secs(1));
+    assert_eq!(
+        later.offset(Duration::from_secs(3600)).elapsed(),
+        Duration::from_secs(0)
+    );
+}
+
+#[test]
+fn test_age() {
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    let id = SnowflakeId(id_generator.real_time_generate());
+
+    assert!(id.age() < Duration::from_secs(1));
+    assert!(SnowflakeId(id.0 - (60_000 << 22)).age() >= Duration::from_secs(60));
+    assert_eq!(
+        id.offset(Duration::from_secs(3600)).age(),
+        Duration::from_secs(0)
+    );
+}
+
+#[test]
+fn test_try_from() {
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    let raw = id_generator.real_time_generate();
+
+    let snowflake = Snowflake::try_from(raw).unwrap();
+    assert_eq!(snowflake, id_generator.reverse(raw as u64));
+    assert_eq!(Snowflake::try_from(raw as u64), Ok(snowflake));
+    assert_eq!(Snowflake::try_from(raw.to_string().as_str()), Ok(snowflake));
+
+    assert_eq!(SnowflakeId::try_from(raw as u64), Ok(SnowflakeId(raw)));
+    assert_eq!(
+        SnowflakeId::try_from(raw.to_string().as_str()),
+        Ok(SnowflakeId(raw))
+    );
+
+    let sign_bit = SnowflakeError::Invalid(ValidationError::SignBitSet);
+    assert_eq!(Snowflake::try_from(u64::MAX), Err(sign_bit));
+    assert_eq!(SnowflakeId::try_from(1u64 << 63), Err(sign_bit));
+    assert_eq!(SnowflakeId::try_new(raw), Ok(SnowflakeId(raw)));
+    assert_eq!(SnowflakeId::try_new(-5), Err(sign_bit));
+    assert_eq!(SnowflakeId::try_from("-5"), Err(SnowflakeError::ParseError));
+    assert_eq!(Snowflake::try_from("12ab"), Err(SnowflakeError::ParseError));
+    assert_eq!(
+        Snowflake::try_from("99999999999999999999"),
+        Err(SnowflakeError::ParseError)
+    );
+    assert!(matches!(
+        Snowflake::try_from(i64::MAX),
+        Err(SnowflakeError::Invalid(ValidationError::TimestampInFuture(
+            _
+        )))
+    ));
+}
+
+#[test]
+fn test_sortable_encodings() {
+    let mut ids = vec![
+        SnowflakeId(0),
+        SnowflakeId(7),
+        SnowflakeId(32),
+        SnowflakeId(1_700_000_000_000 << 22),
+        SnowflakeId(i64::MAX),
+    ];
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    ids.extend((0..100).map(|_| SnowflakeId(id_generator.real_time_generate())));
+
+    let mut decimals: Vec<_> = ids
+        .iter()
+        .map(|id| id.to_sortable_decimal().unwrap())
+        .collect();
+    let mut base32s: Vec<_> = ids.iter().map(|id| id.to_base32()).collect();
+    assert!(decimals.iter().all(|text| text.len() == 20));
+    assert!(base32s.iter().all(|text| text.len() == 13));
+
+    ids.sort();
+    decimals.sort();
+    base32s.sort();
+    for ((id, decimal), base32) in ids.iter().zip(&decimals).zip(&base32s) {
+        assert_eq!(SnowflakeId::from_sortable_decimal(decimal), Some(*id));
+        assert_eq!(SnowflakeId::from_base32(base32), Some(*id));
+    }
+
+    assert_eq!(SnowflakeId::from_sortable_decimal("42"), None);
+    assert_eq!(
+        SnowflakeId::from_sortable_decimal("18446744073709551615"),
+        None
+    );
+    assert_eq!(
+        SnowflakeId::from_sortable_decimal("-0000000000000000042"),
+        None
+    );
+    assert_eq!(SnowflakeId::from_base32("ZZZZZZZZZZZZZ"), None);
+    assert_eq!(SnowflakeId::from_base32("000000000000U"), None);
+    assert_eq!(SnowflakeId::from_base32("8000000000000"), None);
+    assert_eq!(SnowflakeId::from_base32("7ZZZZZZZZZZZ"), None);
+    assert_eq!(
+        SnowflakeId::from_base32("7ZZZZZZZZZZZZ"),
+        Some(SnowflakeId(i64::MAX))
+    );
+}
+
+#[test]
+fn test_sortable_decimal_rejects_negative_ids() {
+    assert_eq!(SnowflakeId(-42).to_sortable_decimal(), None);
+    assert_eq!(SnowflakeId(i64::MIN).to_sortable_decimal(), None);
+}
+
+#[test]
+fn test_bucket() {
+    // 2024-02-29T23:59:59.999Z and 2024-03-01T00:00:00Z.
+    let leap_day = SnowflakeId(1_709_251_199_999 << 22);
+    let march = SnowflakeId(1_709_251_200_000 << 22 | 42);
+
+    assert_eq!(leap_day.bucket(Granularity::Month, UNIX_EPOCH), 54 * 12 + 1);
+    assert_eq!(march.bucket(Granularity::Month, UNIX_EPOCH), 54 * 12 + 2);
+    assert_eq!(
+        march.bucket(Granularity::Day, UNIX_EPOCH) - leap_day.bucket(Granularity::Day, UNIX_EPOCH),
+        1
+    );
+    assert_eq!(march.bucket(Granularity::Hour, UNIX_EPOCH), 1_709_251_200 / 3600);
+    // Both fall in the week from Monday 2024-02-26.
+    assert_eq!(
+        leap_day.bucket(Granularity::Week, UNIX_EPOCH),
+        march.bucket(Granularity::Week, UNIX_EPOCH)
+    );
+    let monday = UNIX_EPOCH + Duration::from_millis(1_708_905_600_000);
+    assert_eq!(march.bucket(Granularity::Week, monday), 0);
+    assert_eq!(march.bucket(Granularity::Week, monday - Duration::from_millis(1)), 1);
+
+    // Buckets follow the calendar, not the time of day of the epoch.
+    let epoch = UNIX_EPOCH + Duration::from_millis(1_709_251_199_000);
+    assert_eq!(leap_day.bucket(Granularity::Day, epoch), 0);
+    assert_eq!(march.bucket(Granularity::Day, epoch), 1);
+    assert_eq!(march.bucket(Granularity::Month, epoch), 1);
+
+    let later = UNIX_EPOCH + Duration::from_millis(1_717_243_200_123);
+    assert_eq!(march.bucket(Granularity::Month, later), -3);
+}
@@ -0,0 +1,70 @@
+use std::ops::Range;
+
+use snowflake::{
+    DeterministicGenerator, HybridGenerator, IdGenerator, LazyGenerator, SegmentGenerator,
+    SegmentStore, SharedGenerator, Snowflake128, Snowflake128Generator, SnowflakeError,
+    SnowflakeId, SnowflakeIdGenerator, ThrottledGenerator,
+};
+
+struct Sequence(i64);
+
+impl SegmentStore for Sequence {
+    type Error = &'static str;
+
+    fn next_segment(&mut self, size: u64) -> Result<Range<i64>, Self::Error> {
+        if self.0 < 0 {
+            return Err("unavailable");
+        }
+        let start = self.0;
+        self.0 += size as i64;
+        Ok(start..self.0)
+    }
+}
+
+fn take_ids<G: IdGenerator>(mut generator: G, count: usize) -> Vec<SnowflakeId> {
+    (0..count).map(|_| generator.next_id().unwrap()).collect()
+}
+
+#[test]
+fn test_generic_over_generators() {
+    let generators: Vec<Box<dyn IdGenerator>> = vec![
+        Box::new(SnowflakeIdGenerator::new(1, 1)),
+        Box::new(SharedGenerator::new(SnowflakeIdGenerator::new(1, 2))),
+        Box::new(DeterministicGenerator::with_seed(7)),
+        Box::new(LazyGenerator(SnowflakeIdGenerator::new(1, 3))),
+        Box::new(HybridGenerator::new(
+            SnowflakeIdGenerator::new(1, 4),
+            Sequence(1),
+            100,
+        )),
+        Box::new(SegmentGenerator::new(Sequence(1), 100)),
+    ];
+
+    for generator in generators {
+        let ids = take_ids(generator, 1000);
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
+
+#[test]
+fn test_throttled_next_id() {
+    let mut throttled = ThrottledGenerator::new(SnowflakeIdGenerator::new(1, 1), 1);
+
+    assert!(throttled.next_id().is_ok());
+    assert_eq!(throttled.next_id(), Err(SnowflakeError::RateLimited));
+}
+
+#[test]
+fn test_other_id_types() {
+    let mut id_generator = Snowflake128Generator::new(42);
+
+    let first: Snowflake128 = id_generator.next_id().unwrap();
+    assert!(first < id_generator.next_id().unwrap());
+}
+
+#[test]
+fn test_segment_store_errors() {
+    let mut segments = SegmentGenerator::new(Sequence(-1), 100);
+
+    assert_eq!(segments.next_id(), Err(SnowflakeError::SegmentStoreFailed));
+}
@@ -0,0 +1,43 @@
+use snowflake::{Ksuid, Layout, SnowflakeError, SnowflakeId, SnowflakeIdGenerator};
+
+#[test]
+fn test_ksuid_round_trip() {
+    let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+
+    for _ in 0..10000 {
+        let id = SnowflakeId(id_generator.real_time_generate());
+        let ksuid: Ksuid = id.to_ksuid().unwrap().to_string().parse().unwrap();
+
+        assert_eq!(SnowflakeId::from_ksuid(ksuid), Some(id));
+    }
+}
+
+#[test]
+fn test_ksuid_before_epoch() {
+    // 2010, before the KSUID epoch.
+    let id = SnowflakeId(Layout::default().compose(1_262_304_000_000, 1, 0));
+
+    assert_eq!(
+        id.to_ksuid(),
+        Err(SnowflakeError::EpochOverflow(1_262_304_000_000))
+    );
+}
+
+#[test]
+fn test_ksuid_after_2039() {
+    // u32::MAX seconds after the KSUID epoch, in 2150.
+    let ksuid: Ksuid = "aWgEPTl1tmebfsQzFP4bxwgy80V".parse().unwrap();
+
+    assert_eq!(ksuid.timestamp(), 1_400_000_000 + i64::from(u32::MAX));
+    assert_eq!(SnowflakeId::from_ksuid(ksuid), None);
+}
+
+#[test]
+fn test_ksuid_parse() {
+    let ksuid: Ksuid = "0ujtsYcgvSTl8PAuAdqWYSMnLOv".parse().unwrap();
+
+    assert_eq!(ksuid.to_string(), "0ujtsYcgvSTl8PAuAdqWYSMnLOv");
+    assert_eq!(ksuid.timestamp(), 1_507_608_047);
+    assert!("0ujtsYcgvSTl8PAuAdqWYSMnLO".parse::<Ksuid>().is_err());
+    assert!("zzzzzzzzzzzzzzzzzzzzzzzzzzz".parse::<Ksuid>().is_err());
+}
@@ -0,0 +1,333 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use snowflake::{
+    get_time_millis, FieldOrder, Granularity, Layout, SnowflakeId, SnowflakeIdGenerator, TimeUnit,
+    ValidationError,
+};
+
+#[test]
+fn test_validate() {
+    let ip = "102.65.2.123".to_string();
+    let mut id_generator = SnowflakeIdGenerator::new_from_ip(ip);
+    let layout = Layout::default();
+
+    let id = id_generator.real_time_generate();
+    assert_eq!(layout.validate(id), Ok(id_generator.reverse(id as u64)));
+
+    assert_eq!(
+        layout.validate(id | i64::MIN),
+        Err(ValidationError::SignBitSet)
+    );
+
+    let future = (get_time_millis() + 2 * 24 * 60 * 60 * 1000) << 22;
+    assert!(matches!(
+        layout.validate(future),
+        Err(ValidationError::TimestampInFuture(_))
+    ));
+
+    let layout = layout.with_max_machine_id(0x27A);
+    assert_eq!(
+        layout.validate(id),
+        Err(ValidationError::MachineOutOfRange(0x27B))
+    );
+}
+
+#[test]
+fn test_decode_slice() {
+    let ip = "102.65.2.123".to_string();
+    let mut id_generator = SnowflakeIdGenerator::new_from_ip(ip);
+
+    let ids: Vec<i64> = (0..10003)
+        .map(|_| id_generator.real_time_generate())
+        .collect();
+    let snowflakes = snowflake::decode_slice(&ids);
+
+    assert_eq!(snowflakes.len(), ids.len());
+    for (&id, snowflake) in ids.iter().zip(snowflakes) {
+        assert_eq!(id_generator.reverse(id as u64), snowflake);
+    }
+}
+
+#[test]
+fn test_field_order() {
+    let ip = "102.65.2.123".to_string();
+    let layout = Layout::default().with_field_order(FieldOrder::SequenceMachine);
+    let mut id_generator = SnowflakeIdGenerator::new_from_ip(ip).with_layout(layout);
+
+    let id = id_generator.real_time_generate();
+
+    assert_eq!(
+        id,
+        id_generator.last_time_millis << 22 | (id_generator.idx as i64) << 10 | 0x27B
+    );
+    assert_eq!(layout.decode(id), id_generator.reverse(id as u64));
+    assert_eq!(layout.decode(id).machine_bits, 0x27B);
+}
+
+#[test]
+fn test_masks_cover_id() {
+    for layout in &[
+        Layout::default(),
+        Layout::default().with_field_order(FieldOrder::SequenceMachine),
+        Layout::new(41, 4, 8),
+    ] {
+        let masks = [
+            layout.timestamp_mask(),
+            layout.machine_mask(),
+            layout.sequence_mask(),
+        ];
+
+        assert_eq!(masks[0] & masks[1], 0);
+        assert_eq!(masks[0] & masks[2], 0);
+        assert_eq!(masks[1] & masks[2], 0);
+
+        let id = layout.compose(1_700_000_000_000, 5, 7);
+        assert_eq!(id & layout.machine_mask(), 5 << layout.machine_shift());
+        assert_eq!(id & layout.sequence_mask(), 7 << layout.sequence_shift());
+        assert_eq!(
+            (id & layout.timestamp_mask()) >> layout.timestamp_shift(),
+            1_700_000_000_000
+        );
+    }
+}
+
+#[test]
+fn test_js_safe_layout() {
+    let ip = "102.65.2.11".to_string();
+    let mut id_generator = SnowflakeIdGenerator::new_from_ip(ip).with_layout(Layout::JS_SAFE);
+
+    let mut ids: Vec<i64> = (0..10000)
+        .map(|_| id_generator.real_time_generate())
+        .collect();
+
+    assert!(ids.iter().all(|&id| id < 1 << 53 && id as f64 as i64 == id));
+    assert!(ids
+        .iter()
+        .all(|&id| Layout::JS_SAFE.decode(id).machine_bits == 11));
+
+    ids.sort();
+    ids.dedup();
+    assert_eq!(10000, ids.len());
+}
+
+#[test]
+fn test_era_survives_overflow() {
+    let legacy = Layout::new(41, 8, 12);
+    let layout = legacy.with_era_bits(2);
+
+    let before = legacy.compose((1 << 41) - 1, 0xFF, 0xFFF);
+    assert_eq!(layout.era(before), 0);
+    assert_eq!(layout.decode(before), legacy.decode(before));
+
+    let timestamp = (1 << 41) + 5;
+    let after = layout.compose(timestamp, 0x2A, 17);
+    assert_eq!(layout.era(after), 1);
+    assert_eq!(layout.decode(after).timestamp, timestamp);
+    assert_eq!(layout.decode(after).machine_bits, 0x2A);
+    assert!(layout.compose(1 << 41, 0, 0) > before);
+    assert!(after > before);
+    assert_eq!(
+        layout.decode_slice(&[before, after])[1],
+        layout.decode(after)
+    );
+    assert!(after > 0);
+}
+
+#[test]
+#[should_panic(expected = "the fields don't fit in 63 bits")]
+fn test_layout_overflow() {
+    Layout::new(42, 10, 12);
+}
+
+#[test]
+#[should_panic(expected = "sequence_bits is over 16")]
+fn test_layout_sequence_overflow() {
+    Layout::new(41, 5, 17);
+}
+
+#[test]
+#[should_panic(expected = "era_bits don't fit in 63 bits")]
+fn test_era_bits_overflow() {
+    Layout::default().with_era_bits(2);
+}
+
+#[test]
+fn test_micros_layout() {
+    let ip = "102.65.2.11".to_string();
+    let mut id_generator = SnowflakeIdGenerator::new_from_ip(ip).with_layout(Layout::MICROS);
+
+    let mut ids: Vec<i64> = (0..10000)
+        .map(|_| id_generator.real_time_generate())
+        .collect();
+    let now_micros = TimeUnit::Microseconds.now();
+
+    let snowflake = Layout::MICROS.decode(ids[0]);
+    assert!(snowflake.timestamp <= now_micros && snowflake.timestamp > now_micros - 1_000_000);
+    assert!(Layout::MICROS.validate(ids[0]).is_ok());
+
+    ids.sort();
+    ids.dedup();
+    assert_eq!(10000, ids.len());
+}
+
+#[test]
+fn test_seconds_layout() {
+    let ip = "102.65.2.123".to_string();
+    let mut id_generator = SnowflakeIdGenerator::new_from_ip(ip).with_layout(Layout::SECONDS);
+
+    let mut ids: Vec<i64> = (0..20000)
+        .map(|_| id_generator.real_time_generate())
+        .collect();
+
+    let first = Layout::SECONDS.decode(ids[0]);
+    let last = Layout::SECONDS.decode(ids[19999]);
+    assert!(last.timestamp > first.timestamp);
+    assert!(Layout::SECONDS.validate(ids[19999]).is_ok());
+
+    ids.sort();
+    ids.dedup();
+    assert_eq!(20000, ids.len());
+}
+
+#[test]
+fn test_explain() {
+    let id = Layout::SECONDS.compose(1_700_000_000, 0xBEEF, 5);
+    assert_eq!(
+        Layout::SECONDS.explain(id),
+        "ts=2023-11-14T22:13:20Z [62..30] machine=0xBEEF [29..14] seq=5 [13..0]"
+    );
+
+    let layout = Layout::new(51, 4, 8)
+        .with_time_unit(TimeUnit::Microseconds)
+        .with_field_order(FieldOrder::SequenceMachine);
+    let id = layout.compose(951_782_400_000_001, 3, 200);
+    assert_eq!(
+        layout.explain(id),
+        "ts=2000-02-29T00:00:00.000001Z [62..12] seq=200 [11..4] machine=0x3 [3..0]"
+    );
+
+    let layout = Layout::new(41, 8, 12).with_era_bits(2);
+    let id = layout.compose((1 << 41) + 1, 0x2A, 17);
+    assert_eq!(
+        layout.explain(id),
+        "era=1 [62..61] ts=2039-09-07T15:47:35.553Z [60..20] machine=0x2A [19..12] seq=17 [11..0]"
+    );
+}
+
+#[test]
+fn test_age() {
+    let layout = Layout::MICROS;
+    let an_hour_ago = TimeUnit::Microseconds.now() - 3_600_000_000;
+
+    let age = layout.age(layout.compose(an_hour_ago, 1, 2));
+    assert!(age >= Duration::from_secs(3600));
+    assert!(age < Duration::from_secs(3601));
+
+    let ahead = TimeUnit::Microseconds.now() + 1_000_000;
+    assert_eq!(layout.age(layout.compose(ahead, 0, 0)), Duration::from_secs(0));
+
+    // 40 bits of milliseconds overflowed in 2004.
+    let layout = Layout::new(40, 8, 12).with_era_bits(3);
+    let id = layout.compose(get_time_millis() - 1000, 0, 0);
+    assert_eq!(layout.era(id), 1);
+    assert!(layout.age(id) >= Duration::from_secs(1));
+    assert!(layout.age(id) < Duration::from_secs(2));
+}
+
+#[test]
+fn test_id_at() {
+    let date = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+    let layout = Layout::default();
+
+    let id = layout.id_at(date);
+    assert_eq!(layout.decode(id).timestamp, 1_700_000_000_123);
+    assert_eq!(layout.decode(id).machine_bits, 0);
+    assert_eq!(layout.decode(id).idx, 0);
+    assert_eq!(id, layout.ids_in_window(date, date).start);
+
+    let before = layout.compose(1_700_000_000_122, 0x3FF, 0xFFF);
+    let at = layout.compose(1_700_000_000_123, 0, 0);
+    assert!(before < id && id <= at);
+
+    assert_eq!(Layout::SECONDS.id_at(date), 1_700_000_000 << 30);
+    assert_eq!(layout.id_at(UNIX_EPOCH), 0);
+    assert_eq!(layout.id_at(UNIX_EPOCH + Duration::from_millis(1 << 42)), i64::MAX);
+}
+
+#[test]
+fn test_period_boundaries() {
+    let layout = Layout::default();
+    // Thursday 2024-02-29T13:14:15.016Z.
+    let id = SnowflakeId(layout.compose(1_709_212_455_016, 0x2A7, 17));
+    let date = UNIX_EPOCH + Duration::from_millis(1_709_212_455_016);
+
+    assert_eq!(layout.start_of_day(date), 1_709_164_800_000 << 22);
+    assert_eq!(layout.end_of_day(date), (1_709_251_200_000 << 22) - 1);
+    assert_eq!(layout.start_of_week(date), 1_708_905_600_000 << 22);
+    assert_eq!(layout.end_of_week(date), (1_709_510_400_000 << 22) - 1);
+    assert_eq!(layout.start_of_month(date), 1_706_745_600_000 << 22);
+    assert_eq!(layout.end_of_month(date), (1_709_251_200_000 << 22) - 1);
+    assert_eq!(layout.start_of(Granularity::Hour, date), 1_709_211_600_000 << 22);
+
+    assert!((layout.start_of_day(date)..=layout.end_of_day(date)).contains(&id.0));
+
+    // December rolls over into the next year.
+    let december = UNIX_EPOCH + Duration::from_millis(1_734_000_000_000);
+    assert_eq!(layout.start_of_month(december), 1_733_011_200_000 << 22);
+    assert_eq!(layout.end_of_month(december), (1_735_689_600_000 << 22) - 1);
+
+    let seconds = Layout::SECONDS;
+    assert_eq!(seconds.start_of_day(date), 1_709_164_800 << 30);
+    assert_eq!(seconds.end_of_day(date), (1_709_251_200 << 30) - 1);
+
+    assert_eq!(layout.start_of_week(UNIX_EPOCH), 0);
+    let far = UNIX_EPOCH + Duration::from_millis(1 << 42);
+    assert_eq!(layout.end_of_month(far), i64::MAX);
+}
+
+#[test]
+fn test_ids_in_window() {
+    let start = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+    let end = start + Duration::from_millis(10);
+    let layout = Layout::default();
+
+    let ids = layout.ids_in_window(start, end);
+    assert!(ids.contains(&layout.compose(1_700_000_000_000, 0, 0)));
+    assert!(ids.contains(&layout.compose(1_700_000_000_009, 0x3FF, 0xFFF)));
+    assert!(!ids.contains(&layout.compose(1_700_000_000_010, 0, 0)));
+
+    let ids = Layout::SECONDS.ids_in_window(start, end);
+    assert_eq!(ids.start, 1_700_000_000 << 30);
+    assert!(ids.is_empty());
+
+    assert_eq!(layout.ids_in_window(UNIX_EPOCH, start).start, 0);
+    let far = UNIX_EPOCH + Duration::from_millis(1 << 42);
+    assert_eq!(layout.ids_in_window(start, far).end, i64::MAX);
+}
+
+#[test]
+fn test_buckets_in_window() {
+    let start = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+    let end = start + Duration::from_millis(5);
+    let layout = Layout::default();
+
+    let buckets = layout.buckets_in_window(start, end, Duration::from_millis(1));
+    assert_eq!(buckets.len(), 5);
+
+    let buckets: Vec<_> = buckets.collect();
+    assert_eq!(buckets.first().unwrap().start, layout.ids_in_window(start, end).start);
+    assert_eq!(buckets.last().unwrap().end, layout.ids_in_window(start, end).end);
+    assert!(buckets.windows(2).all(|pair| pair[0].end == pair[1].start));
+
+    let buckets = layout.buckets_in_window(start, end, Duration::from_millis(2));
+    assert_eq!(buckets.len(), 3);
+    assert_eq!(layout.buckets_in_window(end, start, Duration::from_millis(2)).count(), 0);
+}
+
+#[test]
+#[should_panic(expected = "bucket is shorter than the time unit")]
+fn test_buckets_shorter_than_time_unit() {
+    let start = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+
+    Layout::SECONDS.buckets_in_window(start, start, Duration::from_millis(500));
+}
@@ -0,0 +1,90 @@
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use snowflake::{LeaseKeeper, RenewableLease, SnowflakeError};
+
+struct FlakyLease {
+    renewals: Arc<AtomicUsize>,
+    fail_after: usize,
+}
+
+impl RenewableLease for FlakyLease {
+    fn machine_id(&self) -> i64 {
+        7
+    }
+
+    fn renew(&mut self) -> io::Result<()> {
+        if self.renewals.fetch_add(1, Ordering::SeqCst) + 1 >= self.fail_after {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "session expired"));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_lease_keeper_reports_lost_lease() {
+    let renewals = Arc::new(AtomicUsize::new(0));
+    let lease = FlakyLease {
+        renewals: Arc::clone(&renewals),
+        fail_after: 3,
+    };
+
+    let (keeper, lost) = LeaseKeeper::spawn(lease, Duration::from_millis(10));
+    let mut id_generator = keeper.generator();
+    assert_eq!(id_generator.machine_id(), 7);
+
+    let notification = lost.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(notification.machine_id, 7);
+    assert_eq!(notification.error.kind(), io::ErrorKind::NotFound);
+    assert!(keeper.is_lost());
+    assert_eq!(renewals.load(Ordering::SeqCst), 3);
+    assert_eq!(id_generator.generate(), Err(SnowflakeError::LeaseLost(7)));
+}
+
+#[test]
+fn test_lease_keeper_stops_on_drop() {
+    let renewals = Arc::new(AtomicUsize::new(0));
+    let lease = FlakyLease {
+        renewals: Arc::clone(&renewals),
+        fail_after: usize::MAX,
+    };
+
+    let (keeper, lost) = LeaseKeeper::spawn(lease, Duration::from_millis(5));
+    keeper.with_lease(|lease| lease.renew()).unwrap();
+    drop(keeper);
+
+    let renewed = renewals.load(Ordering::SeqCst);
+    std::thread::sleep(Duration::from_millis(30));
+    assert_eq!(renewals.load(Ordering::SeqCst), renewed);
+    assert!(lost.recv().is_err());
+}
+
+struct ExpiringLease;
+
+impl RenewableLease for ExpiringLease {
+    fn machine_id(&self) -> i64 {
+        3
+    }
+
+    fn renew(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn ttl(&self) -> Option<Duration> {
+        Some(Duration::from_millis(20))
+    }
+}
+
+#[test]
+fn test_lease_keeper_generator_expires() {
+    let (keeper, _lost) = LeaseKeeper::spawn(ExpiringLease, Duration::from_secs(60));
+    let mut id_generator = keeper.generator();
+    id_generator.generate().unwrap();
+
+    // No renewal within the TTL, the claim may have expired.
+    std::thread::sleep(Duration::from_millis(40));
+    assert!(!keeper.is_lost());
+    assert_eq!(id_generator.generate(), Err(SnowflakeError::LeaseLost(3)));
+}
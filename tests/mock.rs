@@ -0,0 +1,27 @@
+use snowflake::{IdGenerator, MockIdGenerator, SnowflakeError, SnowflakeId};
+
+#[test]
+fn test_mock_from_ids() {
+    let mut ids = MockIdGenerator::from_ids(vec![SnowflakeId(3), SnowflakeId(1)]);
+
+    assert_eq!(ids.next_id(), Ok(SnowflakeId(3)));
+    assert_eq!(ids.next_id(), Ok(SnowflakeId(1)));
+    assert_eq!(ids.issued(), 2);
+}
+
+#[test]
+#[should_panic(expected = "MockIdGenerator ran out of ids")]
+fn test_mock_runs_out() {
+    let mut ids = MockIdGenerator::from_ids(0..1);
+
+    ids.next_id().unwrap();
+    let _ = ids.next_id();
+}
+
+#[test]
+fn test_mock_from_fn() {
+    let mut ids = MockIdGenerator::from_fn(|| Err(SnowflakeError::RateLimited));
+
+    assert_eq!(ids.next_id(), Err(SnowflakeError::RateLimited));
+    assert_eq!(ids.issued(), 1);
+}
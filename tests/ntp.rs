@@ -0,0 +1,107 @@
+#![cfg(feature = "ntp")]
+
+use std::io::ErrorKind;
+use std::net::UdpSocket;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use snowflake::{verify_clock, ClockReference, NtpClock, SnowflakeError};
+
+const NTP_UNIX_OFFSET_SECONDS: u64 = 2_208_988_800;
+
+// Answer one SNTP request with a clock `ahead_millis` ahead of ours.
+fn fake_ntp_server(ahead_millis: u64, stratum: u8) -> String {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let address = socket.local_addr().unwrap().to_string();
+
+    thread::spawn(move || {
+        let mut request = [0; 48];
+        let (_, client) = socket.recv_from(&mut request).unwrap();
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let millis = now.as_millis() as u64 + ahead_millis;
+        let seconds = millis / 1000 + NTP_UNIX_OFFSET_SECONDS;
+        let timestamp = (seconds << 32) | (((millis % 1000) << 32) / 1000);
+
+        let mut response = [0; 48];
+        response[0] = 0x24;
+        response[1] = stratum;
+        response[24..32].copy_from_slice(&request[40..48]);
+        response[32..40].copy_from_slice(&timestamp.to_be_bytes());
+        response[40..48].copy_from_slice(&timestamp.to_be_bytes());
+        socket.send_to(&response, client).unwrap();
+    });
+
+    address
+}
+
+#[test]
+fn test_ntp_offset() {
+    let reference = NtpClock::new(fake_ntp_server(5000, 2));
+
+    let offset = reference.offset_millis().unwrap();
+    assert!((-5100..=-4900).contains(&offset), "offset {}", offset);
+}
+
+#[test]
+fn test_verify_clock_refuses_skew() {
+    let reference = NtpClock::new(fake_ntp_server(5000, 2));
+
+    let error = verify_clock(&reference, Duration::from_secs(1)).unwrap_err();
+    match error.get_ref().and_then(|error| error.downcast_ref()) {
+        Some(SnowflakeError::ClockSkewed {
+            skew_millis,
+            max_skew_millis: 1000,
+        }) => assert!(*skew_millis < -4900),
+        _ => panic!("unexpected {:?}", error),
+    }
+}
+
+#[test]
+fn test_verify_clock_accepts_close_clock() {
+    let reference = NtpClock::new(fake_ntp_server(0, 2));
+
+    let offset = verify_clock(&reference, Duration::from_secs(1)).unwrap();
+    assert!(offset.abs() < 100);
+}
+
+#[test]
+fn test_ntp_kiss_of_death() {
+    let reference = NtpClock::new(fake_ntp_server(0, 0));
+
+    assert_eq!(
+        reference.offset_millis().unwrap_err().kind(),
+        ErrorKind::ConnectionRefused
+    );
+}
+
+#[test]
+fn test_ntp_timeout() {
+    let silent = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let reference = NtpClock::new(silent.local_addr().unwrap().to_string())
+        .with_timeout(Duration::from_millis(50));
+
+    assert!(reference.offset_millis().is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_chrony_offset() {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    use snowflake::ChronyClock;
+
+    let script = std::env::temp_dir().join(format!("snowflake-chronyc-{}", std::process::id()));
+    fs::write(
+        &script,
+        "#!/bin/sh\necho 'A29FC87B,ntp.example,3,1717243200.1,0.002500000,-0.000012,0.000100,\
+         -1.234,-0.012,0.034,0.011,0.002,64.5,Normal'\n",
+    )
+    .unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let offset = ChronyClock::new().with_command(&script).offset_millis();
+    fs::remove_file(&script).unwrap();
+    assert_eq!(offset.unwrap(), -3);
+}
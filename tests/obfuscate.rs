@@ -0,0 +1,92 @@
+use std::collections::HashSet;
+
+use snowflake::obfuscate::{Feistel, Optimus, OptimusError};
+use snowflake::SnowflakeIdGenerator;
+
+#[test]
+fn test_feistel_round_trip() {
+    let feistel = Feistel::new(42);
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+
+    let ids: Vec<i64> = (0..10000)
+        .map(|_| id_generator.real_time_generate())
+        .collect();
+    let public: Vec<i64> = ids.iter().map(|&id| feistel.encode(id)).collect();
+
+    assert!(public.iter().all(|&id| id >= 0));
+    assert_eq!(public.iter().collect::<HashSet<_>>().len(), ids.len());
+    assert!(public.windows(2).any(|pair| pair[0] > pair[1]));
+
+    for (&id, &public) in ids.iter().zip(public.iter()) {
+        assert_eq!(feistel.decode(public), id);
+    }
+
+    for &edge in &[0, 1, i64::MAX] {
+        assert_eq!(feistel.decode(feistel.encode(edge)), edge);
+    }
+}
+
+#[test]
+fn test_feistel_depends_on_key() {
+    let id = 6_725_650_666_386_976_769;
+
+    assert_eq!(Feistel::new(1).encode(id), Feistel::new(1).encode(id));
+    assert_ne!(Feistel::new(1).encode(id), Feistel::new(2).encode(id));
+    assert_ne!(Feistel::new(1).decode(Feistel::new(2).encode(id)), id);
+}
+
+#[test]
+#[should_panic]
+fn test_feistel_rejects_negative_ids() {
+    Feistel::new(1).encode(-1);
+}
+
+#[test]
+fn test_optimus_round_trip() {
+    const OPTIMUS: Optimus = Optimus::new(1_580_030_173, 0x2F5B_0C1D_7E39_4A86);
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+
+    let ids: Vec<i64> = (0..10000)
+        .map(|_| id_generator.real_time_generate())
+        .collect();
+    for &id in &ids {
+        let public = OPTIMUS.encode(id);
+        assert!(public >= 0);
+        assert_eq!(OPTIMUS.decode(public), id);
+    }
+
+    for &edge in &[0, 1, i64::MAX] {
+        assert_eq!(OPTIMUS.decode(OPTIMUS.encode(edge)), edge);
+    }
+}
+
+#[test]
+fn test_optimus_validation() {
+    assert!(Optimus::try_new(2_305_843_009_213_693_951, 0).is_ok());
+    assert_eq!(Optimus::try_new(1, 0), Err(OptimusError::NotPrime(1)));
+    assert_eq!(Optimus::try_new(2, 0), Err(OptimusError::NotPrime(2)));
+    assert_eq!(
+        Optimus::try_new(2_305_843_009_213_693_953, 0),
+        Err(OptimusError::NotPrime(2_305_843_009_213_693_953))
+    );
+    assert_eq!(
+        Optimus::try_new(u64::MAX, 0),
+        Err(OptimusError::NotPrime(u64::MAX))
+    );
+    assert_eq!(
+        Optimus::try_new(7, 1 << 63),
+        Err(OptimusError::XorOutOfRange(1 << 63))
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_optimus_rejects_composites() {
+    Optimus::new(1_580_030_175, 0);
+}
+
+#[test]
+#[should_panic(expected = "not an odd prime")]
+fn test_optimus_rejects_even_multipliers() {
+    Optimus::new(2, 0);
+}
@@ -0,0 +1,35 @@
+#![cfg(feature = "bson")]
+
+use bson::oid::ObjectId;
+use snowflake::{SnowflakeId, SnowflakeIdGenerator};
+
+#[test]
+fn test_object_id_round_trip_preserves_order() {
+    let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+
+    let ids: Vec<SnowflakeId> = (0..10000)
+        .map(|_| SnowflakeId(id_generator.real_time_generate()))
+        .collect();
+    let object_ids: Vec<ObjectId> = ids.iter().map(|id| id.to_object_id()).collect();
+
+    let mut sorted = object_ids.clone();
+    sorted.sort();
+    assert_eq!(sorted, object_ids);
+
+    for (id, object_id) in ids.iter().zip(object_ids) {
+        assert_eq!(SnowflakeId::from_object_id(object_id), Some(*id));
+    }
+}
+
+#[test]
+fn test_foreign_object_id() {
+    let object_id = ObjectId::parse_str("64e26b55a1b2c3d4e5000abc").unwrap();
+    let id = SnowflakeId::from_object_id(object_id).unwrap();
+
+    assert_eq!(id.timestamp(), 1_692_560_213_000);
+    assert_eq!(id.machine_bits(), 0xe5 & 0x3FF);
+    assert_eq!(id.idx(), 0xabc);
+
+    let overflow = ObjectId::parse_str("ffffffff0000000000000000").unwrap();
+    assert_eq!(SnowflakeId::from_object_id(overflow), None);
+}
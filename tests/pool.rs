@@ -0,0 +1,19 @@
+#![cfg(feature = "rayon")]
+
+use rayon::prelude::*;
+use snowflake::GeneratorPool;
+
+#[test]
+fn test_generator_pool() {
+    let pool = GeneratorPool::new(0x200, 8);
+
+    let mut ids: Vec<i64> = (0..100000)
+        .into_par_iter()
+        .map(|_| pool.generate())
+        .collect();
+
+    ids.sort();
+    ids.dedup();
+
+    assert_eq!(100000, ids.len());
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "postgres")]
+
+use std::io::ErrorKind;
+
+use postgres::{Client, NoTls};
+use snowflake::PostgresAllocator;
+
+// Connections to the server in `SNOWFLAKE_TEST_POSTGRES`, the tests are
+// skipped without one.
+fn connect() -> Option<Client> {
+    let config = std::env::var("SNOWFLAKE_TEST_POSTGRES").ok()?;
+    Some(Client::connect(&config, NoTls).unwrap())
+}
+
+#[test]
+fn test_postgres_leases() {
+    let (first, second, third) = match (connect(), connect(), connect()) {
+        (Some(first), Some(second), Some(third)) => (first, second, third),
+        _ => return,
+    };
+    let allocator = PostgresAllocator::new(0..2).with_lock_space(0x7E57);
+
+    let mut first = allocator.acquire(first).unwrap();
+    let second = allocator.acquire(second).unwrap();
+    assert_eq!(first.machine_id(), 0);
+    assert_eq!(second.machine_id(), 1);
+    assert_eq!(
+        allocator.acquire(third).unwrap_err().kind(),
+        ErrorKind::AddrInUse
+    );
+
+    first.renew().unwrap();
+    assert_eq!(first.generator().machine_id(), 0);
+
+    drop(first);
+    let again = allocator.acquire(connect().unwrap()).unwrap();
+    assert_eq!(again.machine_id(), 0);
+}
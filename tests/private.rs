@@ -0,0 +1,40 @@
+#![cfg(feature = "privacy")]
+
+use std::collections::HashSet;
+
+use snowflake::{Layout, PrivateIdGenerator, SnowflakeIdGenerator};
+
+#[test]
+fn test_private_ids_are_unique() {
+    let mut private = PrivateIdGenerator::new(SnowflakeIdGenerator::new(1, 1));
+
+    let ids: HashSet<i64> = (0..20000).map(|_| private.generate()).collect();
+    assert_eq!(ids.len(), 20000);
+}
+
+#[test]
+fn test_private_ids_hide_the_count() {
+    let layout = Layout::new(41, 10, 12);
+    let mut private = PrivateIdGenerator::new(SnowflakeIdGenerator::new(1, 1).with_layout(layout));
+
+    let idxs: Vec<u16> = (0..64)
+        .map(|_| layout.decode(private.generate()).idx)
+        .collect();
+
+    // Sequential records would be 0, 1, 2, ... within each millisecond.
+    assert!(idxs.iter().filter(|&&idx| idx >= 64).count() > 32);
+}
+
+#[test]
+fn test_private_ids_wait_once_exhausted() {
+    let layout = Layout::new(41, 10, 2);
+    let mut private = PrivateIdGenerator::new(SnowflakeIdGenerator::new(1, 1).with_layout(layout));
+
+    let ids: Vec<i64> = (0..40).map(|_| private.generate()).collect();
+    let unique: HashSet<_> = ids.iter().collect();
+    assert_eq!(unique.len(), 40);
+
+    let first = layout.decode(ids[0]).timestamp;
+    let last = layout.decode(ids[39]).timestamp;
+    assert!(last - first >= 9);
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "prost")]
+
+use std::convert::TryFrom;
+
+use prost::Message;
+use snowflake::{SnowflakeError, SnowflakeId, SNOWFLAKE_PROTO};
+
+#[derive(Clone, PartialEq, Message)]
+struct User {
+    #[prost(message, optional, tag = "1")]
+    id: Option<SnowflakeId>,
+    #[prost(string, tag = "2")]
+    name: String,
+    #[prost(sfixed64, tag = "3")]
+    referrer_id: i64,
+    #[prost(string, tag = "4")]
+    session_id: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct RawId {
+    #[prost(sfixed64, tag = "1")]
+    value: i64,
+    #[prost(string, tag = "2")]
+    unknown: String,
+}
+
+#[test]
+fn test_wire_format() {
+    let id = SnowflakeId(6725650666386976769);
+    let bytes = id.encode_to_vec();
+
+    assert_eq!(bytes[0], 0x09);
+    assert_eq!(bytes[1..], 6725650666386976769i64.to_le_bytes());
+    assert_eq!(bytes.len(), id.encoded_len());
+
+    let raw = RawId {
+        value: id.0,
+        unknown: String::new(),
+    };
+    assert_eq!(raw.encode_to_vec(), bytes);
+}
+
+#[test]
+fn test_default_is_empty() {
+    assert!(SnowflakeId(0).encode_to_vec().is_empty());
+    assert_eq!(SnowflakeId::decode(&[][..]).unwrap(), SnowflakeId(0));
+}
+
+#[test]
+fn test_unknown_fields_are_skipped() {
+    let raw = RawId {
+        value: 42,
+        unknown: "ignored".to_string(),
+    };
+
+    assert_eq!(
+        SnowflakeId::decode(raw.encode_to_vec().as_slice()).unwrap(),
+        SnowflakeId(42)
+    );
+}
+
+#[test]
+fn test_message_fields() {
+    let id = SnowflakeId(6725650666386976769);
+    let user = User {
+        id: Some(id),
+        name: "ada".to_string(),
+        referrer_id: id.into(),
+        session_id: id.into(),
+    };
+
+    let decoded = User::decode(user.encode_to_vec().as_slice()).unwrap();
+
+    assert_eq!(decoded, user);
+    assert_eq!(SnowflakeId::from(decoded.referrer_id), id);
+    assert_eq!(SnowflakeId::try_from(decoded.session_id), Ok(id));
+}
+
+#[test]
+fn test_string_field() {
+    assert_eq!(
+        String::from(SnowflakeId(6725650666386976769)),
+        "6725650666386976769"
+    );
+    assert_eq!(
+        SnowflakeId::try_from("-1".to_string()),
+        Err(SnowflakeError::ParseError)
+    );
+}
+
+#[test]
+fn test_proto_file() {
+    assert!(SNOWFLAKE_PROTO.contains("package snowflake;"));
+    assert!(SNOWFLAKE_PROTO.contains("sfixed64 value = 1;"));
+}
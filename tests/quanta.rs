@@ -0,0 +1,19 @@
+#![cfg(feature = "quanta")]
+
+use snowflake::{Layout, SnowflakeIdGenerator, TimeSource, TimeUnit};
+
+#[test]
+fn test_quanta_time_source() {
+    let before = snowflake::get_time_millis();
+    let now = TimeSource::Quanta.try_now(TimeUnit::Milliseconds).unwrap();
+    assert!((now - before).abs() < 1000);
+
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1)
+        .with_layout(Layout::new(41, 10, 2))
+        .with_time_source(TimeSource::Quanta);
+    let ids: Vec<i64> = (0..1000).map(|_| id_generator.real_time_generate()).collect();
+
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    let timestamp = Layout::new(41, 10, 2).decode(ids[999]).timestamp;
+    assert!((timestamp - snowflake::get_time_millis()).abs() < 1000);
+}
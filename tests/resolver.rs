@@ -0,0 +1,87 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use snowflake::{ContainerIdResolver, Layout, WorkerIdResolver};
+
+const DOCKER_ID: &str = "3f4a2b1c9d8e7f6a5b4c3d2e1f0a9b8c7d6e5f4a3b2c1d0e9f8a7b6c5d4e3f2a";
+const CONTAINERD_ID: &str = "9b8c7d6e5f4a3b2c1d0e9f8a7b6c5d4e3f2a3f4a2b1c9d8e7f6a5b4c3d2e1f0a";
+
+fn write_fixture(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("snowflake-{}-{}", std::process::id(), name));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn test_container_id_from_cgroup() {
+    let cgroup = write_fixture(
+        "docker-cgroup",
+        &format!(
+            "12:memory:/docker/{}\n0::/docker/{}\n",
+            DOCKER_ID, DOCKER_ID
+        ),
+    );
+    let resolver = ContainerIdResolver::with_paths(&cgroup, "/nonexistent/mountinfo");
+    assert_eq!(resolver.container_id().unwrap(), DOCKER_ID);
+
+    let cgroup = write_fixture(
+        "containerd-cgroup",
+        &format!(
+            "0::/kubepods.slice/kubepods-pod1.slice/cri-containerd-{}.scope\n",
+            CONTAINERD_ID
+        ),
+    );
+    let resolver = ContainerIdResolver::with_paths(&cgroup, "/nonexistent/mountinfo");
+    assert_eq!(resolver.container_id().unwrap(), CONTAINERD_ID);
+}
+
+#[test]
+fn test_container_id_from_mountinfo() {
+    let cgroup = write_fixture("v2-cgroup", "0::/\n");
+    let mountinfo = write_fixture(
+        "v2-mountinfo",
+        &format!(
+            "1180 1160 0:62 / / rw,relatime - overlay overlay rw\n\
+             1190 1180 254:1 /var/lib/docker/containers/{}/hostname /etc/hostname rw - ext4 \
+             /dev/vda1 rw\n",
+            DOCKER_ID
+        ),
+    );
+
+    let resolver = ContainerIdResolver::with_paths(&cgroup, &mountinfo);
+    assert_eq!(resolver.container_id().unwrap(), DOCKER_ID);
+}
+
+#[test]
+fn test_container_machine_bits() {
+    let first = ContainerIdResolver::with_paths(
+        write_fixture("first-cgroup", &format!("0::/docker/{}\n", DOCKER_ID)),
+        "/nonexistent/mountinfo",
+    );
+    let second = ContainerIdResolver::with_paths(
+        write_fixture("second-cgroup", &format!("0::/docker/{}\n", CONTAINERD_ID)),
+        "/nonexistent/mountinfo",
+    );
+
+    let machine_bits = first.machine_bits(Layout::default()).unwrap();
+    assert_eq!(machine_bits, first.machine_bits(Layout::default()).unwrap());
+    assert_ne!(
+        machine_bits,
+        second.machine_bits(Layout::default()).unwrap()
+    );
+    assert!(machine_bits < 1 << 10);
+    assert!(first.machine_bits(Layout::JS_SAFE).unwrap() < 1 << 4);
+
+    let outside = ContainerIdResolver::with_paths(
+        write_fixture(
+            "host-cgroup",
+            "0::/user.slice/user-1000.slice/session-2.scope\n",
+        ),
+        "/nonexistent/mountinfo",
+    );
+    assert_eq!(
+        outside.container_id().unwrap_err().kind(),
+        io::ErrorKind::NotFound
+    );
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "rkyv")]
+
+use rkyv::rancor::Error;
+use rkyv::{Archive, Deserialize, Serialize};
+use snowflake::{ArchivedSnowflakeId, Layout, Snowflake, SnowflakeId};
+
+#[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+struct Record {
+    id: SnowflakeId,
+    decoded: Snowflake,
+    parent_ids: Vec<SnowflakeId>,
+}
+
+fn record() -> Record {
+    let id = SnowflakeId(6725650666386976769);
+    Record {
+        id,
+        decoded: Layout::default().decode(id.0),
+        parent_ids: vec![SnowflakeId(1), SnowflakeId(2)],
+    }
+}
+
+#[test]
+fn test_access_in_place() {
+    let record = record();
+    let bytes = rkyv::to_bytes::<Error>(&record).unwrap();
+
+    let archived = rkyv::access::<ArchivedRecord, Error>(&bytes).unwrap();
+
+    assert_eq!(archived.id.get(), record.id);
+    assert_eq!(archived.id, record.id);
+    assert_eq!(archived.decoded, record.decoded);
+    assert_eq!(archived.decoded.idx, record.decoded.idx);
+    assert!(archived.parent_ids[0] < archived.parent_ids[1]);
+}
+
+#[test]
+fn test_round_trip() {
+    let record = record();
+    let bytes = rkyv::to_bytes::<Error>(&record).unwrap();
+
+    assert_eq!(rkyv::from_bytes::<Record, Error>(&bytes).unwrap(), record);
+}
+
+#[test]
+fn test_archived_layout() {
+    let bytes = rkyv::to_bytes::<Error>(&SnowflakeId(42)).unwrap();
+
+    assert_eq!(std::mem::size_of::<ArchivedSnowflakeId>(), 8);
+    assert_eq!(bytes.as_slice(), 42i64.to_le_bytes());
+}
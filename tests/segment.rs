@@ -0,0 +1,125 @@
+use std::collections::HashSet;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use snowflake::{SegmentGenerator, SegmentStore};
+
+struct Sequence {
+    next: i64,
+    calls: Arc<AtomicUsize>,
+    fail: Arc<AtomicUsize>,
+}
+
+impl SegmentStore for Sequence {
+    type Error = &'static str;
+
+    fn next_segment(&mut self, size: u64) -> Result<Range<i64>, Self::Error> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if self.fail.load(Ordering::SeqCst) > 0 {
+            return Err("store unavailable");
+        }
+
+        let start = self.next;
+        self.next += size as i64;
+        Ok(start..self.next)
+    }
+}
+
+fn sequence() -> (Sequence, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let fail = Arc::new(AtomicUsize::new(0));
+    let store = Sequence {
+        next: 1,
+        calls: Arc::clone(&calls),
+        fail: Arc::clone(&fail),
+    };
+    (store, calls, fail)
+}
+
+// Wait for the background prefetch to land.
+fn wait_for_buffered(generator: &SegmentGenerator<Sequence>, buffered: usize) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while generator.stats().buffered != buffered {
+        assert!(Instant::now() < deadline, "prefetch never landed");
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_segment_ids_are_sequential() {
+    let (store, calls, _) = sequence();
+    let generator = SegmentGenerator::new(store, 10);
+
+    let ids: Vec<i64> = (0..35).map(|_| generator.generate().unwrap()).collect();
+
+    assert_eq!(ids, (1..36).collect::<Vec<i64>>());
+    assert!(calls.load(Ordering::SeqCst) >= 4);
+    assert_eq!(generator.stats().issued, 35);
+}
+
+#[test]
+fn test_segment_prefetches_next_segment() {
+    let (store, calls, _) = sequence();
+    let generator = SegmentGenerator::new(store, 10);
+
+    assert_eq!(generator.generate(), Ok(1));
+    wait_for_buffered(&generator, 19);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    let ids: Vec<i64> = (0..19).map(|_| generator.generate().unwrap()).collect();
+    assert_eq!(ids, (2..21).collect::<Vec<i64>>());
+}
+
+#[test]
+fn test_segment_store_errors_surface_once_segments_run_out() {
+    let (store, _, fail) = sequence();
+    let generator = SegmentGenerator::new(store, 10);
+
+    assert_eq!(generator.generate(), Ok(1));
+    wait_for_buffered(&generator, 19);
+
+    fail.store(1, Ordering::SeqCst);
+    for id in 2..21 {
+        assert_eq!(generator.generate(), Ok(id));
+    }
+    assert_eq!(generator.generate(), Err("store unavailable"));
+
+    fail.store(0, Ordering::SeqCst);
+    assert_eq!(generator.generate(), Ok(21));
+}
+
+#[test]
+fn test_segment_clones_share_segments() {
+    let (store, _, _) = sequence();
+    let generator = SegmentGenerator::new(store, 100);
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let generator = generator.clone();
+            thread::spawn(move || {
+                (0..1000)
+                    .map(|_| generator.generate().unwrap())
+                    .collect::<Vec<i64>>()
+            })
+        })
+        .collect();
+
+    let mut ids = HashSet::new();
+    for handle in handles {
+        let thread_ids = handle.join().unwrap();
+        assert!(thread_ids.windows(2).all(|pair| pair[0] < pair[1]));
+        ids.extend(thread_ids);
+    }
+
+    assert_eq!(ids.len(), 4000);
+}
+
+#[test]
+#[should_panic(expected = "segment_size must be positive")]
+fn test_segment_size_zero() {
+    let (store, _, _) = sequence();
+    SegmentGenerator::new(store, 0);
+}
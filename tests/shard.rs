@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+
+use snowflake::{Layout, ShardKey, SnowflakeError, SnowflakeIdGenerator};
+
+#[test]
+fn test_generate_for_shard() {
+    let layout = Layout::default().with_shard_bits(3);
+    let mut id_generator = SnowflakeIdGenerator::new(1, 2).with_layout(layout);
+    let shard_key = layout.shard_key();
+
+    assert_eq!(shard_key.shift(), 19);
+    assert_eq!(shard_key.shard_count(), 8);
+
+    let ids: Vec<i64> = (0..16)
+        .map(|i| id_generator.generate_for_shard(i % 8).unwrap().0)
+        .collect();
+
+    let snowflakes = layout.decode_slice(&ids);
+    assert!(snowflakes
+        .windows(2)
+        .all(|pair| (pair[0].timestamp, pair[0].idx) < (pair[1].timestamp, pair[1].idx)));
+    for (i, &id) in ids.iter().enumerate() {
+        let snowflake = layout.decode(id);
+        assert_eq!(shard_key.shard_of(id), i as u64 % 8);
+        assert_eq!(snowflake.machine_bits & 0x7F, 1 << 5 | 2);
+    }
+}
+
+#[test]
+fn test_generate_for_shard_errors() {
+    let layout = Layout::default().with_shard_bits(3);
+    let mut id_generator = SnowflakeIdGenerator::new(1, 2).with_layout(layout);
+
+    assert_eq!(
+        id_generator.generate_for_shard(8),
+        Err(SnowflakeError::InvalidShard(8))
+    );
+
+    let mut overlapping = SnowflakeIdGenerator::new(31, 31).with_layout(layout);
+    assert_eq!(
+        overlapping.generate_for_shard(1),
+        Err(SnowflakeError::InvalidMachineId(0x3FF))
+    );
+}
+
+#[test]
+fn test_shard_key() {
+    let shard_key = ShardKey::new(12, 4);
+
+    assert_eq!(shard_key.mask(), 0xF000);
+    assert_eq!(shard_key.shard_of(0xA123), 0xA);
+    assert_eq!(shard_key.with_shard(0xA123, 3), Ok(0x3123));
+    assert_eq!(
+        shard_key.with_shard(0xA123, 16),
+        Err(SnowflakeError::InvalidShard(16))
+    );
+
+    assert_eq!(Layout::default().shard_key().shard_of(i64::MAX), 0);
+}
+
+#[test]
+#[should_panic(expected = "shard_bits don't fit the machine bits")]
+fn test_shard_bits_overflow() {
+    Layout::default().with_thread_bits(4).with_shard_bits(7);
+}
+
+#[test]
+fn test_generate_sharded() {
+    let layout = Layout::default().with_shard_bits(4);
+    let mut id_generator = SnowflakeIdGenerator::new(0, 7).with_layout(layout);
+    let shard_key = layout.shard_key();
+
+    let mut shards = HashSet::new();
+    for tenant_id in 0..64 {
+        let first = id_generator.generate_sharded(tenant_id).unwrap();
+        let second = id_generator.generate_sharded(tenant_id).unwrap();
+
+        assert!(first < second);
+        assert_eq!(shard_key.shard_of(first.0), shard_key.shard_of(second.0));
+        assert_eq!(shard_key.shard_of(first.0), shard_key.shard_for(tenant_id));
+        shards.insert(shard_key.shard_of(first.0));
+    }
+    assert!(shards.len() > 8);
+
+    let mut unsharded = SnowflakeIdGenerator::new(0, 7);
+    assert_eq!(
+        unsharded.generate_sharded(42).unwrap().0 & shard_key.mask(),
+        0
+    );
+}
@@ -0,0 +1,47 @@
+use snowflake::{get_time_millis, IdGenerator, Layout, ShortIdGenerator, SnowflakeError};
+
+#[test]
+fn test_short_generate() {
+    let epoch_seconds = get_time_millis() / 1000 - 3600;
+    let mut id_generator = ShortIdGenerator::new(epoch_seconds, 2);
+
+    let ids: Vec<u32> = (0..100).map(|_| id_generator.generate()).collect();
+
+    let mut sorted = ids.clone();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(sorted, ids);
+
+    for &id in &ids {
+        let snowflake = id_generator.decode(id);
+        assert_eq!(snowflake.machine_bits, 2);
+        assert!(snowflake.timestamp >= epoch_seconds + 3600);
+    }
+}
+
+#[test]
+fn test_short_epoch_overflow() {
+    let now_seconds = get_time_millis() / 1000;
+
+    let mut expired = ShortIdGenerator::new(now_seconds - (1 << 24), 2);
+    assert!(matches!(
+        expired.try_generate(),
+        Err(SnowflakeError::EpochOverflow(_))
+    ));
+
+    let mut future = ShortIdGenerator::new(now_seconds + 3600, 2);
+    assert!(matches!(
+        future.next_id(),
+        Err(SnowflakeError::EpochOverflow(_))
+    ));
+}
+
+#[test]
+fn test_short_waits_for_next_second() {
+    let mut id_generator = ShortIdGenerator::new(get_time_millis() / 1000 - 3600, 2)
+        .with_layout(Layout::new(24, 2, 2));
+
+    let ids: Vec<u32> = (0..6).map(|_| id_generator.generate()).collect();
+
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+}
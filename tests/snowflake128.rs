@@ -0,0 +1,30 @@
+use snowflake::{Snowflake128, Snowflake128Generator};
+
+#[test]
+fn test_snowflake128_generate() {
+    let mut id_generator = Snowflake128Generator::new(42);
+
+    let ids: Vec<Snowflake128> = (0..10000).map(|_| id_generator.generate()).collect();
+
+    let mut sorted = ids.clone();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(sorted, ids);
+
+    for id in ids {
+        assert_eq!(id.machine_id, 42);
+        assert_eq!(Snowflake128::from_bytes(id.to_bytes()), id);
+        assert_eq!(id.to_string().parse(), Ok(id));
+    }
+}
+
+#[test]
+fn test_snowflake128_parse_errors() {
+    assert!("".parse::<Snowflake128>().is_err());
+    assert!("0000000000000000000000000000000g"
+        .parse::<Snowflake128>()
+        .is_err());
+    assert!("+000000000000000000000000000000f"
+        .parse::<Snowflake128>()
+        .is_err());
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "sqlite")]
+
+use std::fs;
+use std::io::ErrorKind;
+use std::thread;
+use std::time::Duration;
+
+use snowflake::{SnowflakeError, WorkerRegistry};
+
+fn registry_path(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("snowflake-{}-{}.db", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn test_registry_leases() {
+    let path = registry_path("leases");
+    let registry = WorkerRegistry::sqlite(&path)
+        .unwrap()
+        .with_machine_ids(0..2);
+
+    let first = registry.acquire("web-1").unwrap();
+    let second = WorkerRegistry::sqlite(&path)
+        .unwrap()
+        .with_machine_ids(0..2)
+        .acquire("web-2")
+        .unwrap();
+    assert_eq!(first.machine_id(), 0);
+    assert_eq!(second.machine_id(), 1);
+    assert_eq!(
+        registry.acquire("web-3").unwrap_err().kind(),
+        ErrorKind::AddrInUse
+    );
+
+    let claims = registry.claims().unwrap();
+    assert_eq!(claims.len(), 2);
+    assert_eq!(claims[1].hostname, "web-2");
+
+    first.renew().unwrap();
+    drop(first);
+    assert_eq!(registry.acquire("web-3").unwrap().machine_id(), 0);
+
+    drop(second);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_registry_reaps_stale_claims() {
+    let path = registry_path("stale");
+    let registry = WorkerRegistry::sqlite(&path)
+        .unwrap()
+        .with_machine_ids(0..1)
+        .with_stale_after(Duration::from_millis(50));
+
+    let stale = registry.acquire("web-1").unwrap();
+    let mut id_generator = stale.generator();
+    id_generator.generate().unwrap();
+    thread::sleep(Duration::from_millis(100));
+
+    // Past the stale timeout without heartbeat, even before anyone reaps.
+    assert_eq!(id_generator.generate(), Err(SnowflakeError::LeaseLost(0)));
+
+    let fresh = registry.acquire("web-2").unwrap();
+    assert_eq!(fresh.machine_id(), 0);
+    assert_eq!(stale.renew().unwrap_err().kind(), ErrorKind::NotFound);
+
+    // The reaped lease doesn't remove the new claim when dropped.
+    drop(stale);
+    assert_eq!(registry.claims().unwrap()[0].hostname, "web-2");
+
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(registry.reap_stale().unwrap(), 1);
+    drop(fresh);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "don't fit the layout")]
+fn test_registry_rejects_machine_ids_past_layout() {
+    let path = registry_path("layout");
+    let _ = WorkerRegistry::sqlite(&path)
+        .unwrap()
+        .with_machine_ids(0..2048);
+}
@@ -0,0 +1,43 @@
+use snowflake::{Layout, SnowflakeError, SnowflakeIdGenerator};
+
+#[test]
+fn test_generate_tagged() {
+    let layout = Layout::default().with_shard_bits(2).with_tag_bits(3);
+    let mut id_generator = SnowflakeIdGenerator::new(0, 7).with_layout(layout);
+
+    assert_eq!(layout.tag_shift(), 17);
+    assert_eq!(layout.tag_mask(), 0x7 << 17);
+
+    for tag in 0..8 {
+        let id = id_generator.generate_tagged(tag).unwrap().0;
+
+        assert_eq!(layout.tag_of(id), tag);
+        assert_eq!(layout.shard_key().shard_of(id), 0);
+        assert_eq!(layout.decode(id).machine_bits & 0x1F, 7);
+    }
+
+    assert_eq!(Layout::default().tag_of(i64::MAX), 0);
+}
+
+#[test]
+fn test_generate_tagged_errors() {
+    let layout = Layout::default().with_tag_bits(3);
+    let mut id_generator = SnowflakeIdGenerator::new(1, 2).with_layout(layout);
+
+    assert_eq!(
+        id_generator.generate_tagged(8),
+        Err(SnowflakeError::InvalidTag(8))
+    );
+
+    let mut overlapping = SnowflakeIdGenerator::new(31, 31).with_layout(layout);
+    assert_eq!(
+        overlapping.generate_tagged(1),
+        Err(SnowflakeError::InvalidMachineId(0x3FF))
+    );
+}
+
+#[test]
+#[should_panic(expected = "tag_bits don't fit the machine bits")]
+fn test_tag_bits_overflow() {
+    Layout::default().with_shard_bits(4).with_tag_bits(7);
+}
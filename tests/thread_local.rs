@@ -0,0 +1,44 @@
+use std::thread;
+
+use snowflake::{set_thread_local_machine_bits, thread_local_generate};
+
+#[test]
+fn test_thread_local_generate() {
+    set_thread_local_machine_bits(0x2A0);
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            thread::spawn(|| {
+                (0..10000)
+                    .map(|_| thread_local_generate())
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    let mut ids: Vec<i64> = handles
+        .into_iter()
+        .flat_map(|handle| handle.join().unwrap())
+        .collect();
+
+    ids.sort();
+    ids.dedup();
+
+    assert_eq!(80000, ids.len());
+}
+
+#[test]
+fn test_thread_local_reuses_indexes_of_exited_threads() {
+    let mut ids: Vec<i64> = (0..64)
+        .flat_map(|_| {
+            thread::spawn(|| (0..100).map(|_| thread_local_generate()).collect::<Vec<_>>())
+                .join()
+                .unwrap()
+        })
+        .collect();
+
+    ids.sort();
+    ids.dedup();
+
+    assert_eq!(6400, ids.len());
+}
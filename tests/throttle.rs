@@ -0,0 +1,28 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use snowflake::{SnowflakeIdGenerator, ThrottledGenerator, TimeSource};
+
+static CLOCK: AtomicI64 = AtomicI64::new(1_700_000_000_000);
+
+#[test]
+fn test_throttled_generate() {
+    let ip = "102.65.2.123".to_string();
+    let id_generator =
+        SnowflakeIdGenerator::new_from_ip(ip).with_time_source(TimeSource::Manual(&CLOCK));
+    let mut throttled = ThrottledGenerator::new(id_generator, 100);
+
+    let issued = (0..1000).filter_map(|_| throttled.generate()).count();
+    assert_eq!(issued, 100);
+
+    CLOCK.fetch_add(50, Ordering::SeqCst);
+
+    let refilled = (0..1000).filter_map(|_| throttled.generate()).count();
+    assert_eq!(refilled, 5);
+    assert_eq!(throttled.generator().stats().clock_anomalies, 0);
+}
+
+#[test]
+#[should_panic(expected = "ids_per_second overflows the bucket")]
+fn test_throttled_rate_overflow() {
+    ThrottledGenerator::new(SnowflakeIdGenerator::new(1, 1), u64::MAX);
+}
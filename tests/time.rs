@@ -0,0 +1,27 @@
+#![cfg(feature = "time")]
+
+use snowflake::{BackfillGenerator, SnowflakeId, SnowflakeIdGenerator};
+use time::{Duration, OffsetDateTime};
+
+#[test]
+fn test_time_crate_timestamps() {
+    let timestamp = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+
+    let id = SnowflakeId(42).with_timestamp(timestamp);
+    assert_eq!(id.timestamp(), 1_700_000_000_000);
+    assert_eq!(
+        id.offset(Duration::milliseconds(-1500)).timestamp(),
+        1_699_999_998_500
+    );
+
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    let now = OffsetDateTime::now_utc();
+    let generated = id_generator.generate_at(now).unwrap();
+    assert_eq!(
+        generated.timestamp() as i128,
+        now.unix_timestamp_nanos() / 1_000_000
+    );
+
+    let mut backfill = BackfillGenerator::new(0x3FF);
+    assert_ne!(backfill.generate(timestamp), backfill.generate(timestamp));
+}
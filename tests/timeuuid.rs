@@ -0,0 +1,49 @@
+#![cfg(feature = "uuid")]
+
+use snowflake::{SnowflakeId, SnowflakeIdGenerator};
+use uuid::Uuid;
+
+// The 60 bit timestamp of a UUIDv1, the order Cassandra sorts timeuuids in.
+fn ticks(uuid: Uuid) -> u64 {
+    let value = uuid.as_u128();
+    ((value >> 64) as u64 & 0xFFF) << 48
+        | ((value >> 80) as u64 & 0xFFFF) << 32
+        | (value >> 96) as u64
+}
+
+#[test]
+fn test_timeuuid_round_trip_preserves_order() {
+    let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+
+    let ids: Vec<SnowflakeId> = (0..10000)
+        .map(|_| SnowflakeId(id_generator.real_time_generate()))
+        .collect();
+
+    for pair in ids.windows(2) {
+        assert!(ticks(pair[0].to_timeuuid().unwrap()) < ticks(pair[1].to_timeuuid().unwrap()));
+    }
+
+    for id in ids {
+        let timeuuid = id.to_timeuuid().unwrap();
+        assert_eq!(SnowflakeId::try_from_timeuuid(timeuuid), Some(id));
+    }
+}
+
+#[test]
+fn test_negative_id_timeuuid() {
+    assert_eq!(SnowflakeId(-1).to_timeuuid(), None);
+    assert_eq!(SnowflakeId(i64::MIN).to_timeuuid(), None);
+}
+
+#[test]
+fn test_foreign_timeuuid() {
+    // 2023-08-20T19:32:53.653Z, node 02:42:ac:12:00:02.
+    let uuid = Uuid::parse_str("5b6962dd-3f90-11ee-be56-0242ac120002").unwrap();
+    let id = SnowflakeId::try_from_timeuuid(uuid).unwrap();
+
+    assert_eq!(id.timestamp(), 1_692_559_973_653);
+    assert_eq!(id.machine_bits(), 0x002);
+
+    let later = Uuid::parse_str("5b6962de-3f90-11ee-be56-0242ac120002").unwrap();
+    assert!(SnowflakeId::try_from_timeuuid(later).unwrap() >= id);
+}
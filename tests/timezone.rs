@@ -0,0 +1,51 @@
+#![cfg(feature = "timezone")]
+
+use chrono::{TimeZone, Timelike, Utc};
+use chrono_tz::America::New_York;
+use chrono_tz::Asia::Kolkata;
+use chrono_tz::UTC;
+use snowflake::{Layout, Snowflake, SnowflakeIdGenerator};
+
+#[test]
+fn test_datetime_in() {
+    let snowflake = Layout::default().decode(1_717_243_200_123 << 22);
+
+    let utc = snowflake.datetime_in(UTC);
+    assert_eq!(utc, Utc.timestamp_millis_opt(1_717_243_200_123).unwrap());
+
+    let kolkata = snowflake.datetime_in(Kolkata);
+    assert_eq!(kolkata, utc);
+    assert_eq!((kolkata.hour(), kolkata.minute()), (17, 30));
+}
+
+#[test]
+fn test_daylight_saving() {
+    let winter = Snowflake {
+        timestamp: 1_704_110_400_000,
+        machine_bits: 0,
+        idx: 0,
+    };
+    let summer = Snowflake {
+        timestamp: 1_719_835_200_000,
+        ..winter
+    };
+
+    assert_eq!(
+        winter.datetime_in(New_York).to_rfc3339(),
+        "2024-01-01T07:00:00-05:00"
+    );
+    assert_eq!(
+        summer.datetime_in(New_York).to_rfc3339(),
+        "2024-07-01T08:00:00-04:00"
+    );
+}
+
+#[test]
+fn test_generated() {
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    let id = id_generator.real_time_generate();
+
+    let created = Layout::default().decode(id).datetime_in(New_York);
+
+    assert_eq!(created.timestamp_millis(), id_generator.last_time_millis);
+}
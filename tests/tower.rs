@@ -0,0 +1,32 @@
+#![cfg(feature = "tower")]
+
+use std::convert::Infallible;
+
+use http::{Request, Response};
+use snowflake::tower::{RequestId, RequestIdLayer, REQUEST_ID_HEADER};
+use snowflake::{AsyncGenerator, SnowflakeIdGenerator};
+use tower::{service_fn, Layer, ServiceExt};
+
+#[tokio::test]
+async fn test_request_id_layer() {
+    let generator = AsyncGenerator::new(SnowflakeIdGenerator::new(1, 1));
+    let service = RequestIdLayer::new(generator).layer(service_fn(|req: Request<()>| async move {
+        let RequestId(id) = *req.extensions().get::<RequestId>().unwrap();
+        Ok::<_, Infallible>(Response::new(id.0))
+    }));
+
+    let mut seen = Vec::new();
+    for _ in 0..2 {
+        let res = service.clone().oneshot(Request::new(())).await.unwrap();
+        let header: i64 = res.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+
+        assert_eq!(header, *res.body());
+        seen.push(header);
+    }
+
+    assert!(seen[0] < seen[1]);
+}
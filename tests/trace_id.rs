@@ -0,0 +1,27 @@
+use snowflake::{get_time_millis, SnowflakeIdGenerator, TraceIdGenerator};
+
+#[test]
+fn test_trace_ids() {
+    let mut trace_ids = TraceIdGenerator::new(SnowflakeIdGenerator::new(3, 9));
+    let before = get_time_millis();
+
+    let first = trace_ids.trace_id();
+    let second = trace_ids.trace_id();
+
+    assert_ne!(first[8..], second[8..]);
+    assert!(first < second);
+
+    let decoded = trace_ids.decode_trace_id(first);
+    assert_eq!(3 << 5 | 9, decoded.machine_bits);
+    assert!(decoded.timestamp >= before);
+}
+
+#[test]
+fn test_span_ids() {
+    let mut trace_ids = TraceIdGenerator::new(SnowflakeIdGenerator::new(3, 9));
+
+    let span_id = trace_ids.span_id();
+    assert_ne!([0; 8], span_id);
+    assert!(span_id < trace_ids.span_id());
+    assert_eq!(3 << 5 | 9, trace_ids.decode_span_id(span_id).machine_bits);
+}
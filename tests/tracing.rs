@@ -0,0 +1,84 @@
+#![cfg(feature = "tracing")]
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use snowflake::SnowflakeIdGenerator;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+use tracing_core::span::Current;
+
+// Collects every i64 recorded on any span.
+struct Recorder(Arc<Mutex<Vec<(String, i64)>>>);
+
+impl Visit for Recorder {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0
+            .lock()
+            .unwrap()
+            .push((field.name().to_owned(), value));
+    }
+
+    fn record_debug(&mut self, _: &Field, _: &dyn fmt::Debug) {}
+}
+
+struct RecordingSubscriber {
+    recorded: Arc<Mutex<Vec<(String, i64)>>>,
+    entered: Mutex<Option<&'static Metadata<'static>>>,
+    metadata: Mutex<Option<&'static Metadata<'static>>>,
+}
+
+impl Subscriber for RecordingSubscriber {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        *self.metadata.lock().unwrap() = Some(attrs.metadata());
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, values: &Record<'_>) {
+        values.record(&mut Recorder(self.recorded.clone()));
+    }
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event<'_>) {}
+
+    fn enter(&self, _: &Id) {
+        *self.entered.lock().unwrap() = *self.metadata.lock().unwrap();
+    }
+
+    fn exit(&self, _: &Id) {
+        *self.entered.lock().unwrap() = None;
+    }
+
+    fn current_span(&self) -> Current {
+        match *self.entered.lock().unwrap() {
+            Some(metadata) => Current::new(Id::from_u64(1), metadata),
+            None => Current::none(),
+        }
+    }
+}
+
+#[test]
+fn test_traced_generate() {
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+    let subscriber = RecordingSubscriber {
+        recorded: recorded.clone(),
+        entered: Mutex::new(None),
+        metadata: Mutex::new(None),
+    };
+
+    let id = tracing::subscriber::with_default(subscriber, || {
+        let span = tracing::info_span!("create", snowflake_id = tracing::field::Empty);
+        span.in_scope(|| SnowflakeIdGenerator::new(1, 1).traced_generate())
+    });
+
+    assert_eq!(
+        vec![("snowflake_id".to_owned(), id)],
+        *recorded.lock().unwrap()
+    );
+}
@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+use std::mem::size_of;
+
+use snowflake::{SnowflakeError, SnowflakeId, SnowflakeIdGenerator, TypedId};
+
+struct User;
+struct Order;
+
+type UserId = TypedId<User>;
+type OrderId = TypedId<Order>;
+
+#[test]
+fn test_typed_id() {
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    let first = UserId::from(id_generator.real_time_generate());
+    let second = UserId::from(id_generator.real_time_generate());
+
+    assert!(first < second);
+    assert_eq!(size_of::<UserId>(), size_of::<i64>());
+    assert_eq!(SnowflakeId::from(first), first.id());
+    assert_eq!(i64::from(first), first.get());
+    assert_eq!(first.to_string(), first.get().to_string());
+    assert_eq!(format!("{:?}", UserId::from(42)), "TypedId(42)");
+
+    let ids: HashSet<UserId> = vec![first, second, first].into_iter().collect();
+    assert_eq!(ids.len(), 2);
+
+    let order: OrderId = first.cast();
+    assert_eq!(order.get(), first.get());
+}
+
+#[test]
+fn test_typed_id_from_str() {
+    assert_eq!("42".parse::<UserId>(), Ok(UserId::from(42)));
+    assert_eq!(
+        "forty-two".parse::<UserId>(),
+        Err(SnowflakeError::ParseError)
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_typed_id_serde() {
+    let id = UserId::from(6725650666386976769);
+
+    let json = serde_json::to_string(&id).unwrap();
+    assert_eq!(json, serde_json::to_string(&id.id()).unwrap());
+    assert_eq!(serde_json::from_str::<UserId>(&json).unwrap(), id);
+    assert_eq!(
+        serde_json::from_str::<UserId>("\"6725650666386976769\"").unwrap(),
+        id
+    );
+}
+
+#[cfg(feature = "sqlx")]
+#[test]
+fn test_typed_id_sqlx() {
+    fn stored_as<DB, X>()
+    where
+        DB: sqlx::Database,
+        X: sqlx::Type<DB> + for<'q> sqlx::Encode<'q, DB> + for<'r> sqlx::Decode<'r, DB>,
+    {
+    }
+
+    // Compiles if any database storing `i64` stores typed ids.
+    #[allow(dead_code)]
+    fn stores_typed_ids<DB>()
+    where
+        DB: sqlx::Database,
+        i64: sqlx::Type<DB> + for<'q> sqlx::Encode<'q, DB> + for<'r> sqlx::Decode<'r, DB>,
+    {
+        stored_as::<DB, UserId>();
+    }
+}
@@ -0,0 +1,35 @@
+#![cfg(feature = "ulid")]
+
+use snowflake::{IdGenerator, SnowflakeId, SnowflakeIdGenerator, UlidGenerator};
+
+#[test]
+fn test_ulid_round_trip() {
+    let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+
+    let ids: Vec<SnowflakeId> = (0..10000)
+        .map(|_| SnowflakeId(id_generator.real_time_generate()))
+        .collect();
+    let ulids: Vec<_> = ids.iter().map(|id| id.to_ulid()).collect();
+
+    let mut sorted = ulids.clone();
+    sorted.sort();
+    assert_eq!(sorted, ulids);
+
+    for (id, ulid) in ids.iter().zip(ulids) {
+        assert_eq!(SnowflakeId::try_from_ulid(ulid), Some(*id));
+    }
+}
+
+#[test]
+fn test_ulid_generator() {
+    let mut ulids = UlidGenerator::new(SnowflakeIdGenerator::new(1, 7));
+
+    let generated: Vec<_> = (0..10000).map(|_| ulids.next_id().unwrap()).collect();
+
+    assert!(generated.windows(2).all(|pair| pair[0] < pair[1]));
+    let id = SnowflakeId::try_from_ulid(generated[0]).unwrap();
+    assert_eq!(
+        ulids.generator().reverse(id.0 as u64).machine_bits,
+        1 << 5 | 7
+    );
+}
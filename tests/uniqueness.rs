@@ -0,0 +1,39 @@
+use snowflake::{SnowflakeIdGenerator, UniquenessChecker};
+
+#[test]
+fn test_exact_checker() {
+    let mut checker = UniquenessChecker::exact();
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+
+    for _ in 0..10000 {
+        assert!(checker.insert(id_generator.real_time_generate()));
+    }
+    assert!(checker.is_clean());
+
+    assert!(
+        !checker.insert(id_generator.last_time_millis << 22 | 33 << 12 | id_generator.idx as i64)
+    );
+    assert_eq!(checker.checked(), 10001);
+    assert_eq!(checker.duplicates(), 1);
+}
+
+#[test]
+fn test_bloom_checker_finds_shared_machine_ids() {
+    let mut clean = UniquenessChecker::bloom(20000, 0.0001);
+    let mut id_generator = SnowflakeIdGenerator::new(1, 1);
+    for _ in 0..20000 {
+        clean.insert(id_generator.real_time_generate());
+    }
+    assert!(clean.duplicates() <= 5);
+
+    let mut checker = UniquenessChecker::bloom(20000, 0.0001);
+    let mut first = SnowflakeIdGenerator::new(1, 1);
+    let mut second = first;
+    for _ in 0..10000 {
+        checker.insert(first.lazy_generate());
+        checker.insert(second.lazy_generate());
+    }
+
+    assert!(checker.duplicates() >= 9995);
+    assert!(checker.first_duplicate().is_some());
+}
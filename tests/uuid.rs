@@ -0,0 +1,51 @@
+#![cfg(feature = "uuid")]
+
+use snowflake::{IdGenerator, SnowflakeId, SnowflakeIdGenerator, UuidV7Generator};
+
+#[test]
+fn test_uuid_v7_preserves_order() {
+    let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+
+    let ids: Vec<SnowflakeId> = (0..10000)
+        .map(|_| SnowflakeId(id_generator.real_time_generate()))
+        .collect();
+    let uuids: Vec<_> = ids.iter().map(|id| id.to_uuid_v7()).collect();
+
+    let mut sorted = uuids.clone();
+    sorted.sort();
+    assert_eq!(sorted, uuids);
+
+    for (id, uuid) in ids.iter().zip(uuids) {
+        assert_eq!(SnowflakeId::try_from_uuid_v7(uuid), Some(*id));
+    }
+}
+
+#[test]
+fn test_uuid_embedding() {
+    let mut id_generator = SnowflakeIdGenerator::new_from_ip("102.65.2.123".to_string());
+
+    for _ in 0..10000 {
+        let id = SnowflakeId(id_generator.real_time_generate());
+        let uuid = id.as_uuid();
+
+        assert_eq!(SnowflakeId::from_uuid(uuid), Some(id));
+        assert_eq!(SnowflakeId::try_from_uuid_v7(uuid), None);
+    }
+
+    assert_eq!(SnowflakeId::from_uuid(SnowflakeId(42).to_uuid_v7()), None);
+}
+
+#[test]
+fn test_uuid_v7_generator() {
+    let mut uuids = UuidV7Generator::new(SnowflakeIdGenerator::new(1, 7));
+
+    let generated: Vec<_> = (0..10000).map(|_| uuids.next_id().unwrap()).collect();
+
+    assert!(generated.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(generated.iter().all(|uuid| uuid.get_version_num() == 7));
+    let id = SnowflakeId::try_from_uuid_v7(generated[0]).unwrap();
+    assert_eq!(
+        uuids.generator().reverse(id.0 as u64).machine_bits,
+        1 << 5 | 7
+    );
+}
@@ -0,0 +1,75 @@
+use std::thread;
+use std::time::Duration;
+
+use snowflake::{Layout, SnowflakeError, WorkerIdPool};
+
+#[test]
+fn test_worker_id_pool_leases() {
+    let pool = WorkerIdPool::new(0..2);
+
+    let first = pool.acquire().unwrap();
+    let second = pool.acquire().unwrap();
+
+    assert_ne!(first.machine_id(), second.machine_id());
+    assert!(pool.acquire().is_none());
+
+    let machine_id = first.machine_id();
+    drop(first);
+
+    assert_eq!(pool.available(), 1);
+    thread::sleep(Duration::from_millis(2));
+    assert_eq!(pool.acquire().unwrap().machine_id(), machine_id);
+}
+
+#[test]
+fn test_worker_id_pool_quarantine() {
+    let pool = WorkerIdPool::new(0..3).with_quarantine(Duration::from_millis(50));
+
+    let first = pool.acquire().unwrap();
+    drop(first);
+
+    // Released ids go to the back of the queue.
+    assert_eq!(pool.acquire().unwrap().machine_id(), 1);
+    let third = pool.acquire().unwrap();
+    assert_eq!(third.machine_id(), 2);
+
+    // The only free id is still in quarantine.
+    assert!(pool.acquire().is_none());
+    thread::sleep(Duration::from_millis(60));
+    assert_eq!(pool.acquire().unwrap().machine_id(), 0);
+}
+
+#[test]
+fn test_worker_id_pool_layout_quarantine() {
+    let pool = WorkerIdPool::new(0..1).with_layout(Layout::SECONDS);
+
+    drop(pool.acquire().unwrap());
+    assert!(pool.acquire().is_none());
+}
+
+#[test]
+fn test_worker_id_lease_generator() {
+    let pool = WorkerIdPool::new(5..6);
+    let lease = pool.acquire().unwrap();
+
+    let mut id_generator = lease.generator();
+    assert_eq!(id_generator.machine_id(), 5);
+    let id = id_generator.generate().unwrap();
+    assert_eq!(id.machine_bits(), 5);
+
+    drop(lease);
+    assert!(!id_generator.is_valid());
+    assert_eq!(id_generator.generate(), Err(SnowflakeError::LeaseLost(5)));
+}
+
+#[test]
+#[should_panic(expected = "don't fit the layout")]
+fn test_worker_id_pool_rejects_wide_range() {
+    WorkerIdPool::new(1000..1030);
+}
+
+#[test]
+#[should_panic(expected = "don't fit the layout")]
+fn test_worker_id_pool_rejects_range_of_layout() {
+    WorkerIdPool::new(0..32).with_layout(Layout::JS_SAFE);
+}